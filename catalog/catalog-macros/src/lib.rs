@@ -8,9 +8,10 @@ use std::collections::hash_map::DefaultHasher;
 use std::default::default;
 use std::hash::{Hash, Hasher};
 
+use fnv::FnvHasher;
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::__private::TokenStream2;
 use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
@@ -44,7 +45,7 @@ fn ctor(crat: &Path, name: &Ident, body: &TokenStream2) -> TokenStream2 {
     let bytes = LitByteStr::new(&bytes, Span::call_site());
     quote! {
         #crat::reexport::cfg_if::cfg_if!(
-            if #[cfg(any(target_arch = "wasm32", target_arch = "wasi"))] {
+            if #[cfg(any(target_arch = "wasm32", target_os = "wasi"))] {
                 const _: () = {
                     use #crat::reexport::wasm_bindgen;
                     #[wasm_bindgen::prelude::wasm_bindgen]
@@ -66,6 +67,16 @@ fn ctor(crat: &Path, name: &Ident, body: &TokenStream2) -> TokenStream2 {
     }
 }
 
+/// Orders registrations for `Registry::new_sorted`: the module path and name of the item,
+/// then a hash of the item to tell apart items with the same name in different `const _` blocks.
+fn key(name: &Ident, input: &Item) -> TokenStream2 {
+    // FNV rather than `DefaultHasher`, whose output may change between Rust releases.
+    let mut hasher = FnvHasher::default();
+    input.to_token_stream().to_string().hash(&mut hasher);
+    let suffix = format!("::{} {:016X}", name, hasher.finish());
+    quote! { concat!(module_path!(), #suffix) }
+}
+
 enum CustomArg {
     Registry(Path),
     Lazy(LitBool),
@@ -162,11 +173,12 @@ fn register_impl(args: CustomArgs, input: Item) -> Result<TokenStream2, Error> {
     match &input {
         Item::Fn(f) => {
             let name = &f.sig.ident;
+            let key = key(name, &input);
             let ctored = ctor(
                 crat,
                 name,
                 &quote! {
-                    #crat::Registry::register(&#registry, |x| {
+                    #crat::Registry::register(&#registry, #key, |x| {
                         #crat::BuilderFrom::insert(x, #name());
                     })
                 },
@@ -193,11 +205,12 @@ fn register_impl(args: CustomArgs, input: Item) -> Result<TokenStream2, Error> {
                 if let Some(mutability) = mutability {
                     return Err(Error::new(mutability.span(), "Cannot use mutable statics."));
                 }
+                let key = key(ident, &input);
                 let ctored = ctor(
                     crat,
                     &ident,
                     &quote! {
-                        #crat::Registry::register(&#registry, |x| {
+                        #crat::Registry::register(&#registry, #key, |x| {
                             #crat::BuilderFrom::insert(x, #crat::LazyEntry::__private(&#ident))
                         })
                     },
@@ -217,11 +230,12 @@ fn register_impl(args: CustomArgs, input: Item) -> Result<TokenStream2, Error> {
                 })
             } else {
                 let name = &s.ident;
+                let key = key(name, &input);
                 let ctored = ctor(
                     crat,
                     &s.ident,
                     &quote! {
-                        #crat::Registry::register(&#registry, |x| {
+                        #crat::Registry::register(&#registry, #key, |x| {
                             #crat::BuilderFrom::insert(x, &#name)
                         })
                     },
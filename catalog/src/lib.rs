@@ -61,11 +61,12 @@ pub use catalog_macros::register;
 use cfg_if::cfg_if;
 use parking_lot::Mutex;
 use rand::prelude::SliceRandom;
-use rand::thread_rng;
+use rand::rngs::StdRng;
+use rand::{thread_rng, SeedableRng};
 use safe_cell::{SafeLazy, SafeOnceCell};
 
 cfg_if!(
-    if #[cfg(any(target_arch = "wasm32", target_arch = "wasi"))] {
+    if #[cfg(any(target_arch = "wasm32", target_os = "wasi"))] {
         #[path = "wasm_imp.rs"]
         mod imp;
     } else {
@@ -91,8 +92,9 @@ pub trait BuilderFrom<T> {
 }
 
 pub struct Registry<B: Builder> {
-    inputs: Mutex<Option<Vec<fn(&mut B)>>>,
+    inputs: Mutex<Option<Vec<(&'static str, fn(&mut B))>>>,
     output: SafeOnceCell<B::Output>,
+    sorted: bool,
 }
 
 pub struct LazyEntry<T: 'static> {
@@ -113,19 +115,40 @@ impl<T> LazyEntry<T> {
 }
 
 impl<B: Builder> Registry<B> {
+    /// A registry that inserts its entries in a random order, so that nothing can depend on the
+    /// order. Setting the `CATALOG_SEED` environment variable to a number makes the order
+    /// reproducible. A `CATALOG_SEED` that is not a number panics when the registry is first used.
     pub const fn new() -> Self {
         Registry {
             inputs: Mutex::new(Some(vec![])),
             output: SafeOnceCell::new(),
+            sorted: false,
+        }
+    }
+    /// A registry that inserts its entries ordered by the module path and name of the item that
+    /// registered them.
+    pub const fn new_sorted() -> Self {
+        Registry {
+            inputs: Mutex::new(Some(vec![])),
+            output: SafeOnceCell::new(),
+            sorted: true,
         }
     }
     #[doc(hidden)]
-    pub fn register(&self, entry: fn(&mut B)) {
+    pub fn register(&self, key: &'static str, entry: fn(&mut B)) {
         self.inputs
             .lock()
             .as_mut()
             .expect("Registry already initialized")
-            .push(entry);
+            .push((key, entry));
+    }
+}
+
+fn seed() -> Option<u64> {
+    let seed = std::env::var("CATALOG_SEED").ok()?;
+    match seed.parse() {
+        Ok(seed) => Some(seed),
+        Err(e) => panic!("Invalid CATALOG_SEED={:?}: {}", seed, e),
     }
 }
 
@@ -136,9 +159,15 @@ impl<B: Builder> Deref for Registry<B> {
         self.output.get_or_init(|| {
             imp::init();
             let mut vec = self.inputs.lock().take().unwrap();
-            vec.shuffle(&mut thread_rng());
+            vec.sort_by_key(|(key, _)| *key);
+            if !self.sorted {
+                match seed() {
+                    Some(seed) => vec.shuffle(&mut StdRng::seed_from_u64(seed)),
+                    None => vec.shuffle(&mut thread_rng()),
+                }
+            }
             let mut result = B::new();
-            for x in vec {
+            for (_, x) in vec {
                 x(&mut result);
             }
             result.build()
@@ -0,0 +1,31 @@
+#![deny(unused_must_use)]
+
+//! Sets an invalid `CATALOG_SEED`, so it runs as its own test binary where no other test can
+//! observe it.
+
+use catalog::{Builder, BuilderFrom, Registry};
+use catalog_macros::register;
+
+struct TestRegistryOrder(Vec<&'static str>);
+
+static TEST_REGISTRY_BAD_SEED: Registry<TestRegistryOrder> = Registry::new();
+
+impl Builder for TestRegistryOrder {
+    type Output = Vec<&'static str>;
+    fn new() -> Self { TestRegistryOrder(vec![]) }
+    fn build(self) -> Vec<&'static str> { self.0 }
+}
+
+impl BuilderFrom<&'static &'static str> for TestRegistryOrder {
+    fn insert(&mut self, element: &'static &'static str) { self.0.push(element) }
+}
+
+#[register(TEST_REGISTRY_BAD_SEED)]
+static BAD_SEED_0: &str = "0";
+
+#[test]
+#[should_panic(expected = "Invalid CATALOG_SEED=\"forty-two\"")]
+fn test_bad_seed() {
+    std::env::set_var("CATALOG_SEED", "forty-two");
+    let _ = &*TEST_REGISTRY_BAD_SEED;
+}
@@ -16,6 +16,8 @@ struct TestRegistry(HashMap<&'static str, &'static str>);
 
 struct TestRegistryLazy(Vec<&'static TestEntryLazy>);
 
+struct TestRegistryOrder(Vec<&'static str>);
+
 struct TestEntry {
     key: &'static str,
     value: &'static str,
@@ -29,6 +31,8 @@ static TEST_REGISTRY: Registry<TestRegistry> = Registry::new();
 
 static TEST_REGISTRY_LAZY: Registry<TestRegistryLazy> = Registry::new();
 
+static TEST_REGISTRY_SORTED: Registry<TestRegistryOrder> = Registry::new_sorted();
+
 impl Builder for TestRegistry {
     type Output = HashMap<&'static str, &'static str>;
     fn new() -> Self { TestRegistry(HashMap::new()) }
@@ -45,6 +49,12 @@ impl Builder for TestRegistryLazy {
     }
 }
 
+impl Builder for TestRegistryOrder {
+    type Output = Vec<&'static str>;
+    fn new() -> Self { TestRegistryOrder(vec![]) }
+    fn build(self) -> Vec<&'static str> { self.0 }
+}
+
 impl BuilderFrom<TestEntry> for TestRegistry {
     fn insert(&mut self, element: TestEntry) { self.0.insert(element.key, element.value); }
 }
@@ -53,6 +63,10 @@ impl BuilderFrom<&TestEntry> for TestRegistry {
     fn insert(&mut self, element: &TestEntry) { self.0.insert(element.key, element.value); }
 }
 
+impl BuilderFrom<&'static &'static str> for TestRegistryOrder {
+    fn insert(&mut self, element: &'static &'static str) { self.0.push(element) }
+}
+
 impl BuilderFrom<&'static TestEntryLazy> for TestRegistryLazy {
     fn insert(&mut self, element: &'static TestEntryLazy) { self.0.push(element) }
 }
@@ -85,11 +99,20 @@ static TEST_ENTRY_LAZY1: TestEntryLazy = TestEntryLazy::new();
 #[register(TEST_REGISTRY_LAZY, lazy = true)]
 static TEST_ENTRY_LAZY2: TestEntryLazy = TestEntryLazy::new();
 
+#[register(TEST_REGISTRY_SORTED)]
+static SORTED_B: &str = "b";
+
+#[register(TEST_REGISTRY_SORTED)]
+static SORTED_A: &str = "a";
+
 mod foo {
     use catalog_macros::register;
 
     use crate::TestEntry;
 
+    #[register(crate::TEST_REGISTRY_SORTED)]
+    static SORTED_C: &str = "c";
+
     #[register(crate::TEST_REGISTRY)]
     fn register_fn2() -> TestEntry {
         crate::TestEntry {
@@ -118,3 +141,9 @@ fn test() {
         .collect()
     );
 }
+
+#[wasm_bindgen_test::wasm_bindgen_test]
+#[test]
+fn test_sorted() {
+    assert_eq!(vec!["a", "b", "c"], *TEST_REGISTRY_SORTED);
+}
@@ -0,0 +1,44 @@
+#![deny(unused_must_use)]
+
+//! Sets `CATALOG_SEED`, so it runs as its own test binary where no other test can observe it.
+
+use catalog::{Builder, BuilderFrom, Registry};
+use catalog_macros::register;
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+struct TestRegistryOrder(Vec<&'static str>);
+
+static TEST_REGISTRY_SEEDED: Registry<TestRegistryOrder> = Registry::new();
+
+impl Builder for TestRegistryOrder {
+    type Output = Vec<&'static str>;
+    fn new() -> Self { TestRegistryOrder(vec![]) }
+    fn build(self) -> Vec<&'static str> { self.0 }
+}
+
+impl BuilderFrom<&'static &'static str> for TestRegistryOrder {
+    fn insert(&mut self, element: &'static &'static str) { self.0.push(element) }
+}
+
+#[register(TEST_REGISTRY_SEEDED)]
+static SEEDED_0: &str = "0";
+
+#[register(TEST_REGISTRY_SEEDED)]
+static SEEDED_1: &str = "1";
+
+#[register(TEST_REGISTRY_SEEDED)]
+static SEEDED_2: &str = "2";
+
+#[register(TEST_REGISTRY_SEEDED)]
+static SEEDED_3: &str = "3";
+
+#[test]
+fn test_seed() {
+    std::env::set_var("CATALOG_SEED", "42");
+    let mut expected = vec!["0", "1", "2", "3"];
+    expected.shuffle(&mut StdRng::seed_from_u64(42));
+    assert_ne!(vec!["0", "1", "2", "3"], expected);
+    assert_eq!(expected, *TEST_REGISTRY_SEEDED);
+}
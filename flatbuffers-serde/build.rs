@@ -1,6 +1,13 @@
+use std::env;
+
 fn main() {
     let mut success = true;
-    success &= flatc_build::build("schema/");
+    // The `builtin-flatc` feature generates the modules without needing a `flatc` executable.
+    if env::var_os("CARGO_FEATURE_BUILTIN_FLATC").is_some() {
+        success &= flatc_build::build_rust("schema/");
+    } else {
+        success &= flatc_build::build("schema/");
+    }
     if !success {
         std::process::exit(1);
     }
@@ -0,0 +1,112 @@
+use std::any::Any;
+use std::convert::TryInto;
+use std::mem::size_of;
+
+use flatbuffers::{FlatBufferBuilder, Follow, UOffsetT, UnionWIPOffset, VOffsetT, WIPOffset};
+
+use crate::any_generated::{AnyFlat, TypeTagHash};
+use crate::de::error::Error;
+use crate::de::verify::{check_table, out_of_bounds, FollowChecked};
+use crate::flat_util::Flat128;
+use crate::tag::TypeTag;
+
+/// Created by [`AnyFlat::deserialize_any`] for a type tag with no serde type registered, so that
+/// [`AnyFlat::serialize_any`] can write it out again.
+///
+/// Without knowing its type, the bytes that belong to the value cannot be told apart from the rest
+/// of the buffer. Everything the value refers to was written before the `AnyFlat` table, so only
+/// the bytes after that table are kept, which leaves out the table itself and whatever encloses
+/// it.
+#[derive(Clone, Debug)]
+pub struct UnknownFlat {
+    type_tag_hash: TypeTagHash,
+    /// The end of the buffer, padded at the front to a multiple of 16 bytes.
+    buf: Vec<u8>,
+    loc: usize,
+}
+
+impl<'a> AnyFlat<'a> {
+    /// Write `value` with the serde type registered in [`TYPE_TAGS`](crate::tag::TYPE_TAGS) for
+    /// its type, or write an [`UnknownFlat`] back out unchanged.
+    pub fn serialize_any<'b>(
+        fbb: &'b mut FlatBufferBuilder<'a>,
+        value: &dyn Any,
+    ) -> crate::ser::Result<WIPOffset<AnyFlat<'a>>> {
+        if let Some(unknown) = value.downcast_ref::<UnknownFlat>() {
+            let data = unknown.push(fbb);
+            return Ok(Self::create_raw(fbb, unknown.type_tag_hash, data));
+        }
+        let tag = TypeTag::lookup_native_id(value.type_id())
+            .filter(|tag| tag.serde_type_tag().is_some())
+            .ok_or(crate::ser::error::Error::MissingTypeTag(value.type_id()))?;
+        let data = tag.serde_type_tag().unwrap().serialize(fbb, value)?;
+        Ok(Self::create_raw(fbb, tag.type_tag_hash(), data))
+    }
+    /// Read the value with the serde type registered for its type tag, or an [`UnknownFlat`] if
+    /// there is none.
+    pub fn deserialize_any(&self) -> crate::de::Result<Box<dyn Any>> {
+        let (buf, loc) = (self.table.buf, self.table.loc);
+        check_table(buf, loc)?;
+        let hash = self.slot(AnyFlat::VT_TYPE_TAG_HASH, size_of::<TypeTagHash>())?;
+        let hash = *TypeTagHash::follow(buf, hash.ok_or(Error::MissingTypeTagHash)?);
+        let data = self.slot(AnyFlat::VT_DATA, size_of::<UOffsetT>())?;
+        let data = data.ok_or(Error::MissingData)?;
+        let data = data.saturating_add(UOffsetT::follow_checked(buf, data)? as usize);
+        // Everything the value refers to was written before this table, so it lies after it.
+        let start = loc + self.table.vtable().object_inline_num_bytes();
+        if data < start || data >= buf.len() {
+            return Err(out_of_bounds(data, data));
+        }
+        match TypeTag::lookup_hash(hash) {
+            Ok(tag) => match tag.serde_type_tag() {
+                Some(serde) => serde.deserialize(buf, data),
+                // A flatbuffers type without a serde impl, such as a nested `AnyFlat`.
+                None => Ok(Box::new(UnknownFlat::new(hash, buf, start, data))),
+            },
+            // Colliding hashes panic when `TYPE_TAGS` is built, so the lookup only fails for a
+            // hash that no type registered.
+            Err(_) => Ok(Box::new(UnknownFlat::new(hash, buf, start, data))),
+        }
+    }
+    /// The location of the field in `slot`, checked to leave room for `size` bytes inside the
+    /// table, which [`check_table`] found inside the buffer, or `None` if it is absent.
+    fn slot(&self, slot: VOffsetT, size: usize) -> crate::de::Result<Option<usize>> {
+        let vtable = self.table.vtable();
+        match vtable.get(slot) as usize {
+            0 => Ok(None),
+            offset if offset + size > vtable.object_inline_num_bytes() => Err(out_of_bounds(
+                self.table.loc + offset,
+                self.table.loc + offset + size,
+            )),
+            offset => Ok(Some(self.table.loc + offset)),
+        }
+    }
+}
+
+impl UnknownFlat {
+    /// Keep `buf[start..]`, which holds the value at `loc`, where `start <= loc < buf.len()`.
+    /// Flatbuffers are aligned relative to their end, so the padding goes at the front.
+    fn new(type_tag_hash: TypeTagHash, buf: &[u8], start: usize, loc: usize) -> Self {
+        let len = (buf.len() - start).next_multiple_of(16);
+        let padding = len - (buf.len() - start);
+        let mut copy = vec![0; padding];
+        copy.extend_from_slice(&buf[start..]);
+        UnknownFlat {
+            type_tag_hash,
+            buf: copy,
+            loc: loc - start + padding,
+        }
+    }
+    pub fn type_tag_hash(&self) -> TypeTagHash { self.type_tag_hash }
+    /// Copy the kept bytes into `fbb` in 16-byte chunks, so that they start at the largest
+    /// alignment and every offset within them still points to the same place.
+    fn push(&self, fbb: &mut FlatBufferBuilder) -> WIPOffset<UnionWIPOffset> {
+        let mut start = 0;
+        for chunk in self.buf.chunks_exact(16).rev() {
+            start = fbb
+                .push(Flat128(u128::from_le_bytes(chunk.try_into().unwrap())))
+                .value();
+        }
+        WIPOffset::new(start - self.loc as u32)
+    }
+}
@@ -8,7 +8,6 @@ use flatbuffers::{
     FlatBufferBuilder, Follow, ForwardsUOffset, InvalidFlatbuffer, Push, Table,
     TableUnfinishedWIPOffset, UnionWIPOffset, VOffsetT, Verifiable, Verifier, WIPOffset,
 };
-use registry::registry;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
@@ -23,7 +22,7 @@ use crate::ser::wrapper::{Serializer, Stack};
 use crate::tag::{HasFlatTypeTag, HasTypeTag, TypeTag};
 
 pub struct AnyFlat<'a> {
-    table: flatbuffers::Table<'a>,
+    pub(crate) table: flatbuffers::Table<'a>,
 }
 
 #[derive(Debug)]
@@ -65,7 +64,7 @@ impl<'a> AnyFlat<'a> {
             .get::<TypeTagHash>(AnyFlat::VT_TYPE_TAG_HASH, None)
     }
     #[inline]
-    unsafe fn data_raw<T: Follow<'a> + 'a>(&self) -> Option<T::Inner> {
+    pub(crate) unsafe fn data_raw<T: Follow<'a> + 'a>(&self) -> Option<T::Inner> {
         println!("{:?} {}", self.table.loc, type_name::<T>());
         self.table.get::<ForwardsUOffset<T>>(AnyFlat::VT_DATA, None)
     }
@@ -95,6 +94,8 @@ impl<'a> AnyFlat<'a> {
     ) -> Result<T::Inner, TypeMismatch> {
         unsafe { self.follow_raw::<T>(T::type_tag()) }
     }
+    /// # Safety
+    /// `T` must be the flatbuffers type registered under `to`.
     pub unsafe fn follow_raw<T: Follow<'a> + 'a>(
         &self,
         to: &'static TypeTag,
@@ -119,7 +120,7 @@ impl<'a> AnyFlat<'a> {
     }
 }
 
-impl<'a> Push for &'a TypeTagHash {
+impl Push for &TypeTagHash {
     type Output = TypeTagHash;
     fn push(&self, dst: &mut [u8], _rest: &[u8]) { dst.copy_from_slice(&self.0) }
 }
@@ -171,7 +172,7 @@ impl Verifiable for AnyFlat<'_> {
     }
 }
 
-impl<'a> Verifiable for TypeTagHash {
+impl Verifiable for TypeTagHash {
     fn run_verifier(v: &mut Verifier, pos: usize) -> Result<(), InvalidFlatbuffer> {
         v.in_buffer::<TypeTagHash>(pos)
     }
@@ -199,13 +200,11 @@ impl Debug for TypeTagHash {
 
 unsafe impl<'a> Covariant for AnyFlat<'a> {
     type Super<'b>
+        = AnyFlat<'b>
     where
-        AnyFlat<'a>: 'b,
-    = AnyFlat<'b>;
+        AnyFlat<'a>: 'b;
 }
 
 fn test(x: FlatBuffer<AnyFlat<'static>>) -> impl Debug { x }
 
-registry!(
-    value crate::tag::TYPE_TAGS => type_tag!(type AnyFlat<'a>, name "flatbuffer_serde::any::AnyFlat", kinds [flat]);
-);
+type_tag!(type AnyFlat<'a>, name "flatbuffer_serde::any::AnyFlat", kinds [flat]);
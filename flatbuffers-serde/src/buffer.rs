@@ -2,18 +2,37 @@ use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 
 use flatbuffers::{
-    root, root_unchecked, FlatBufferBuilder, Follow, ForwardsUOffset, InvalidFlatbuffer,
+    root, root_unchecked, FlatBufferBuilder, Follow, ForwardsUOffset, InvalidFlatbuffer, UOffsetT,
     Verifiable, Verifier, VerifierOptions, WIPOffset,
 };
+use serde::{Deserialize, Serialize};
 
+use crate::de::deserialize_raw;
+use crate::de::error::Error;
+use crate::de::verify::FollowChecked;
 use crate::flat_util::FlatUnion;
+use crate::ser::content::Content;
+use crate::ser::{serialize_raw, serialize_raw_with, Options};
 use crate::vec_slice::VecSlice;
+use crate::view::{locate_scalar, ScalarKind};
 
 pub struct FlatBuffer<T> {
     data: VecSlice,
     phantom: PhantomData<T>,
 }
 
+impl<T: Verifiable> FlatBuffer<T> {
+    pub fn from_vec_slice(data: VecSlice) -> Result<Self, InvalidFlatbuffer> {
+        let opts = VerifierOptions::default();
+        let mut v = Verifier::new(&opts, data.as_ref());
+        <ForwardsUOffset<T>>::run_verifier(&mut v, 0)?;
+        Ok(FlatBuffer {
+            data,
+            phantom: PhantomData,
+        })
+    }
+}
+
 impl<T> FlatBuffer<T> {
     pub fn new<'a>(mut builder: FlatBufferBuilder<'a>, finish: WIPOffset<T>) -> FlatBuffer<T> {
         builder.finish(finish, None);
@@ -28,18 +47,6 @@ impl<T> FlatBuffer<T> {
         let root = f(&mut fbb);
         Self::new(fbb, root)
     }
-    pub fn from_vec_slice(data: VecSlice) -> Result<Self, InvalidFlatbuffer>
-    where
-        T: Verifiable,
-    {
-        let opts = VerifierOptions::default();
-        let mut v = Verifier::new(&opts, data.as_ref());
-        <ForwardsUOffset<T>>::run_verifier(&mut v, 0)?;
-        Ok(FlatBuffer {
-            data,
-            phantom: PhantomData,
-        })
-    }
     pub fn into_inner(self) -> VecSlice { self.data }
     pub fn as_slice(&self) -> &[u8] { self.data.as_ref() }
     pub fn root<'a>(&'a self) -> T::Inner
@@ -57,6 +64,96 @@ impl<T> FlatBuffer<T> {
     }
 }
 
+/// Marks a [`FlatBuffer`] whose root was written by [`serialize_raw`] from a `T`.
+pub struct Serde<T>(PhantomData<T>);
+
+impl<'a, T> Follow<'a> for Serde<T> {
+    type Inner = FlatUnion<'a>;
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner { FlatUnion { buf, loc } }
+}
+
+/// The type and little-endian bytes of `content` if it is a fixed-size scalar, looking through
+/// newtypes as the serializer does.
+fn scalar_bytes(content: Content) -> crate::de::Result<(ScalarKind, Vec<u8>)> {
+    Ok(match content {
+        Content::Bool(x) => (ScalarKind::Bool, vec![x as u8]),
+        Content::I8(x) => (ScalarKind::I8, x.to_le_bytes().to_vec()),
+        Content::I16(x) => (ScalarKind::I16, x.to_le_bytes().to_vec()),
+        Content::I32(x) => (ScalarKind::I32, x.to_le_bytes().to_vec()),
+        Content::I64(x) => (ScalarKind::I64, x.to_le_bytes().to_vec()),
+        Content::I128(x) => (ScalarKind::I128, x.to_le_bytes().to_vec()),
+        Content::U8(x) => (ScalarKind::U8, x.to_le_bytes().to_vec()),
+        Content::U16(x) => (ScalarKind::U16, x.to_le_bytes().to_vec()),
+        Content::U32(x) => (ScalarKind::U32, x.to_le_bytes().to_vec()),
+        Content::U64(x) => (ScalarKind::U64, x.to_le_bytes().to_vec()),
+        Content::U128(x) => (ScalarKind::U128, x.to_le_bytes().to_vec()),
+        Content::F32(x) => (ScalarKind::F32, x.0.to_le_bytes().to_vec()),
+        Content::F64(x) => (ScalarKind::F64, x.0.to_le_bytes().to_vec()),
+        Content::Char(x) => (ScalarKind::Char, (x as u32).to_le_bytes().to_vec()),
+        Content::NewtypeStruct(_, x) => return scalar_bytes(*x),
+        _ => return Err(Error::Unsupported),
+    })
+}
+
+impl<T: Serialize> FlatBuffer<Serde<T>> {
+    pub fn serialize(value: &T) -> crate::ser::Result<Self> {
+        let mut fbb = FlatBufferBuilder::new();
+        let root = serialize_raw(&mut fbb, value)?;
+        Ok(Self::new(fbb, WIPOffset::new(root.value())))
+    }
+    pub fn serialize_with(value: &T, options: &Options) -> crate::ser::Result<Self> {
+        let mut fbb = FlatBufferBuilder::new();
+        let root = serialize_raw_with(&mut fbb, value, options)?;
+        Ok(Self::new(fbb, WIPOffset::new(root.value())))
+    }
+}
+
+impl<T: for<'de> Deserialize<'de>> FlatBuffer<Serde<T>> {
+    /// Check that `data` holds a `T` by deserializing one, with every offset, vtable and vector
+    /// length checked against the bounds of `data`, so that bytes from an untrusted source fail
+    /// here with [`Error::Invalid`]. The `T` itself is dropped.
+    pub fn from_vec_slice(data: VecSlice) -> crate::de::Result<Self> {
+        let root = UOffsetT::follow_checked(data.as_ref(), 0)? as usize;
+        deserialize_raw::<T>(data.as_ref(), root)?;
+        Ok(FlatBuffer {
+            data,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<T> FlatBuffer<Serde<T>> {
+    /// Overwrite the fixed-size scalar at `path`, such as `a.b[3].c`, with `value`, which must be
+    /// of the same type as the field. Fails with [`Error::MissingSlot`] if the slot was never
+    /// written, which is the case for zero values that are not in a pinned slot.
+    pub fn mutate_field<U: Serialize>(&mut self, path: &str, value: &U) -> crate::de::Result<()>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let (kind, bytes) = scalar_bytes(Content::new(value)?)?;
+        let (loc, field) = locate_scalar::<T>(self.root(), path)?
+            .ok_or_else(|| Error::MissingSlot(path.to_string()))?;
+        if field != kind {
+            return Err(Error::ScalarMismatch {
+                path: path.to_string(),
+                field,
+                value: kind,
+            });
+        }
+        self.data.as_mut()[loc..loc + bytes.len()].copy_from_slice(&bytes);
+        Ok(())
+    }
+    pub fn deserialize<'a>(&'a self) -> crate::de::Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        let root = self.root();
+        deserialize_raw(root.buf, root.loc)
+    }
+}
+
+/// # Safety
+/// `Super<'b>` must be `Self` with its lifetime shortened to `'b`.
 pub unsafe trait Covariant {
     type Super<'b>
     where
@@ -4,34 +4,65 @@ use flatbuffers::InvalidFlatbuffer;
 
 use crate::any_generated::{TypeMismatch, TypeTagHash};
 use crate::tag::TypeTag;
+use crate::view::ScalarKind;
 
 #[derive(Debug)]
 pub enum Error {
     Custom(String),
+    BadBool,
     BadChar,
+    BadUtf8,
     Unsupported,
     MissingEnumValue,
     MissingTypeTagHash,
     MissingData,
     TypeMismatch(TypeMismatch),
+    BadPath(String),
+    MissingFieldId(String),
+    Invalid(InvalidFlatbuffer),
+    MissingSlot(String),
+    ScalarMismatch {
+        path: String,
+        field: ScalarKind,
+        value: ScalarKind,
+    },
+    Serialize(crate::ser::error::Error),
 }
 
 impl From<TypeMismatch> for Error {
     fn from(x: TypeMismatch) -> Self { Error::TypeMismatch(x) }
 }
 
+impl From<crate::ser::error::Error> for Error {
+    fn from(x: crate::ser::error::Error) -> Self { Error::Serialize(x) }
+}
+
+impl From<InvalidFlatbuffer> for Error {
+    fn from(x: InvalidFlatbuffer) -> Self { Error::Invalid(x) }
+}
+
 impl std::error::Error for Error {}
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Custom(x) => write!(f, "custom deserializer error: {}", x),
+            Error::BadBool => write!(f, "bad bool value"),
             Error::BadChar => write!(f, "bad char value"),
+            Error::BadUtf8 => write!(f, "string is not valid UTF-8"),
             Error::Unsupported => write!(f, "unsupported operation"),
             Error::MissingEnumValue => write!(f, "enum tag was present, but value was not"),
             Error::TypeMismatch(x) => write!(f, "{}", x),
             Error::MissingTypeTagHash => write!(f, "Missing AnyFlat::type_tag_hash field"),
             Error::MissingData => write!(f, "Missing AnyFlat::data field"),
+            Error::BadPath(x) => write!(f, "bad path: {}", x),
+            Error::MissingFieldId(x) => write!(f, "no field id for `{}`", x),
+            Error::Invalid(x) => write!(f, "invalid flatbuffer: {}", x),
+            Error::MissingSlot(x) => write!(f, "`{}` was never written", x),
+            Error::ScalarMismatch { path, field, value } => {
+                write!(f, "`{}` is a {}, but the value is a {}", path, field, value)
+            }
+            Error::Serialize(x) => write!(f, "cannot serialize value: {}", x),
         }
     }
 }
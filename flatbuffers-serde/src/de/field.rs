@@ -3,6 +3,8 @@ use serde::de::Visitor;
 
 use crate::de::error::Error;
 use crate::de::identity::IdentityDeserializer;
+use crate::de::some::SomeDeserializer;
+use crate::de::verify::FollowChecked;
 use crate::de::wrapper::FlatDeserializer;
 
 #[derive(Debug)]
@@ -12,7 +14,9 @@ pub struct FieldDeserializer<'de> {
 }
 
 impl<'de> FieldDeserializer<'de> {
-    fn follow<T: Follow<'de>>(&self) -> T::Inner { T::follow(self.buf, self.loc) }
+    fn follow<T: FollowChecked<'de>>(&self) -> Result<T::Inner, Error> {
+        T::follow_checked(self.buf, self.loc)
+    }
 }
 
 impl<'de> Follow<'de> for FieldDeserializer<'de> {
@@ -20,21 +24,27 @@ impl<'de> Follow<'de> for FieldDeserializer<'de> {
     fn follow(buf: &'de [u8], loc: usize) -> Self::Inner { FieldDeserializer { buf, loc } }
 }
 
-impl<'a, 'de> FlatDeserializer<'de> for FieldDeserializer<'de> {
-    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
-        self.follow::<ForwardsUOffset<IdentityDeserializer>>()
-            .deserialize_option(visitor)
+impl<'de> FollowChecked<'de> for FieldDeserializer<'de> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        Ok(FieldDeserializer { buf, loc })
+    }
+}
+
+impl<'de> FlatDeserializer<'de> for FieldDeserializer<'de> {
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error> {
+        self.follow::<ForwardsUOffset<IdentityDeserializer>>()?
+            .follow_option()
     }
 
-    fn deserialize_fixed<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
-        Some(self.follow::<T>())
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        Ok(Some(self.follow::<T>()?))
     }
 
-    fn deserialize_variable<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
-        Some(self.follow::<ForwardsUOffset<T>>())
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        Ok(Some(self.follow::<ForwardsUOffset<T>>()?))
     }
     fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
-        self.follow::<ForwardsUOffset<IdentityDeserializer>>()
+        self.follow::<ForwardsUOffset<IdentityDeserializer>>()?
             .deserialize_enum(visitor)
     }
 }
@@ -2,7 +2,9 @@ use flatbuffers::{Follow, ForwardsUOffset};
 use serde::de::Visitor;
 
 use crate::de::error::Error;
+use crate::de::some::SomeDeserializer;
 use crate::de::table::TableDeserializer;
+use crate::de::verify::FollowChecked;
 use crate::de::wrapper::{Deserializer, FlatDeserializer};
 use crate::flat_util::FollowOrNull;
 
@@ -13,7 +15,9 @@ pub struct IdentityDeserializer<'de> {
 }
 
 impl<'de> IdentityDeserializer<'de> {
-    fn follow<T: Follow<'de>>(&self) -> T::Inner { T::follow(self.buf, self.loc) }
+    fn follow<T: FollowChecked<'de>>(&self) -> Result<T::Inner, Error> {
+        T::follow_checked(self.buf, self.loc)
+    }
 }
 
 impl<'de> Follow<'de> for IdentityDeserializer<'de> {
@@ -21,24 +25,27 @@ impl<'de> Follow<'de> for IdentityDeserializer<'de> {
     fn follow(buf: &'de [u8], loc: usize) -> Self::Inner { IdentityDeserializer { buf, loc } }
 }
 
+impl<'de> FollowChecked<'de> for IdentityDeserializer<'de> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        Ok(IdentityDeserializer { buf, loc })
+    }
+}
+
 impl<'de> FlatDeserializer<'de> for IdentityDeserializer<'de> {
-    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
-        if let Some(deserializer) =
-            self.follow::<FollowOrNull<ForwardsUOffset<Deserializer<IdentityDeserializer>>>>()
-        {
-            visitor.visit_some(deserializer)
-        } else {
-            visitor.visit_none()
-        }
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error> {
+        Ok(self
+            .follow::<FollowOrNull<ForwardsUOffset<IdentityDeserializer>>>()?
+            .map(SomeDeserializer::Identity))
     }
-    fn deserialize_fixed<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
-        Some(self.follow::<T>())
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        Ok(Some(self.follow::<T>()?))
     }
-    fn deserialize_variable<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
-        Some(self.follow::<T>())
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        Ok(Some(self.follow::<T>()?))
     }
     fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
         println!("deserialize_enum({:?})", self);
-        self.follow::<TableDeserializer>().deserialize_enum(visitor)
+        self.follow::<TableDeserializer>()?
+            .deserialize_enum(visitor)
     }
 }
@@ -4,6 +4,7 @@ use serde::de::{DeserializeSeed, MapAccess};
 use crate::de::error::Error;
 use crate::de::table::TableDeserializer;
 use crate::de::vector::VectorDeserializer;
+use crate::de::verify::FollowChecked;
 use crate::de::wrapper::Deserializer;
 
 #[derive(Debug)]
@@ -23,13 +24,24 @@ impl<'de> Follow<'de> for MapDeserializer<'de> {
     }
 }
 
-impl<'a, 'de> MapAccess<'de> for &'a mut MapDeserializer<'de> {
+impl<'de> FollowChecked<'de> for MapDeserializer<'de> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        Ok(MapDeserializer {
+            vector: VectorDeserializer::follow_checked(buf, loc)?,
+            entry: None,
+        })
+    }
+}
+
+impl<'de> MapAccess<'de> for &mut MapDeserializer<'de> {
     type Error = Error;
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
         K: DeserializeSeed<'de>,
     {
-        let mut entry = self.vector.next::<ForwardsUOffset<TableDeserializer>>();
+        let mut entry = self
+            .vector
+            .next_element::<ForwardsUOffset<TableDeserializer>>()?;
         if let Some(mut entry) = entry {
             let key = seed.deserialize(Deserializer::new(&mut entry))?;
             self.entry = Some(entry);
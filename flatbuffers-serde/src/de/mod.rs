@@ -1,8 +1,8 @@
-use flatbuffers::Follow;
 use serde::Deserialize;
 
 use crate::de::error::Error;
 use crate::de::identity::IdentityDeserializer;
+use crate::de::verify::FollowChecked;
 use crate::de::wrapper::Deserializer;
 
 pub mod error;
@@ -10,12 +10,22 @@ pub mod field;
 pub mod identity;
 pub mod map;
 pub mod none;
+pub mod skip;
+pub mod some;
 pub mod table;
 pub mod vector;
+pub mod verify;
 pub mod wrapper;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Deserialize the value at `loc` in `buf`. Strings and byte slices are borrowed from `buf`, so
+/// `#[serde(borrow)] &'a str` fields and `serde_bytes::Bytes` are read without allocating.
+///
+/// Every offset, vtable and vector length is checked against the bounds of `buf` before it is
+/// followed, so `buf` can come from an untrusted source.
 pub fn deserialize_raw<'a, T: Deserialize<'a>>(buf: &'a [u8], loc: usize) -> crate::de::Result<T> {
-    T::deserialize(Deserializer::<IdentityDeserializer>::follow(buf, loc))
+    T::deserialize(Deserializer::new(IdentityDeserializer::follow_checked(
+        buf, loc,
+    )?))
 }
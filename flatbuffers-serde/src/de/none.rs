@@ -1,10 +1,12 @@
-use flatbuffers::Follow;
 use serde::de::value::U32Deserializer;
 use serde::de::{
     DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
 };
 
 use crate::de::error::Error;
+use crate::de::identity::IdentityDeserializer;
+use crate::de::some::SomeDeserializer;
+use crate::de::verify::FollowChecked;
 use crate::de::wrapper::{Deserializer, FlatDeserializer};
 
 #[derive(Debug)]
@@ -14,12 +16,14 @@ pub struct NoneDeserializer;
 pub struct RepeatNoneDeserializer;
 
 impl<'de> FlatDeserializer<'de> for NoneDeserializer {
-    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
-        visitor.visit_none()
-    }
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error> { Ok(None) }
 
-    fn deserialize_fixed<T: Follow<'de> + 'de>(self) -> Option<T::Inner> { None }
-    fn deserialize_variable<T: Follow<'de> + 'de>(self) -> Option<T::Inner> { None }
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        Ok(None)
+    }
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        Ok(None)
+    }
     fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
         struct NoneEnumAccess;
         struct NoneVariantAccess;
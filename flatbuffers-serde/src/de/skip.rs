@@ -0,0 +1,71 @@
+use std::fmt::Formatter;
+
+use flatbuffers::{Follow, UOffsetT};
+use serde::de::{EnumAccess, VariantAccess, Visitor};
+
+use crate::de::error::Error;
+use crate::de::identity::IdentityDeserializer;
+use crate::de::none::NoneDeserializer;
+use crate::de::some::SomeDeserializer;
+use crate::de::verify::FollowChecked;
+use crate::de::wrapper::FlatDeserializer;
+
+/// Moves past the slot or element that a value occupies without reading it, producing the same
+/// value as an absent field.
+#[derive(Debug)]
+pub struct SkipDeserializer<I> {
+    imp: I,
+}
+
+/// Occupies as much space as `T` in a vector, but is never read.
+#[repr(transparent)]
+struct Skipped<T>(T);
+
+impl<'de, T: Follow<'de>> Follow<'de> for Skipped<T> {
+    type Inner = ();
+    fn follow(buf: &'de [u8], loc: usize) -> Self::Inner {}
+}
+
+impl<'de, T: Follow<'de>> FollowChecked<'de> for Skipped<T> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> { Ok(()) }
+}
+
+/// Reads just the variant of an enum, which is enough to move past both of its slots.
+struct SkipEnum;
+
+impl<'de> Visitor<'de> for SkipEnum {
+    type Value = ();
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        write!(formatter, "an enum")
+    }
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (_, variant): (u16, _) = data.variant()?;
+        variant.unit_variant()
+    }
+}
+
+impl<I> SkipDeserializer<I> {
+    pub fn new(imp: I) -> Self { SkipDeserializer { imp } }
+}
+
+impl<'de, I: FlatDeserializer<'de>> FlatDeserializer<'de> for SkipDeserializer<I> {
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error> {
+        self.imp.deserialize_fixed::<Skipped<UOffsetT>>()?;
+        Ok(None)
+    }
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        self.imp.deserialize_fixed::<Skipped<T>>()?;
+        Ok(None)
+    }
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        self.imp.deserialize_variable::<Skipped<T>>()?;
+        Ok(None)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.imp.deserialize_enum(SkipEnum)?;
+        NoneDeserializer.deserialize_enum(visitor)
+    }
+}
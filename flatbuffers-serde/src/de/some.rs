@@ -0,0 +1,42 @@
+use serde::de::Visitor;
+
+use crate::de::error::Error;
+use crate::de::field::FieldDeserializer;
+use crate::de::identity::IdentityDeserializer;
+use crate::de::verify::FollowChecked;
+use crate::de::wrapper::FlatDeserializer;
+
+/// The contents of a `Some`, which are either found by following an offset, or stored directly in
+/// the slot of a pinned struct field.
+#[derive(Debug)]
+pub enum SomeDeserializer<'de> {
+    Identity(IdentityDeserializer<'de>),
+    Field(FieldDeserializer<'de>),
+}
+
+impl<'de> FlatDeserializer<'de> for SomeDeserializer<'de> {
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error> {
+        match self {
+            SomeDeserializer::Identity(x) => x.follow_option(),
+            SomeDeserializer::Field(x) => x.follow_option(),
+        }
+    }
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        match self {
+            SomeDeserializer::Identity(x) => x.deserialize_fixed::<T>(),
+            SomeDeserializer::Field(x) => x.deserialize_fixed::<T>(),
+        }
+    }
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        match self {
+            SomeDeserializer::Identity(x) => x.deserialize_variable::<T>(),
+            SomeDeserializer::Field(x) => x.deserialize_variable::<T>(),
+        }
+    }
+    fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            SomeDeserializer::Identity(x) => x.deserialize_enum(visitor),
+            SomeDeserializer::Field(x) => x.deserialize_enum(visitor),
+        }
+    }
+}
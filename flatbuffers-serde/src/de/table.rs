@@ -7,13 +7,20 @@ use serde::de::{
 use crate::de::error::Error;
 use crate::de::field::FieldDeserializer;
 use crate::de::identity::IdentityDeserializer;
+use crate::de::none::{NoneDeserializer, RepeatNoneDeserializer};
+use crate::de::some::SomeDeserializer;
+use crate::de::verify::{check_table, FollowChecked};
 use crate::de::wrapper::{Deserializer, FlatDeserializer};
 use crate::flat_util::VariantT;
+use crate::slots::FieldIds;
 
 #[derive(Debug)]
 pub struct TableDeserializer<'de> {
     table: Table<'de>,
     index: usize,
+    /// For a struct with [`FieldIds`], its ids and the names of its fields in order.
+    pinned: Option<(&'static FieldIds, &'static [&'static str])>,
+    field: usize,
 }
 
 impl<'de> Follow<'de> for TableDeserializer<'de> {
@@ -22,6 +29,52 @@ impl<'de> Follow<'de> for TableDeserializer<'de> {
         TableDeserializer {
             table: Table::follow(buf, loc),
             index: 0,
+            pinned: None,
+            field: 0,
+        }
+    }
+}
+
+impl<'de> FollowChecked<'de> for TableDeserializer<'de> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        check_table(buf, loc)?;
+        Ok(Self::follow(buf, loc))
+    }
+}
+
+impl<'de> TableDeserializer<'de> {
+    /// Read `fields` from the slots given by `ids` instead of from consecutive slots.
+    pub fn pin(&mut self, ids: &'static FieldIds, fields: &'static [&'static str]) {
+        self.pinned = Some((ids, fields));
+    }
+    /// Move to the slot of the next field, which is the next slot unless the table is pinned.
+    pub fn next_field(&mut self) -> Result<(), Error> {
+        if let Some((ids, fields)) = self.pinned {
+            if let Some(field) = fields.get(self.field) {
+                self.index = ids.id(field).ok_or_else(|| {
+                    Error::MissingFieldId(format!("{}::{}", ids.struct_name(), field))
+                })? as usize;
+            }
+        }
+        self.field += 1;
+        Ok(())
+    }
+    /// Whether the variant read from the previous slot was absent too, in which case the whole enum
+    /// is missing, as it is for a field added after the buffer was written.
+    fn variant_absent(&self) -> bool {
+        self.table.vtable().get(((self.index - 1) * 2 + 4) as u16) == 0
+    }
+    /// Follow the next slot as a `T`, or `None` if it is absent.
+    fn slot<T: FollowChecked<'de>>(&mut self) -> Result<Option<T::Inner>, Error> {
+        let offset = self.table.vtable().get((self.index * 2 + 4) as u16);
+        self.index += 1;
+        if offset == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(T::follow_checked(
+                self.table.buf,
+                self.table.loc + offset as usize,
+            )?))
         }
     }
 }
@@ -34,14 +87,14 @@ impl<'a, 'de> EnumAccess<'de> for &'a mut TableDeserializer<'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        let variant = self.deserialize_fixed::<VariantT>().unwrap_or(0);
+        let variant = self.deserialize_fixed::<VariantT>()?.unwrap_or(0);
         let de: U16Deserializer<Error> = variant.into_deserializer();
         let variant = seed.deserialize(de)?;
         Ok((variant, self))
     }
 }
 
-impl<'a, 'de> VariantAccess<'de> for &'a mut TableDeserializer<'de> {
+impl<'de> VariantAccess<'de> for &mut TableDeserializer<'de> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
@@ -53,18 +106,23 @@ impl<'a, 'de> VariantAccess<'de> for &'a mut TableDeserializer<'de> {
     where
         T: DeserializeSeed<'de>,
     {
-        let deserializer = self.deserialize_fixed::<FieldDeserializer>();
-        let mut deserializer = deserializer.ok_or(Error::MissingEnumValue)?;
-        seed.deserialize(Deserializer::new(deserializer))
+        // Scalar values equal to their default are omitted like any other field.
+        match self.deserialize_fixed::<FieldDeserializer>()? {
+            Some(deserializer) => seed.deserialize(Deserializer::new(deserializer)),
+            None => seed.deserialize(Deserializer::new(NoneDeserializer)),
+        }
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let deserializer = self.deserialize_fixed::<ForwardsUOffset<TableDeserializer>>();
-        let mut deserializer = deserializer.ok_or(Error::MissingEnumValue)?;
-        visitor.visit_seq(&mut deserializer)
+        let absent = self.variant_absent();
+        match self.deserialize_fixed::<ForwardsUOffset<TableDeserializer>>()? {
+            Some(mut deserializer) => visitor.visit_seq(&mut deserializer),
+            None if absent => visitor.visit_seq(RepeatNoneDeserializer),
+            None => Err(Error::MissingEnumValue),
+        }
     }
 
     fn struct_variant<V>(
@@ -86,35 +144,32 @@ impl<'de> SeqAccess<'de> for TableDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         println!("next_element_seed {:?}", self.index);
+        self.next_field()?;
         Ok(Some(seed.deserialize(Deserializer::new(self))?))
     }
 }
 
-impl<'a, 'de> FlatDeserializer<'de> for &'a mut TableDeserializer<'de> {
-    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
-        if let Some(deserializer) =
-            self.deserialize_fixed::<ForwardsUOffset<IdentityDeserializer>>()
-        {
-            visitor.visit_some(Deserializer::new(deserializer))
+impl<'de> FlatDeserializer<'de> for &mut TableDeserializer<'de> {
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error> {
+        if self.pinned.is_some() {
+            Ok(self
+                .deserialize_fixed::<FieldDeserializer>()?
+                .map(SomeDeserializer::Field))
         } else {
-            visitor.visit_none()
+            Ok(self
+                .deserialize_fixed::<ForwardsUOffset<IdentityDeserializer>>()?
+                .map(SomeDeserializer::Identity))
         }
     }
 
-    fn deserialize_fixed<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
         println!("deserialize_value({:?})", self);
-        let result = self.table.get::<T>((self.index * 2 + 4) as u16, None);
-        self.index += 1;
-        result
+        self.slot::<T>()
     }
 
-    fn deserialize_variable<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
         println!("deserialize_value({:?})", self);
-        let result = self
-            .table
-            .get::<ForwardsUOffset<T>>((self.index * 2 + 4) as u16, None);
-        self.index += 1;
-        result
+        self.slot::<ForwardsUOffset<T>>()
     }
     fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
         visitor.visit_enum(self)
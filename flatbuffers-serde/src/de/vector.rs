@@ -5,6 +5,9 @@ use serde::de::{DeserializeSeed, SeqAccess, Visitor};
 
 use crate::de::error::Error;
 use crate::de::identity::IdentityDeserializer;
+use crate::de::none::NoneDeserializer;
+use crate::de::some::SomeDeserializer;
+use crate::de::verify::FollowChecked;
 use crate::de::wrapper::{Deserializer, FlatDeserializer};
 use crate::flat_util::FollowOrNull;
 
@@ -28,42 +31,52 @@ impl<'de> Follow<'de> for VectorDeserializer<'de> {
     }
 }
 
+impl<'de> FollowChecked<'de> for VectorDeserializer<'de> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        UOffsetT::follow_checked(buf, loc)?;
+        Ok(Self::follow(buf, loc))
+    }
+}
+
 impl<'de> VectorDeserializer<'de> {
-    pub fn next<T: Follow<'de>>(&mut self) -> Option<T::Inner> {
+    /// The number of elements not yet read.
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    /// The element `index` places past the next one, without reading those in between.
+    pub fn get<T: FollowChecked<'de>>(&self, index: usize) -> Result<T::Inner, Error> {
+        assert!(index < self.len);
+        T::follow_checked(self.buf, self.loc + index * size_of::<T>())
+    }
+    pub fn next_element<T: FollowChecked<'de>>(&mut self) -> Result<Option<T::Inner>, Error> {
         if self.len == 0 {
-            return None;
+            return Ok(None);
         }
-        let result = T::follow(self.buf, self.loc);
+        let result = T::follow_checked(self.buf, self.loc)?;
         self.loc += size_of::<T>();
         self.len -= 1;
-        Some(result)
+        Ok(Some(result))
     }
 }
 
-impl<'a, 'de> FlatDeserializer<'de> for &'a mut VectorDeserializer<'de> {
-    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
-        println!("deserialize_option({:?})", self);
-        let deserializer = self
-            .deserialize_fixed::<FollowOrNull<ForwardsUOffset<IdentityDeserializer>>>()
-            .unwrap();
-        if let Some(deserializer) = deserializer {
-            visitor.visit_some(Deserializer::new(deserializer))
-        } else {
-            visitor.visit_none()
-        }
+impl<'de> FlatDeserializer<'de> for &mut VectorDeserializer<'de> {
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error> {
+        Ok(self
+            .deserialize_fixed::<FollowOrNull<ForwardsUOffset<IdentityDeserializer>>>()?
+            .flatten()
+            .map(SomeDeserializer::Identity))
     }
 
-    fn deserialize_fixed<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
-        let result = self.next::<T>().unwrap();
-        Some(result)
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
+        self.next_element::<T>()
     }
-    fn deserialize_variable<T: Follow<'de> + 'de>(self) -> Option<T::Inner> {
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error> {
         self.deserialize_fixed::<ForwardsUOffset<T>>()
     }
     fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
-        self.deserialize_fixed::<ForwardsUOffset<IdentityDeserializer>>()
-            .unwrap()
-            .deserialize_enum(visitor)
+        match self.deserialize_fixed::<ForwardsUOffset<IdentityDeserializer>>()? {
+            Some(deserializer) => deserializer.deserialize_enum(visitor),
+            None => NoneDeserializer.deserialize_enum(visitor),
+        }
     }
 }
 
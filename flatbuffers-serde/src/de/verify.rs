@@ -0,0 +1,97 @@
+use std::mem::size_of;
+
+use flatbuffers::{Follow, ForwardsUOffset, InvalidFlatbuffer, SOffsetT, UOffsetT, VOffsetT};
+
+use crate::de::error::Error;
+use crate::flat_util::{Flat128, FlatUnit, FollowOrNull};
+
+/// A [`Follow`] that checks everything it reads against the bounds of `buf` first, so that a
+/// value can be deserialized from bytes that were not produced by
+/// [`serialize_raw`](crate::ser::serialize_raw).
+pub trait FollowChecked<'de>: Follow<'de> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error>;
+}
+
+pub fn out_of_bounds(start: usize, end: usize) -> Error {
+    Error::Invalid(InvalidFlatbuffer::RangeOutOfBounds {
+        range: start..end,
+        error_trace: Default::default(),
+    })
+}
+
+/// Check that the `size` bytes at `loc` are inside `buf`.
+pub fn check_range(buf: &[u8], loc: usize, size: usize) -> Result<(), Error> {
+    match loc.checked_add(size) {
+        Some(end) if end <= buf.len() => Ok(()),
+        _ => Err(out_of_bounds(loc, loc.saturating_add(size))),
+    }
+}
+
+/// Check the vtable of the table at `loc` and that the table itself is inside `buf`, so that its
+/// slots can be looked up.
+pub fn check_table(buf: &[u8], loc: usize) -> Result<(), Error> {
+    let soffset = SOffsetT::follow_checked(buf, loc)?;
+    let vtable = (loc as i64) - (soffset as i64);
+    if vtable < 0 || vtable as usize >= buf.len() {
+        return Err(Error::Invalid(InvalidFlatbuffer::SignedOffsetOutOfBounds {
+            soffset,
+            position: loc,
+            error_trace: Default::default(),
+        }));
+    }
+    let vtable = vtable as usize;
+    let vtable_len = VOffsetT::follow_checked(buf, vtable)? as usize;
+    if !vtable_len.is_multiple_of(size_of::<VOffsetT>()) {
+        return Err(Error::Invalid(InvalidFlatbuffer::Unaligned {
+            position: vtable + vtable_len,
+            unaligned_type: "VOffsetT",
+            error_trace: Default::default(),
+        }));
+    }
+    if vtable_len < 2 * size_of::<VOffsetT>() {
+        return Err(out_of_bounds(vtable, vtable + 2 * size_of::<VOffsetT>()));
+    }
+    check_range(buf, vtable, vtable_len)?;
+    let table_len = VOffsetT::follow(buf, vtable + size_of::<VOffsetT>()) as usize;
+    check_range(buf, loc, table_len)
+}
+
+macro_rules! follow_checked_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl<'de> FollowChecked<'de> for $t {
+                fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+                    check_range(buf, loc, size_of::<Self>())?;
+                    Ok(Self::follow(buf, loc))
+                }
+            }
+        )*
+    };
+}
+
+follow_checked_scalar!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64, Flat128, FlatUnit);
+
+impl<'de> FollowChecked<'de> for &'de [u8] {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        let len = UOffsetT::follow_checked(buf, loc)? as usize;
+        check_range(buf, loc + size_of::<UOffsetT>(), len)?;
+        Ok(Self::follow(buf, loc))
+    }
+}
+
+impl<'de, T: FollowChecked<'de>> FollowChecked<'de> for ForwardsUOffset<T> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        let offset = UOffsetT::follow_checked(buf, loc)? as usize;
+        T::follow_checked(buf, loc.saturating_add(offset))
+    }
+}
+
+impl<'de, T: FollowChecked<'de>> FollowChecked<'de> for FollowOrNull<T> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        if UOffsetT::follow_checked(buf, loc)? == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(T::follow_checked(buf, loc)?))
+        }
+    }
+}
@@ -4,16 +4,31 @@ use flatbuffers::Follow;
 use serde::de::Visitor;
 
 use crate::de::error::Error;
+use crate::de::identity::IdentityDeserializer;
 use crate::de::map::MapDeserializer;
 use crate::de::none::{NoneDeserializer, RepeatNoneDeserializer};
+use crate::de::some::SomeDeserializer;
 use crate::de::table::TableDeserializer;
 use crate::de::vector::VectorDeserializer;
+use crate::de::verify::FollowChecked;
 use crate::flat_util::{Flat128, FlatUnit};
+use crate::slots::FieldIds;
 
 pub trait FlatDeserializer<'de>: Debug {
-    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>;
-    fn deserialize_fixed<T: Follow<'de> + 'de>(self) -> Option<T::Inner>;
-    fn deserialize_variable<T: Follow<'de> + 'de>(self) -> Option<T::Inner>;
+    /// The contents of an `Option`, or `None` if it is absent.
+    fn follow_option(self) -> Result<Option<SomeDeserializer<'de>>, Error>;
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        Self: Sized,
+    {
+        if let Some(deserializer) = self.follow_option()? {
+            visitor.visit_some(Deserializer::new(deserializer))
+        } else {
+            visitor.visit_none()
+        }
+    }
+    fn deserialize_fixed<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error>;
+    fn deserialize_variable<T: FollowChecked<'de> + 'de>(self) -> Result<Option<T::Inner>, Error>;
     fn deserialize_enum<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error>;
 }
 
@@ -50,86 +65,91 @@ where
     where
         V: Visitor<'de>,
     {
-        visitor.visit_bool(self.imp.deserialize_fixed::<bool>().unwrap_or_default())
+        // Reading any other byte as a `bool` is undefined behavior.
+        match self.imp.deserialize_fixed::<u8>()?.unwrap_or_default() {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            _ => Err(Error::BadBool),
+        }
     }
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i8(self.imp.deserialize_fixed::<i8>().unwrap_or_default())
+        visitor.visit_i8(self.imp.deserialize_fixed::<i8>()?.unwrap_or_default())
     }
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i16(self.imp.deserialize_fixed::<i16>().unwrap_or_default())
+        visitor.visit_i16(self.imp.deserialize_fixed::<i16>()?.unwrap_or_default())
     }
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(self.imp.deserialize_fixed::<i32>().unwrap_or_default())
+        visitor.visit_i32(self.imp.deserialize_fixed::<i32>()?.unwrap_or_default())
     }
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i64(self.imp.deserialize_fixed::<i64>().unwrap_or_default())
+        visitor.visit_i64(self.imp.deserialize_fixed::<i64>()?.unwrap_or_default())
     }
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i128(self.imp.deserialize_fixed::<Flat128>().unwrap_or_default() as i128)
+        visitor.visit_i128(self.imp.deserialize_fixed::<Flat128>()?.unwrap_or_default() as i128)
     }
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u8(self.imp.deserialize_fixed::<u8>().unwrap_or_default())
+        visitor.visit_u8(self.imp.deserialize_fixed::<u8>()?.unwrap_or_default())
     }
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u16(self.imp.deserialize_fixed::<u16>().unwrap_or_default())
+        visitor.visit_u16(self.imp.deserialize_fixed::<u16>()?.unwrap_or_default())
     }
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(self.imp.deserialize_fixed::<u32>().unwrap_or_default())
+        visitor.visit_u32(self.imp.deserialize_fixed::<u32>()?.unwrap_or_default())
     }
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(self.imp.deserialize_fixed::<u64>().unwrap_or_default())
+        visitor.visit_u64(self.imp.deserialize_fixed::<u64>()?.unwrap_or_default())
     }
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u128(self.imp.deserialize_fixed::<Flat128>().unwrap_or_default())
+        visitor.visit_u128(self.imp.deserialize_fixed::<Flat128>()?.unwrap_or_default())
     }
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f32(self.imp.deserialize_fixed::<f32>().unwrap_or_default())
+        visitor.visit_f32(self.imp.deserialize_fixed::<f32>()?.unwrap_or_default())
     }
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f64(self.imp.deserialize_fixed::<f64>().unwrap_or_default())
+        visitor.visit_f64(self.imp.deserialize_fixed::<f64>()?.unwrap_or_default())
     }
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
         visitor.visit_char(
-            std::char::from_u32(self.imp.deserialize_fixed::<u32>().unwrap_or_default())
+            std::char::from_u32(self.imp.deserialize_fixed::<u32>()?.unwrap_or_default())
                 .ok_or(Error::BadChar)?,
         )
     }
@@ -137,7 +157,13 @@ where
     where
         V: Visitor<'de>,
     {
-        visitor.visit_borrowed_str(self.imp.deserialize_variable::<&str>().unwrap_or_default())
+        // flatbuffers' `Follow for &str` skips UTF-8 validation, so borrow the bytes and check
+        // them here instead.
+        let bytes = self
+            .imp
+            .deserialize_variable::<&[u8]>()?
+            .unwrap_or_default();
+        visitor.visit_borrowed_str(std::str::from_utf8(bytes).map_err(|_| Error::BadUtf8)?)
     }
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
@@ -149,7 +175,11 @@ where
     where
         V: Visitor<'de>,
     {
-        visitor.visit_borrowed_bytes(self.imp.deserialize_variable::<&[u8]>().unwrap_or_default())
+        visitor.visit_borrowed_bytes(
+            self.imp
+                .deserialize_variable::<&[u8]>()?
+                .unwrap_or_default(),
+        )
     }
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
@@ -167,7 +197,7 @@ where
     where
         V: Visitor<'de>,
     {
-        self.imp.deserialize_fixed::<FlatUnit>();
+        self.imp.deserialize_fixed::<FlatUnit>()?;
         visitor.visit_unit()
     }
     fn deserialize_unit_struct<V>(
@@ -178,7 +208,7 @@ where
     where
         V: Visitor<'de>,
     {
-        self.imp.deserialize_fixed::<FlatUnit>();
+        self.imp.deserialize_fixed::<FlatUnit>()?;
         visitor.visit_unit()
     }
     fn deserialize_newtype_struct<V>(
@@ -195,7 +225,7 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut deserializer = self.imp.deserialize_variable::<VectorDeserializer>();
+        let mut deserializer = self.imp.deserialize_variable::<VectorDeserializer>()?;
         if let Some(mut deserializer) = deserializer {
             visitor.visit_seq(deserializer)
         } else {
@@ -206,7 +236,7 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut deserializer = self.imp.deserialize_variable::<TableDeserializer>();
+        let mut deserializer = self.imp.deserialize_variable::<TableDeserializer>()?;
         if let Some(mut deserializer) = deserializer {
             visitor.visit_seq(deserializer)
         } else {
@@ -228,7 +258,7 @@ where
     where
         V: Visitor<'de>,
     {
-        let mut deserializer = self.imp.deserialize_variable::<MapDeserializer>();
+        let mut deserializer = self.imp.deserialize_variable::<MapDeserializer>()?;
         if let Some(mut deserializer) = deserializer {
             visitor.visit_map(&mut deserializer)
         } else {
@@ -244,7 +274,16 @@ where
     where
         V: Visitor<'de>,
     {
-        self.deserialize_tuple(fields.len(), visitor)
+        let ids = match FieldIds::lookup(name) {
+            Some(ids) => ids,
+            None => return self.deserialize_tuple(fields.len(), visitor),
+        };
+        if let Some(mut deserializer) = self.imp.deserialize_variable::<TableDeserializer>()? {
+            deserializer.pin(ids, fields);
+            visitor.visit_seq(deserializer)
+        } else {
+            visitor.visit_seq(RepeatNoneDeserializer)
+        }
     }
     fn deserialize_enum<V>(
         self,
@@ -19,7 +19,8 @@ pub struct FlatUnion<'a> {
 }
 
 impl Push for Flat128 {
-    type Output = u128;
+    /// Aligned to 8 bytes like the largest flatbuffers scalar, whatever the alignment of `u128`.
+    type Output = [u64; 2];
     fn push(&self, dst: &mut [u8], _rest: &[u8]) { dst.copy_from_slice(&self.0.to_le_bytes()) }
 }
 
@@ -53,7 +54,7 @@ impl<'de, T: Follow<'de>> Follow<'de> for FollowOrNull<T> {
 impl<'de> Follow<'de> for FlatUnit {
     type Inner = ();
 
-    fn follow(buf: &'de [u8], loc: usize) -> Self::Inner { () }
+    fn follow(buf: &'de [u8], loc: usize) -> Self::Inner {}
 }
 
 impl<'a> Follow<'a> for FlatUnion<'a> {
@@ -1,4 +1,3 @@
-#![feature(never_type)]
 #![feature(const_fn_trait_bound)]
 #![feature(const_fn_fn_ptr_basics)]
 #![feature(const_mut_refs)]
@@ -12,14 +11,12 @@
 #![allow(incomplete_features)]
 #![allow(unreachable_code)]
 #![feature(trivial_bounds)]
-#![feature(log_syntax)]
 #![feature(generic_associated_types)]
 
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::marker::PhantomData;
 
-use registry::registry;
 use sha2::Sha256;
 
 #[macro_use]
@@ -31,18 +28,20 @@ mod de;
 mod flat_util;
 mod ser;
 
+#[allow(clippy::all)]
 pub mod test_generated {
     include!(concat!(env!("OUT_DIR"), "/test_generated.rs"));
 }
 
+pub mod any;
 pub mod any_generated;
 pub mod buffer;
+pub mod slots;
 pub mod tag;
 #[cfg(test)]
 mod test;
 pub mod vec_slice;
+pub mod view;
 
-registry! {
-    require tag;
-    require any_generated;
-}
+pub use ser::{serialize_raw_with, Options};
+pub use view::*;
@@ -8,14 +8,17 @@ macro_rules! cfg_kinds {
     };
 }
 
+/// Implements [`HasTypeTag`](crate::tag::HasTypeTag) for a type and registers it in
+/// [`TYPE_TAGS`](crate::tag::TYPE_TAGS).
 macro_rules! type_tag {
     (
         type $ty:tt $(<$lt:tt>)*,
         name $name:literal,
         kinds $kinds:tt
-    ) => {{
+    ) => {const _: () = {
         use $crate::reexport::lazy_static::lazy_static;
         use $crate::tag::FlatTypeTag;
+        use $crate::tag::SerdeTypeTag;
         use $crate::tag::TypeTag;
         use $crate::tag::HasFlatTypeTag;
         use $crate::tag::HasTypeTag;
@@ -32,12 +35,50 @@ macro_rules! type_tag {
                 static FLAT_TAG_OPTION: Option<FlatTypeTag> = None;
             }
         };
+        $crate::cfg_kinds!{
+            if serde in $kinds {
+                static SERDE_TAG_OPTION: Option<SerdeTypeTag> = Some(SerdeTypeTag::new::<$ty>());
+            } else {
+                static SERDE_TAG_OPTION: Option<SerdeTypeTag> = None;
+            }
+        };
         lazy_static! {
-            static ref TYPE_TAG: TypeTag = TypeTag::new::<$ty>($name, FLAT_TAG_OPTION);
+            static ref TYPE_TAG: TypeTag =
+                TypeTag::new::<$ty>($name, FLAT_TAG_OPTION, SERDE_TAG_OPTION);
         }
         impl $(<$lt>)* HasTypeTag for $ty $(<$lt>)*{
             fn type_tag() -> &'static TypeTag { &TYPE_TAG }
         }
-        PhantomData::<$ty>
-    }}
+        #[$crate::reexport::catalog::register(
+            $crate::tag::TYPE_TAGS,
+            crate = $crate::reexport::catalog
+        )]
+        fn register $(<$lt>)* () -> PhantomData<$ty $(<$lt>)*> { PhantomData }
+    };}
+}
+
+/// Pins the slots of a struct's fields by registering them in
+/// [`FIELD_IDS`](crate::slots::FIELD_IDS):
+/// ```ignore
+/// field_ids!(Shape { name = 0, kind = 1 enum, color = 3 });
+/// ```
+#[macro_export]
+macro_rules! field_ids {
+    (@enum enum) => { true };
+    (@enum) => { false };
+    ($name:ident { $($field:ident = $id:literal $($enum:ident)?),* $(,)? }) => {
+        const _: () = {
+            #[$crate::reexport::catalog::register(
+                $crate::slots::FIELD_IDS,
+                crate = $crate::reexport::catalog
+            )]
+            fn register() -> &'static $crate::slots::FieldIds {
+                static FIELD_IDS: $crate::slots::FieldIds = $crate::slots::FieldIds::new(
+                    stringify!($name),
+                    &[$((stringify!($field), $id, $crate::field_ids!(@enum $($enum)?))),*],
+                );
+                &FIELD_IDS
+            }
+        };
+    };
 }
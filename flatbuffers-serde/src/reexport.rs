@@ -1 +1,2 @@
+pub use catalog;
 pub use lazy_static;
@@ -0,0 +1,454 @@
+use std::cmp::Ordering;
+
+use serde::ser::{
+    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Serialize, Serializer};
+
+use crate::ser::error::Error;
+use crate::ser::Result;
+
+/// An owned copy of the calls a `Serialize` impl makes, so that map entries can be written after
+/// all of them have been seen. Contents of the same type are ordered field by field, with strings
+/// and bytes compared lexicographically and floats by their IEEE 754 total order. Contents of
+/// different types are ordered by variant, as documented on
+/// [`Options::sort_maps`](crate::Options::sort_maps), so reordering the variants changes the
+/// order of sorted maps.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Content {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(TotalF32),
+    F64(TotalF64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, Box<Content>),
+    NewtypeVariant(&'static str, u32, &'static str, Box<Content>),
+    Seq(Vec<Content>),
+    Tuple(Vec<Content>),
+    TupleStruct(&'static str, Vec<Content>),
+    TupleVariant(&'static str, u32, &'static str, Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    Struct(&'static str, Vec<(&'static str, Content)>),
+    StructVariant(
+        &'static str,
+        u32,
+        &'static str,
+        Vec<(&'static str, Content)>,
+    ),
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct TotalF32(pub f32);
+
+#[derive(Debug, Copy, Clone)]
+pub struct TotalF64(pub f64);
+
+impl TotalF32 {
+    fn key(self) -> i32 {
+        let bits = self.0.to_bits() as i32;
+        bits ^ (((bits >> 31) as u32) >> 1) as i32
+    }
+}
+
+impl TotalF64 {
+    fn key(self) -> i64 {
+        let bits = self.0.to_bits() as i64;
+        bits ^ (((bits >> 63) as u64) >> 1) as i64
+    }
+}
+
+impl Eq for TotalF32 {}
+
+impl PartialEq for TotalF32 {
+    fn eq(&self, other: &Self) -> bool { self.key() == other.key() }
+}
+
+impl Ord for TotalF32 {
+    fn cmp(&self, other: &Self) -> Ordering { self.key().cmp(&other.key()) }
+}
+
+impl PartialOrd for TotalF32 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Eq for TotalF64 {}
+
+impl PartialEq for TotalF64 {
+    fn eq(&self, other: &Self) -> bool { self.key() == other.key() }
+}
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering { self.key().cmp(&other.key()) }
+}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Content {
+    pub fn new<T: ?Sized + Serialize>(value: &T) -> Result<Self> {
+        value.serialize(ContentSerializer)
+    }
+}
+
+/// Replays the captured calls against another serializer.
+impl Serialize for Content {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Content::Bool(x) => serializer.serialize_bool(*x),
+            Content::I8(x) => serializer.serialize_i8(*x),
+            Content::I16(x) => serializer.serialize_i16(*x),
+            Content::I32(x) => serializer.serialize_i32(*x),
+            Content::I64(x) => serializer.serialize_i64(*x),
+            Content::I128(x) => serializer.serialize_i128(*x),
+            Content::U8(x) => serializer.serialize_u8(*x),
+            Content::U16(x) => serializer.serialize_u16(*x),
+            Content::U32(x) => serializer.serialize_u32(*x),
+            Content::U64(x) => serializer.serialize_u64(*x),
+            Content::U128(x) => serializer.serialize_u128(*x),
+            Content::F32(x) => serializer.serialize_f32(x.0),
+            Content::F64(x) => serializer.serialize_f64(x.0),
+            Content::Char(x) => serializer.serialize_char(*x),
+            Content::String(x) => serializer.serialize_str(x),
+            Content::Bytes(x) => serializer.serialize_bytes(x),
+            Content::None => serializer.serialize_none(),
+            Content::Some(x) => serializer.serialize_some(&**x),
+            Content::Unit => serializer.serialize_unit(),
+            Content::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Content::UnitVariant(name, index, variant) => {
+                serializer.serialize_unit_variant(name, *index, variant)
+            }
+            Content::NewtypeStruct(name, x) => serializer.serialize_newtype_struct(name, &**x),
+            Content::NewtypeVariant(name, index, variant, x) => {
+                serializer.serialize_newtype_variant(name, *index, variant, &**x)
+            }
+            Content::Seq(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for x in elements {
+                    seq.serialize_element(x)?;
+                }
+                seq.end()
+            }
+            Content::Tuple(elements) => {
+                let mut tuple = serializer.serialize_tuple(elements.len())?;
+                for x in elements {
+                    tuple.serialize_element(x)?;
+                }
+                tuple.end()
+            }
+            Content::TupleStruct(name, fields) => {
+                let mut tuple = serializer.serialize_tuple_struct(name, fields.len())?;
+                for x in fields {
+                    tuple.serialize_field(x)?;
+                }
+                tuple.end()
+            }
+            Content::TupleVariant(name, index, variant, fields) => {
+                let mut tuple =
+                    serializer.serialize_tuple_variant(name, *index, variant, fields.len())?;
+                for x in fields {
+                    tuple.serialize_field(x)?;
+                }
+                tuple.end()
+            }
+            Content::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+            Content::Struct(name, fields) => {
+                let mut st = serializer.serialize_struct(name, fields.len())?;
+                for (k, v) in fields {
+                    st.serialize_field(k, v)?;
+                }
+                st.end()
+            }
+            Content::StructVariant(name, index, variant, fields) => {
+                let mut st =
+                    serializer.serialize_struct_variant(name, *index, variant, fields.len())?;
+                for (k, v) in fields {
+                    st.serialize_field(k, v)?;
+                }
+                st.end()
+            }
+        }
+    }
+}
+
+struct ContentSerializer;
+
+enum SeqKind {
+    Seq,
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(&'static str, u32, &'static str),
+}
+
+enum StructKind {
+    Struct(&'static str),
+    StructVariant(&'static str, u32, &'static str),
+}
+
+struct SeqContent {
+    kind: SeqKind,
+    elements: Vec<Content>,
+}
+
+struct StructContent {
+    kind: StructKind,
+    fields: Vec<(&'static str, Content)>,
+}
+
+struct MapContent {
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl Serializer for ContentSerializer {
+    type Ok = Content;
+    type Error = Error;
+    type SerializeSeq = SeqContent;
+    type SerializeTuple = SeqContent;
+    type SerializeTupleStruct = SeqContent;
+    type SerializeTupleVariant = SeqContent;
+    type SerializeMap = MapContent;
+    type SerializeStruct = StructContent;
+    type SerializeStructVariant = StructContent;
+    fn serialize_bool(self, v: bool) -> Result<Content> { Ok(Content::Bool(v)) }
+    fn serialize_i8(self, v: i8) -> Result<Content> { Ok(Content::I8(v)) }
+    fn serialize_i16(self, v: i16) -> Result<Content> { Ok(Content::I16(v)) }
+    fn serialize_i32(self, v: i32) -> Result<Content> { Ok(Content::I32(v)) }
+    fn serialize_i64(self, v: i64) -> Result<Content> { Ok(Content::I64(v)) }
+    fn serialize_i128(self, v: i128) -> Result<Content> { Ok(Content::I128(v)) }
+    fn serialize_u8(self, v: u8) -> Result<Content> { Ok(Content::U8(v)) }
+    fn serialize_u16(self, v: u16) -> Result<Content> { Ok(Content::U16(v)) }
+    fn serialize_u32(self, v: u32) -> Result<Content> { Ok(Content::U32(v)) }
+    fn serialize_u64(self, v: u64) -> Result<Content> { Ok(Content::U64(v)) }
+    fn serialize_u128(self, v: u128) -> Result<Content> { Ok(Content::U128(v)) }
+    fn serialize_f32(self, v: f32) -> Result<Content> { Ok(Content::F32(TotalF32(v))) }
+    fn serialize_f64(self, v: f64) -> Result<Content> { Ok(Content::F64(TotalF64(v))) }
+    fn serialize_char(self, v: char) -> Result<Content> { Ok(Content::Char(v)) }
+    fn serialize_str(self, v: &str) -> Result<Content> { Ok(Content::String(v.to_string())) }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Content> { Ok(Content::Bytes(v.to_vec())) }
+    fn serialize_none(self) -> Result<Content> { Ok(Content::None) }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Content> {
+        Ok(Content::Some(Box::new(Content::new(value)?)))
+    }
+    fn serialize_unit(self) -> Result<Content> { Ok(Content::Unit) }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Content> {
+        Ok(Content::UnitStruct(name))
+    }
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Content> {
+        Ok(Content::UnitVariant(name, variant_index, variant))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Content> {
+        Ok(Content::NewtypeStruct(name, Box::new(Content::new(value)?)))
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content> {
+        Ok(Content::NewtypeVariant(
+            name,
+            variant_index,
+            variant,
+            Box::new(Content::new(value)?),
+        ))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqContent> {
+        Ok(SeqContent::new(SeqKind::Seq))
+    }
+    fn serialize_tuple(self, len: usize) -> Result<SeqContent> {
+        Ok(SeqContent::new(SeqKind::Tuple))
+    }
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<SeqContent> {
+        Ok(SeqContent::new(SeqKind::TupleStruct(name)))
+    }
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqContent> {
+        Ok(SeqContent::new(SeqKind::TupleVariant(
+            name,
+            variant_index,
+            variant,
+        )))
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<MapContent> {
+        Ok(MapContent {
+            entries: vec![],
+            key: None,
+        })
+    }
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<StructContent> {
+        Ok(StructContent::new(StructKind::Struct(name)))
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructContent> {
+        Ok(StructContent::new(StructKind::StructVariant(
+            name,
+            variant_index,
+            variant,
+        )))
+    }
+}
+
+impl SeqContent {
+    fn new(kind: SeqKind) -> Self {
+        SeqContent {
+            kind,
+            elements: vec![],
+        }
+    }
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.elements.push(Content::new(value)?);
+        Ok(())
+    }
+    fn finish(self) -> Result<Content> {
+        Ok(match self.kind {
+            SeqKind::Seq => Content::Seq(self.elements),
+            SeqKind::Tuple => Content::Tuple(self.elements),
+            SeqKind::TupleStruct(name) => Content::TupleStruct(name, self.elements),
+            SeqKind::TupleVariant(name, index, variant) => {
+                Content::TupleVariant(name, index, variant, self.elements)
+            }
+        })
+    }
+}
+
+impl SerializeSeq for SeqContent {
+    type Ok = Content;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Content> { self.finish() }
+}
+
+impl SerializeTuple for SeqContent {
+    type Ok = Content;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Content> { self.finish() }
+}
+
+impl SerializeTupleStruct for SeqContent {
+    type Ok = Content;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Content> { self.finish() }
+}
+
+impl SerializeTupleVariant for SeqContent {
+    type Ok = Content;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Content> { self.finish() }
+}
+
+impl StructContent {
+    fn new(kind: StructKind) -> Self {
+        StructContent {
+            kind,
+            fields: vec![],
+        }
+    }
+    fn push<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.fields.push((key, Content::new(value)?));
+        Ok(())
+    }
+    fn finish(self) -> Result<Content> {
+        Ok(match self.kind {
+            StructKind::Struct(name) => Content::Struct(name, self.fields),
+            StructKind::StructVariant(name, index, variant) => {
+                Content::StructVariant(name, index, variant, self.fields)
+            }
+        })
+    }
+}
+
+impl SerializeStruct for StructContent {
+    type Ok = Content;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.push(key, value)
+    }
+    fn end(self) -> Result<Content> { self.finish() }
+}
+
+impl SerializeStructVariant for StructContent {
+    type Ok = Content;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.push(key, value)
+    }
+    fn end(self) -> Result<Content> { self.finish() }
+}
+
+impl SerializeMap for MapContent {
+    type Ok = Content;
+    type Error = Error;
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(Content::new(key)?);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().unwrap();
+        self.entries.push((key, Content::new(value)?));
+        Ok(())
+    }
+    fn end(self) -> Result<Content> { Ok(Content::Map(self.entries)) }
+}
@@ -25,9 +25,9 @@ impl<'a, 'b> EnumBuilder<'a, 'b> {
 impl<'a, 'b> SerializeTupleVariant for EnumBuilder<'a, 'b> {
     type Ok = Value;
     type Error = Error;
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let value = value.serialize(self.table.reborrow())?;
         self.table.push(value);
@@ -44,9 +44,9 @@ impl<'a, 'b> SerializeTupleVariant for EnumBuilder<'a, 'b> {
 impl<'a, 'b> SerializeStructVariant for EnumBuilder<'a, 'b> {
     type Ok = Value;
     type Error = Error;
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         SerializeTupleVariant::serialize_field(self, value)
     }
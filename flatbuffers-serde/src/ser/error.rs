@@ -1,8 +1,12 @@
+use std::any::TypeId;
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug)]
 pub enum Error {
     Custom(String),
+    MissingFieldId(String),
+    MissingTypeTag(TypeId),
+    UnmarkedEnum(String),
 }
 
 impl std::error::Error for Error {}
@@ -11,6 +15,9 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Custom(x) => write!(f, "custom error: {}", x),
+            Error::MissingFieldId(x) => write!(f, "no field id for `{}`", x),
+            Error::MissingTypeTag(x) => write!(f, "no serde type tag for {:?}", x),
+            Error::UnmarkedEnum(x) => write!(f, "field id for enum `{}` is not marked `enum`", x),
         }
     }
 }
@@ -2,6 +2,7 @@ use serde::ser::{SerializeMap, SerializeSeq};
 use serde::Serialize;
 
 use crate::ser;
+use crate::ser::content::Content;
 use crate::ser::error::Error;
 use crate::ser::value::{OneValue, Value};
 use crate::ser::vector::VectorBuilder;
@@ -11,39 +12,71 @@ use crate::ser::{error, Result};
 pub struct MapBuilder<'a, 'b> {
     vector: VectorBuilder<'a, 'b>,
     key: Option<Value>,
+    /// With [`Options::sort_maps`](crate::ser::Options::sort_maps), the entries seen so far,
+    /// which are only written once they can be sorted.
+    sorted: Option<Vec<(Content, Content)>>,
+    sorted_key: Option<Content>,
 }
 
 impl<'a, 'b> MapBuilder<'a, 'b> {
     pub fn new(serializer: Serializer<'a, 'b>) -> Self {
+        let sorted = if serializer.stack.options.sort_maps {
+            Some(vec![])
+        } else {
+            None
+        };
         MapBuilder {
             vector: VectorBuilder::new(serializer),
             key: None,
+            sorted,
+            sorted_key: None,
         }
     }
+    fn push_entry(&mut self, key: Value, value: Value) {
+        let mut table = self.vector.reborrow().start_table();
+        table.push(key);
+        table.push(value);
+        let table = table.end_table();
+        self.vector.push(OneValue::Ref(table));
+    }
 }
 
 impl<'a, 'b> SerializeMap for MapBuilder<'a, 'b> {
     type Ok = Value;
     type Error = Error;
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<()>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.key = Some(key.serialize(self.vector.reborrow())?);
+        if self.sorted.is_some() {
+            self.sorted_key = Some(Content::new(key)?);
+        } else {
+            self.key = Some(key.serialize(self.vector.reborrow())?);
+        }
         Ok(())
     }
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
+        if let Some(sorted) = &mut self.sorted {
+            sorted.push((self.sorted_key.take().unwrap(), Content::new(value)?));
+            return Ok(());
+        }
         let key = self.key.take().unwrap();
         let value = value.serialize(self.vector.reborrow())?;
-        let mut table = self.vector.reborrow().start_table();
-        table.push(key);
-        table.push(value);
-        let table = table.end_table();
-        self.vector.push(OneValue::Ref(table));
+        self.push_entry(key, value);
         Ok(())
     }
-    fn end(self) -> Result<Value> { self.vector.end() }
+    fn end(mut self) -> Result<Value> {
+        if let Some(mut sorted) = self.sorted.take() {
+            sorted.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            for (key, value) in sorted.iter() {
+                let key = key.serialize(self.vector.reborrow())?;
+                let value = value.serialize(self.vector.reborrow())?;
+                self.push_entry(key, value);
+            }
+        }
+        self.vector.end()
+    }
 }
@@ -4,6 +4,7 @@ use serde::Serialize;
 use crate::ser::error::Error;
 use crate::ser::wrapper::{Serializer, Stack};
 
+pub mod content;
 pub mod enu;
 pub mod error;
 pub mod map;
@@ -14,11 +15,36 @@ pub mod wrapper;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Write the entries of every map in order of their keys, so that equal maps produce equal
+    /// bytes regardless of iteration order. Required by [`FlatMapView`](crate::view::FlatMapView).
+    ///
+    /// Keys are compared by the values their `Serialize` impl produces, not by their bytes. Keys of
+    /// different kinds are ordered by kind first: `bool`, then signed integers from `i8` to `i128`,
+    /// unsigned integers from `u8` to `u128`, `f32`, `f64`, `char`, strings, bytes, `None`, `Some`,
+    /// unit, unit structs, unit variants, newtype structs, newtype variants, sequences, tuples,
+    /// tuple structs, tuple variants, maps, structs and struct variants. So an `i64` key sorts
+    /// before every `u8` key, whatever their values. Keys of the same kind are compared by value:
+    /// integers numerically, floats by their IEEE 754 total order, strings and bytes
+    /// lexicographically, and everything else by its name, variant index and then contents in
+    /// order.
+    pub sort_maps: bool,
+}
+
 pub fn serialize_raw<'a, 'b, T: Serialize>(
     fbb: &'b mut FlatBufferBuilder<'a>,
     value: &T,
 ) -> crate::ser::Result<WIPOffset<UnionWIPOffset>> {
-    let mut stack = Stack::new();
+    serialize_raw_with(fbb, value, &Options::default())
+}
+
+pub fn serialize_raw_with<'a, 'b, T: Serialize>(
+    fbb: &'b mut FlatBufferBuilder<'a>,
+    value: &T,
+    options: &Options,
+) -> crate::ser::Result<WIPOffset<UnionWIPOffset>> {
+    let mut stack = Stack::new(options.clone());
     let mut serializer = Serializer::new(fbb, &mut stack);
     let value = serializer.serialize_to_offset(value)?;
     Ok(WIPOffset::new(value.value()))
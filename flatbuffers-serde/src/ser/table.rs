@@ -1,17 +1,20 @@
 use core::marker::Sized;
 use core::result::Result::Ok;
 
-use flatbuffers::{UnionWIPOffset, WIPOffset};
+use flatbuffers::{UnionWIPOffset, VOffsetT, WIPOffset};
 use serde::ser::{Serialize, SerializeStruct, SerializeTuple, SerializeTupleStruct};
 
 use crate::ser::error::Error;
 use crate::ser::value::{OneValue, Value};
 use crate::ser::wrapper::Serializer;
 use crate::ser::Result;
+use crate::slots::FieldIds;
 
 pub struct TableBuilder<'a, 'b> {
     serializer: Serializer<'a, 'b>,
     element_start: usize,
+    /// For a struct with [`FieldIds`], the id of each field pushed so far.
+    pinned: Option<(&'static FieldIds, Vec<u16>)>,
 }
 
 impl<'a, 'b> TableBuilder<'a, 'b> {
@@ -20,8 +23,14 @@ impl<'a, 'b> TableBuilder<'a, 'b> {
         TableBuilder {
             serializer,
             element_start: len,
+            pinned: None,
         }
     }
+    pub fn pinned(serializer: Serializer<'a, 'b>, ids: &'static FieldIds) -> Self {
+        let mut builder = TableBuilder::new(serializer);
+        builder.pinned = Some((ids, vec![]));
+        builder
+    }
     pub fn push(&mut self, value: Value) { self.serializer.stack.field_stack.push(value); }
     pub fn reborrow<'c>(&'c mut self) -> Serializer<'c, 'b> { self.serializer.reborrow() }
 }
@@ -30,12 +39,16 @@ impl<'a, 'b> TableBuilder<'a, 'b> {
     pub fn end_table(self) -> WIPOffset<UnionWIPOffset> {
         let table = self.serializer.fbb.start_table();
         let mut off = 4;
+        let mut ids = self.pinned.map(|(_, ids)| ids.into_iter());
         for element in self
             .serializer
             .stack
             .field_stack
             .drain(self.element_start..)
         {
+            if let Some(ids) = &mut ids {
+                off = 4 + 2 * ids.next().unwrap() as VOffsetT;
+            }
             match element {
                 Value::OneValue(element) => {
                     element.push_slot_always(self.serializer.fbb, off);
@@ -47,19 +60,22 @@ impl<'a, 'b> TableBuilder<'a, 'b> {
                     value.push_slot_always(self.serializer.fbb, off);
                     off += 2;
                 }
+                Value::Some(value) => {
+                    value.push_slot_present(self.serializer.fbb, off);
+                    off += 2;
+                }
             }
         }
-        let table = self.serializer.fbb.end_table(table).as_union_value();
-        table
+        self.serializer.fbb.end_table(table).as_union_value()
     }
 }
 
 impl<'a, 'b> SerializeTuple for TableBuilder<'a, 'b> {
     type Ok = Value;
     type Error = Error;
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let value = value.serialize(self.serializer.reborrow())?;
         self.serializer.stack.field_stack.push(value);
@@ -71,9 +87,9 @@ impl<'a, 'b> SerializeTuple for TableBuilder<'a, 'b> {
 impl<'a, 'b> SerializeTupleStruct for TableBuilder<'a, 'b> {
     type Ok = Value;
     type Error = Error;
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let value = value.serialize(self.serializer.reborrow())?;
         self.serializer.stack.field_stack.push(value);
@@ -85,11 +101,30 @@ impl<'a, 'b> SerializeTupleStruct for TableBuilder<'a, 'b> {
 impl<'a, 'b> SerializeStruct for TableBuilder<'a, 'b> {
     type Ok = Value;
     type Error = Error;
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        SerializeTupleStruct::serialize_field(self, value)
+        if let Some((ids, pushed)) = &mut self.pinned {
+            let id = ids
+                .id(key)
+                .ok_or_else(|| Error::MissingFieldId(format!("{}::{}", ids.struct_name(), key)))?;
+            pushed.push(id);
+            let value = value.serialize(self.serializer.reborrow_pinned())?;
+            if let Value::Enum { .. } = value {
+                if !ids.is_enum(key) {
+                    return Err(Error::UnmarkedEnum(format!(
+                        "{}::{}",
+                        ids.struct_name(),
+                        key
+                    )));
+                }
+            }
+            self.serializer.stack.field_stack.push(value);
+            Ok(())
+        } else {
+            SerializeTupleStruct::serialize_field(self, value)
+        }
     }
     fn end(self) -> Result<Value> { SerializeTupleStruct::end(self) }
 }
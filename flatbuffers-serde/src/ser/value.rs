@@ -21,7 +21,12 @@ pub enum OneValue {
 #[derive(Copy, Clone, Debug)]
 pub enum Value {
     OneValue(OneValue),
-    Enum { variant: VariantT, value: OneValue },
+    Enum {
+        variant: VariantT,
+        value: OneValue,
+    },
+    /// The contents of a `Some` in a pinned slot, which is written even if it is zero.
+    Some(OneValue),
 }
 
 impl Debug for OneValue {
@@ -52,13 +57,13 @@ impl Value {
                 builder.push(Value::Enum { variant, value });
                 OneValue::Ref(builder.end_table())
             }
+            Value::Some(x) => OneValue::SomeRef(x.to_offset(serializer.fbb, x)),
         }
     }
 
     pub fn to_offset(self, serializer: &mut Serializer) -> WIPOffset<UnionWIPOffset> {
         let value = self.to_one_value(serializer);
-        let value = value.to_offset(serializer.fbb, value);
-        value
+        value.to_offset(serializer.fbb, value)
     }
 }
 
@@ -77,6 +82,19 @@ impl OneValue {
         }
     }
 
+    /// Write the value into slot `off`, leaving the slot present even for zero or unit values.
+    pub fn push_slot_present(self, fbb: &mut FlatBufferBuilder, off: VOffsetT) {
+        match self {
+            OneValue::Fixed0 => fbb.push_slot_always(off, 0u8),
+            OneValue::Fixed8(x) => fbb.push_slot_always(off, x),
+            OneValue::Fixed16(x) => fbb.push_slot_always(off, x),
+            OneValue::Fixed32(x) => fbb.push_slot_always(off, x),
+            OneValue::Fixed64(x) => fbb.push_slot_always(off, x),
+            OneValue::Fixed128(x) => fbb.push_slot_always(off, Flat128(x)),
+            x => x.push_slot_always(fbb, off),
+        }
+    }
+
     pub fn to_offset(
         self,
         fbb: &mut FlatBufferBuilder,
@@ -66,7 +66,8 @@ impl<'a, 'b> VectorBuilder<'a, 'b> {
                 }
             }
         } else {
-            self.serializer.fbb.start_vector::<Flat128>(len);
+            // An empty vector has no elements to align, so only its length needs alignment.
+            self.serializer.fbb.start_vector::<UOffsetT>(len);
         }
         for element in iter.rev() {
             match element {
@@ -134,7 +135,7 @@ impl<'a, 'b> VectorBuilder<'a, 'b> {
         } else {
             self.serializer
                 .fbb
-                .end_vector::<Flat128>(len)
+                .end_vector::<UOffsetT>(len)
                 .as_union_value()
         };
         Ok(Value::OneValue(OneValue::Ref(vector)))
@@ -144,9 +145,9 @@ impl<'a, 'b> VectorBuilder<'a, 'b> {
 impl<'a, 'b> SerializeSeq for VectorBuilder<'a, 'b> {
     type Ok = Value;
     type Error = Error;
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let value = value.serialize(self.serializer.reborrow())?;
         let value = value.to_one_value(&mut self.serializer);
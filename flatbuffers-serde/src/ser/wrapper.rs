@@ -8,18 +8,21 @@ use crate::ser::map::MapBuilder;
 use crate::ser::table::TableBuilder;
 use crate::ser::value::{OneValue, Value};
 use crate::ser::vector::VectorBuilder;
-use crate::ser::{error, Result};
+use crate::ser::{error, Options, Result};
+use crate::slots::FieldIds;
 
 pub struct Stack {
     pub field_stack: Vec<Value>,
     pub vector_stack: Vec<OneValue>,
+    pub options: Options,
 }
 
 impl Stack {
-    pub fn new() -> Self {
+    pub fn new(options: Options) -> Self {
         Stack {
             field_stack: vec![],
             vector_stack: vec![],
+            options,
         }
     }
 }
@@ -27,16 +30,30 @@ impl Stack {
 pub struct Serializer<'a, 'b> {
     pub fbb: &'a mut FlatBufferBuilder<'b>,
     pub stack: &'a mut Stack,
+    /// Whether the value fills a pinned slot, where `Some` is written without indirection.
+    pub pinned_slot: bool,
 }
 
 impl<'a, 'b> Serializer<'a, 'b> {
     pub fn new(fbb: &'a mut FlatBufferBuilder<'b>, stack: &'a mut Stack) -> Self {
-        Serializer { fbb, stack }
+        Serializer {
+            fbb,
+            stack,
+            pinned_slot: false,
+        }
     }
     pub fn reborrow<'c>(&'c mut self) -> Serializer<'c, 'b> {
         Serializer {
             fbb: self.fbb,
             stack: self.stack,
+            pinned_slot: false,
+        }
+    }
+    pub fn reborrow_pinned<'c>(&'c mut self) -> Serializer<'c, 'b> {
+        Serializer {
+            fbb: self.fbb,
+            stack: self.stack,
+            pinned_slot: true,
         }
     }
     pub fn start_vector(mut self) -> VectorBuilder<'a, 'b> { VectorBuilder::new(self) }
@@ -109,11 +126,20 @@ impl<'a, 'b> serde::Serializer for Serializer<'a, 'b> {
         )))
     }
     fn serialize_none(mut self) -> Result<Self::Ok> { Ok(Value::OneValue(OneValue::NoneRef)) }
-    fn serialize_some<T: ?Sized>(mut self, value: &T) -> Result<Self::Ok>
+    fn serialize_some<T>(mut self, value: &T) -> Result<Self::Ok>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let value = value.serialize(self.reborrow())?;
+        if self.pinned_slot {
+            let value = match value.to_one_value(&mut self) {
+                x @ (OneValue::SomeRef(_) | OneValue::NoneRef) => {
+                    OneValue::Ref(x.to_offset(self.fbb, x))
+                }
+                x => x,
+            };
+            return Ok(Value::Some(value));
+        }
         let value = Value::OneValue(OneValue::SomeRef(value.to_offset(&mut self)));
         Ok(value)
     }
@@ -132,13 +158,13 @@ impl<'a, 'b> serde::Serializer for Serializer<'a, 'b> {
             value: OneValue::Fixed0,
         })
     }
-    fn serialize_newtype_struct<T: ?Sized>(mut self, name: &'static str, value: &T) -> Result<Value>
+    fn serialize_newtype_struct<T>(mut self, name: &'static str, value: &T) -> Result<Value>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(self)
     }
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         mut self,
         name: &'static str,
         variant_index: u32,
@@ -146,7 +172,7 @@ impl<'a, 'b> serde::Serializer for Serializer<'a, 'b> {
         value: &T,
     ) -> Result<Value>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let value = value.serialize(self.reborrow())?;
         let value = value.to_one_value(&mut self);
@@ -181,7 +207,10 @@ impl<'a, 'b> serde::Serializer for Serializer<'a, 'b> {
         Ok(MapBuilder::new(self))
     }
     fn serialize_struct(mut self, name: &'static str, len: usize) -> Result<TableBuilder<'a, 'b>> {
-        Ok(self.start_table())
+        match FieldIds::lookup(name) {
+            Some(ids) => Ok(TableBuilder::pinned(self, ids)),
+            None => Ok(self.start_table()),
+        }
     }
     fn serialize_struct_variant(
         mut self,
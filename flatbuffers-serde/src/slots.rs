@@ -0,0 +1,122 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+
+use catalog::{BuilderFrom, Registry};
+use flatbuffers::VOffsetT;
+
+/// Slot numbers pinned to the fields of a struct by [`field_ids!`](crate::field_ids), so that
+/// fields can be added, removed or reordered without breaking buffers that were already written.
+///
+/// Structs are found by the name that `Serialize` and `Deserialize` give them, which is the name
+/// of the Rust type unless it is renamed. That name must be unique: every struct serialized under
+/// it uses these ids, whatever module it is in, and registering two `FieldIds` under one name
+/// panics.
+///
+/// Every field needs an id. An enum field is marked `enum` and takes up two slots, `id` for the
+/// variant and `id + 1` for the value. Registering fields whose slots overlap panics.
+///
+/// `Option` fields are written differently in a pinned struct. There, `Some(x)` is written
+/// directly into the slot of its field and `None` leaves it absent, so a field can become optional
+/// without breaking existing buffers. Everywhere else, including unpinned structs, `Some(x)` is an
+/// offset to a copy of `x`. Pinning a struct therefore changes how its existing buffers are read
+/// if it has `Option` fields.
+pub struct FieldIds {
+    struct_name: &'static str,
+    /// The name, id and whether it is an enum of each field.
+    fields: &'static [(&'static str, u16, bool)],
+}
+
+pub struct FieldIdSet {
+    by_struct_name: HashMap<&'static str, &'static FieldIds>,
+}
+
+pub static FIELD_IDS: Registry<FieldIdSet> = Registry::new();
+
+impl FieldIds {
+    pub const fn new(
+        struct_name: &'static str,
+        fields: &'static [(&'static str, u16, bool)],
+    ) -> Self {
+        FieldIds {
+            struct_name,
+            fields,
+        }
+    }
+    pub fn lookup(struct_name: &str) -> Option<&'static FieldIds> {
+        FIELD_IDS.by_struct_name.get(struct_name).cloned()
+    }
+    pub fn struct_name(&self) -> &'static str { self.struct_name }
+    pub fn id(&self, field: &str) -> Option<u16> {
+        self.fields
+            .iter()
+            .find(|(name, _, _)| *name == field)
+            .map(|(_, id, _)| *id)
+    }
+    /// Whether `field` is marked as an enum, taking up two slots.
+    pub fn is_enum(&self, field: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|(name, _, is_enum)| *name == field && *is_enum)
+    }
+    /// Panic if two fields have the same name or share a slot, or if a slot does not fit in a
+    /// vtable.
+    pub(crate) fn check(&self) {
+        let mut names = HashSet::new();
+        let mut slots = HashMap::new();
+        for &(name, id, is_enum) in self.fields {
+            if !names.insert(name) {
+                panic!("Duplicate field id for {}::{}", self.struct_name, name);
+            }
+            for slot in id as usize..=id as usize + is_enum as usize {
+                if 4 + 2 * slot > VOffsetT::MAX as usize {
+                    panic!(
+                        "Slot {} of {}::{} is too large",
+                        slot, self.struct_name, name
+                    );
+                }
+                if let Some(other) = slots.insert(slot, name) {
+                    panic!(
+                        "Collision of slot {} for {}::{} and {}::{}",
+                        slot, self.struct_name, name, self.struct_name, other
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl catalog::Builder for FieldIdSet {
+    type Output = FieldIdSet;
+    fn new() -> Self {
+        FieldIdSet {
+            by_struct_name: HashMap::new(),
+        }
+    }
+    fn build(self) -> Self::Output { self }
+}
+
+impl BuilderFrom<&'static FieldIds> for FieldIdSet {
+    fn insert(&mut self, ids: &'static FieldIds) {
+        ids.check();
+        if let Some(other) = self.by_struct_name.insert(ids.struct_name, ids) {
+            panic!("Collision of struct name for {:?} and {:?}", ids, other);
+        }
+    }
+}
+
+impl Debug for FieldIds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldIds")
+            .field("struct_name", &self.struct_name)
+            .field("fields", &self.fields)
+            .finish()
+    }
+}
+
+impl Debug for FieldIdSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.by_struct_name.values())
+            .finish()
+    }
+}
@@ -1,15 +1,21 @@
-use std::any::{type_name, TypeId};
+use std::any::{type_name, Any, TypeId};
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 
-use flatbuffers::{ForwardsUOffset, InvalidFlatbuffer, Verifiable, Verifier, WIPOffset};
+use catalog::{BuilderFrom, Registry};
+use flatbuffers::{
+    FlatBufferBuilder, ForwardsUOffset, InvalidFlatbuffer, UnionWIPOffset, Verifiable, Verifier,
+    WIPOffset,
+};
 use lazy_static::lazy_static;
-use registry::{registry, BuilderFrom, Registry};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 use crate::any_generated::TypeTagHash;
+use crate::de::deserialize_raw;
+use crate::ser::serialize_raw;
 
 #[derive(Debug)]
 pub struct TypeTag {
@@ -18,6 +24,7 @@ pub struct TypeTag {
     native_name: &'static str,
     native_id: TypeId,
     flat_type_tag: Option<FlatTypeTag>,
+    serde_type_tag: Option<SerdeTypeTag>,
 }
 
 #[derive(Copy, Clone)]
@@ -26,6 +33,21 @@ pub struct FlatTypeTag {
         for<'a, 'b, 'c> fn(&'a mut Verifier<'b, 'c>, loc: usize) -> Result<(), InvalidFlatbuffer>,
 }
 
+/// Reads and writes a type with `serialize_raw` and `deserialize_raw` without naming it, for
+/// [`AnyFlat::serialize_any`](crate::any_generated::AnyFlat::serialize_any) and
+/// [`AnyFlat::deserialize_any`](crate::any_generated::AnyFlat::deserialize_any).
+#[derive(Copy, Clone)]
+pub struct SerdeTypeTag {
+    serialize: SerializeDyn,
+    deserialize: DeserializeDyn,
+}
+
+type SerializeDyn = for<'a, 'b, 'c> fn(
+    &'a mut FlatBufferBuilder<'b>,
+    &'c dyn Any,
+) -> crate::ser::Result<WIPOffset<UnionWIPOffset>>;
+type DeserializeDyn = for<'a> fn(&'a [u8], usize) -> crate::de::Result<Box<dyn Any>>;
+
 pub struct TypeTagSet {
     by_native_id: HashMap<TypeId, &'static TypeTag>,
     by_hash: HashMap<TypeTagHash, &'static TypeTag>,
@@ -36,6 +58,8 @@ pub trait HasTypeTag {
     fn type_tag() -> &'static TypeTag;
 }
 
+/// # Safety
+/// The tag's verifier must accept only buffers that `Self` can follow.
 pub unsafe trait HasFlatTypeTag {
     fn flat_type_tag() -> &'static FlatTypeTag;
 }
@@ -48,6 +72,7 @@ impl TypeTag {
     pub fn native_name(&self) -> &'static str { self.native_name }
     pub fn native_id(&self) -> TypeId { self.native_id }
     pub fn flat_type_tag(&self) -> Option<FlatTypeTag> { self.flat_type_tag }
+    pub fn serde_type_tag(&self) -> Option<SerdeTypeTag> { self.serde_type_tag }
 }
 
 impl FlatTypeTag {
@@ -60,7 +85,41 @@ impl FlatTypeTag {
     }
 }
 
-impl registry::Builder for TypeTagSet {
+impl SerdeTypeTag {
+    pub const fn new<T: 'static + Serialize + for<'de> Deserialize<'de>>() -> Self {
+        SerdeTypeTag {
+            serialize: serialize_dyn::<T>,
+            deserialize: deserialize_dyn::<T>,
+        }
+    }
+    /// Write `value`, which must be of the type this tag was created for.
+    pub fn serialize<'a, 'b>(
+        &self,
+        fbb: &'a mut FlatBufferBuilder<'b>,
+        value: &dyn Any,
+    ) -> crate::ser::Result<WIPOffset<UnionWIPOffset>> {
+        (self.serialize)(fbb, value)
+    }
+    pub fn deserialize(&self, buf: &[u8], loc: usize) -> crate::de::Result<Box<dyn Any>> {
+        (self.deserialize)(buf, loc)
+    }
+}
+
+fn serialize_dyn<'a, 'b, T: 'static + Serialize>(
+    fbb: &'a mut FlatBufferBuilder<'b>,
+    value: &dyn Any,
+) -> crate::ser::Result<WIPOffset<UnionWIPOffset>> {
+    serialize_raw(fbb, value.downcast_ref::<T>().unwrap())
+}
+
+fn deserialize_dyn<T: 'static + for<'de> Deserialize<'de>>(
+    buf: &[u8],
+    loc: usize,
+) -> crate::de::Result<Box<dyn Any>> {
+    Ok(Box::new(deserialize_raw::<T>(buf, loc)?))
+}
+
+impl catalog::Builder for TypeTagSet {
     type Output = TypeTagSet;
     fn new() -> Self {
         TypeTagSet {
@@ -75,31 +134,39 @@ impl registry::Builder for TypeTagSet {
 impl<T: HasTypeTag> BuilderFrom<PhantomData<T>> for TypeTagSet {
     fn insert(&mut self, _: PhantomData<T>) {
         let tag = T::type_tag();
-        self.by_native_id.insert(tag.native_id, tag).map(|other| {
+        if let Some(other) = self.by_native_id.insert(tag.native_id, tag) {
             panic!("Collision of TypeId for {:?} and {:?}", tag, other);
-        });
-        self.by_hash.insert(tag.type_tag_hash, tag).map(|other| {
+        }
+        if let Some(other) = self.by_hash.insert(tag.type_tag_hash, tag) {
             panic!("Collision of TypeTagHash for {:?} and {:?}", tag, other);
-        });
-        self.by_name.insert(tag.type_tag_name, tag).map(|other| {
+        }
+        if let Some(other) = self.by_name.insert(tag.type_tag_name, tag) {
             panic!("Collision of name for {:?} and {:?}", tag, other);
-        });
+        }
     }
 }
 
 impl TypeTag {
-    pub fn new<T: 'static>(name: &'static str, flat_type_tag: Option<FlatTypeTag>) -> Self {
+    pub fn new<T: 'static>(
+        name: &'static str,
+        flat_type_tag: Option<FlatTypeTag>,
+        serde_type_tag: Option<SerdeTypeTag>,
+    ) -> Self {
         TypeTag {
             type_tag_hash: TypeTagHash::new(name),
             type_tag_name: name,
             native_name: type_name::<T>(),
             native_id: TypeId::of::<T>(),
             flat_type_tag,
+            serde_type_tag,
         }
     }
     pub fn lookup_hash(hash: TypeTagHash) -> Result<&'static Self, TypeTagHash> {
         TYPE_TAGS.by_hash.get(&hash).cloned().ok_or(hash)
     }
+    pub fn lookup_native_id(id: TypeId) -> Option<&'static Self> {
+        TYPE_TAGS.by_native_id.get(&id).cloned()
+    }
 }
 
 impl FlatTypeTag {
@@ -114,19 +181,17 @@ unsafe impl<T: HasFlatTypeTag> HasFlatTypeTag for WIPOffset<T> {
     fn flat_type_tag() -> &'static FlatTypeTag { T::flat_type_tag() }
 }
 
-registry! {
-    value TYPE_TAGS => type_tag!(type u8, name "std::u8", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type u16, name "std::u16", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type u32, name "std::u32", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type u64, name "std::u64", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type u128, name "std::u128", kinds [serde]);
-    value TYPE_TAGS => type_tag!(type i8, name "std::i8", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type i16, name "std::i16", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type i32, name "std::i32", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type i64, name "std::i64", kinds [flat, serde]);
-    value TYPE_TAGS => type_tag!(type i128, name "std::i128", kinds [serde]);
-    value TYPE_TAGS => type_tag!(type String, name "std::string::String", kinds [serde]);
-}
+type_tag!(type u8, name "std::u8", kinds [flat, serde]);
+type_tag!(type u16, name "std::u16", kinds [flat, serde]);
+type_tag!(type u32, name "std::u32", kinds [flat, serde]);
+type_tag!(type u64, name "std::u64", kinds [flat, serde]);
+type_tag!(type u128, name "std::u128", kinds [serde]);
+type_tag!(type i8, name "std::i8", kinds [flat, serde]);
+type_tag!(type i16, name "std::i16", kinds [flat, serde]);
+type_tag!(type i32, name "std::i32", kinds [flat, serde]);
+type_tag!(type i64, name "std::i64", kinds [flat, serde]);
+type_tag!(type i128, name "std::i128", kinds [serde]);
+type_tag!(type String, name "std::string::String", kinds [serde]);
 
 impl Debug for TypeTagSet {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -141,3 +206,12 @@ impl Debug for FlatTypeTag {
             .finish()
     }
 }
+
+impl Debug for SerdeTypeTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SerdeTypeTag")
+            .field("serialize", &())
+            .field("deserialize", &())
+            .finish()
+    }
+}
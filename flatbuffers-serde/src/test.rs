@@ -1,17 +1,23 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
+use std::num::NonZeroU32;
 
-use flatbuffers::{root, root_unchecked, FlatBufferBuilder, WIPOffset};
+use flatbuffers::{root, root_unchecked, FlatBufferBuilder, InvalidFlatbuffer, WIPOffset};
 use itertools::Itertools;
-use registry::registry;
 use serde::{Deserialize, Serialize};
-use serde_bytes::ByteBuf;
+use serde_bytes::{ByteBuf, Bytes};
 
-use crate::any_generated::AnyFlat;
+use crate::any::UnknownFlat;
+use crate::any_generated::{AnyFlat, TypeTagHash};
+use crate::buffer::{FlatBuffer, Serde};
 use crate::de::deserialize_raw;
+use crate::de::error::Error;
 use crate::flat_util::FlatUnion;
-use crate::ser::serialize_raw;
+use crate::ser::{serialize_raw, Options};
+use crate::slots::FieldIds;
 use crate::tag::{HasTypeTag, TYPE_TAGS};
+use crate::vec_slice::VecSlice;
+use crate::view::FlatView;
 
 fn run_raw_test<T: Serialize + for<'de> Deserialize<'de> + Eq + Debug>(value: T, expected: &[u8]) {
     println!("\nSerializing {:?}", value);
@@ -242,6 +248,439 @@ fn raw_test() {
     );
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct Borrowed<'a> {
+    name: &'a str,
+    #[serde(borrow)]
+    data: &'a Bytes,
+    #[serde(borrow)]
+    tags: Vec<&'a str>,
+    #[serde(borrow)]
+    nick: Option<&'a str>,
+}
+
+#[test]
+fn borrowed_test() {
+    let value = Borrowed {
+        name: "abc",
+        data: Bytes::new(&[55, 66, 77]),
+        tags: vec!["x", "yz"],
+        nick: Some("n"),
+    };
+    let mut fbb = FlatBufferBuilder::new();
+    let any_off = serialize_raw(&mut fbb, &value).unwrap();
+    fbb.finish_minimal(any_off);
+    let data = fbb.finished_data();
+    let any = unsafe { root_unchecked::<FlatUnion>(data) };
+    let actual = deserialize_raw::<Borrowed>(any.buf, any.loc).unwrap();
+    assert_eq!(actual, value);
+    let range = data.as_ptr_range();
+    assert!(range.contains(&actual.name.as_ptr()));
+    assert!(range.contains(&actual.data.as_ptr()));
+    assert!(range.contains(&actual.tags[1].as_ptr()));
+    assert!(range.contains(&actual.nick.unwrap().as_ptr()));
+
+    let mut fbb = FlatBufferBuilder::new();
+    let any_off = serialize_raw(&mut fbb, &ByteBuf::from(vec![0xff])).unwrap();
+    fbb.finish_minimal(any_off);
+    let any = unsafe { root_unchecked::<FlatUnion>(fbb.finished_data()) };
+    let result = deserialize_raw::<&str>(any.buf, any.loc);
+    assert!(matches!(result, Err(Error::BadUtf8)));
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct ViewInner {
+    d: Result<u8, u16>,
+    c: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct ViewMiddle {
+    x: Result<u8, u8>,
+    b: Vec<ViewInner>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct ViewOuter {
+    first: u32,
+    a: ViewMiddle,
+    flag: bool,
+    maybe: Option<ViewMiddle>,
+    pair: (u8, String),
+    /// Cannot be built from an absent value, so it is only read when on the path.
+    count: NonZeroU32,
+}
+
+#[test]
+fn view_test() {
+    let inner = |c: &str| ViewInner {
+        d: Err(7),
+        c: c.to_string(),
+    };
+    let value = ViewOuter {
+        first: 42,
+        a: ViewMiddle {
+            x: Err(3),
+            b: vec![inner("zero"), inner("one"), inner("two")],
+        },
+        flag: true,
+        maybe: None,
+        pair: (5, "five".to_string()),
+        count: NonZeroU32::new(9).unwrap(),
+    };
+    let view = FlatView::new(FlatBuffer::serialize(&value).unwrap());
+    assert_eq!(view.get::<u32>("first").unwrap(), Some(42));
+    assert_eq!(view.get::<bool>("flag").unwrap(), Some(true));
+    assert_eq!(view.get::<Result<u8, u8>>("a.x").unwrap(), Some(Err(3)));
+    assert_eq!(view.get::<&str>("a.b[1].c").unwrap(), Some("one"));
+    assert_eq!(
+        view.get::<Result<u8, u16>>("a.b[2].d").unwrap(),
+        Some(Err(7))
+    );
+    assert_eq!(view.get::<ViewInner>("a.b[2]").unwrap(), Some(inner("two")));
+    assert_eq!(view.get::<String>("a.b[3].c").unwrap(), None);
+    assert_eq!(view.get::<String>("maybe.b[0].c").unwrap(), None);
+    assert_eq!(
+        view.get::<String>("pair[1]").unwrap(),
+        Some("five".to_string())
+    );
+    assert_eq!(view.get::<u32>("count").unwrap(), Some(9));
+    assert!(matches!(view.get::<u8>("a.y"), Err(Error::BadPath(_))));
+    assert!(matches!(view.get::<u8>("first.y"), Err(Error::BadPath(_))));
+    assert!(matches!(view.get::<u8>("a.b[x]"), Err(Error::BadPath(_))));
+    assert_eq!(view.into_inner().deserialize().unwrap(), value);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct Index {
+    name: String,
+    counts: HashMap<String, u32>,
+    ids: HashMap<i64, Vec<String>>,
+}
+
+#[test]
+fn sorted_map_test() {
+    let words = [
+        "apple",
+        "banana",
+        "cherry",
+        "date",
+        "elderberry",
+        "fig",
+        "grape",
+    ];
+    let index = || Index {
+        name: "fruit".to_string(),
+        counts: words
+            .iter()
+            .map(|x| (x.to_string(), x.len() as u32))
+            .collect(),
+        ids: (-20i64..20)
+            .map(|x| (x * 7, vec![words[x.rem_euclid(7) as usize].to_string()]))
+            .collect(),
+    };
+    let options = Options { sort_maps: true };
+    let first = FlatBuffer::serialize_with(&index(), &options).unwrap();
+    let second = FlatBuffer::serialize_with(&index(), &options).unwrap();
+    assert_eq!(first.as_slice(), second.as_slice());
+    let sorted: BTreeMap<_, _> = index().counts.into_iter().collect();
+    assert_eq!(
+        FlatBuffer::serialize_with(&index().counts, &options)
+            .unwrap()
+            .as_slice(),
+        FlatBuffer::serialize(&sorted).unwrap().as_slice()
+    );
+    assert_eq!(first.deserialize().unwrap(), index());
+
+    let view = FlatView::new(first);
+    let counts = view.map::<&str, u32>("counts").unwrap().unwrap();
+    assert_eq!(counts.len(), words.len());
+    for word in words {
+        assert_eq!(counts.get(&word).unwrap(), Some(word.len() as u32));
+    }
+    assert_eq!(counts.get(&"aardvark").unwrap(), None);
+    assert_eq!(counts.get(&"zucchini").unwrap(), None);
+    let ids = view.map::<i64, Vec<String>>("ids").unwrap().unwrap();
+    assert_eq!(ids.get(&-140).unwrap(), Some(vec!["banana".to_string()]));
+    assert_eq!(ids.get(&14).unwrap(), Some(vec!["cherry".to_string()]));
+    assert_eq!(ids.get(&15).unwrap(), None);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename = "Record")]
+struct RecordV1 {
+    id: u32,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename = "Record")]
+struct RecordV2 {
+    id: u32,
+    parent: Option<u32>,
+    name: String,
+    kind: Result<u8, String>,
+    flag: Option<()>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename = "Record")]
+struct RecordReordered {
+    name: String,
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct Unpinned {
+    id: u32,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct UnmarkedEnum {
+    kind: Option<Result<u8, String>>,
+    value: Result<u8, String>,
+}
+
+field_ids!(UnmarkedEnum { kind = 0, value = 1 });
+
+field_ids!(Record {
+    id = 0,
+    name = 1,
+    parent = 2,
+    kind = 3 enum,
+    flag = 5,
+});
+
+/// Read a buffer written from a `U` as a `T`.
+fn reread<'a, T: Deserialize<'a>, U>(buffer: &'a FlatBuffer<Serde<U>>) -> T {
+    let root = buffer.root();
+    deserialize_raw(root.buf, root.loc).unwrap()
+}
+
+#[test]
+fn field_ids_test() {
+    let v1 = RecordV1 {
+        id: 7,
+        name: "seven".to_string(),
+    };
+    let v1_buffer = FlatBuffer::serialize(&v1).unwrap();
+    assert_eq!(
+        reread::<RecordV2, _>(&v1_buffer),
+        RecordV2 {
+            id: 7,
+            parent: None,
+            name: "seven".to_string(),
+            kind: Ok(0),
+            flag: None,
+        }
+    );
+    assert_eq!(
+        reread::<RecordReordered, _>(&v1_buffer),
+        RecordReordered {
+            name: "seven".to_string(),
+            id: 7,
+        }
+    );
+
+    let v2 = RecordV2 {
+        id: 8,
+        parent: Some(0),
+        name: "eight".to_string(),
+        kind: Err("error".to_string()),
+        flag: Some(()),
+    };
+    let v2_buffer = FlatBuffer::serialize(&v2).unwrap();
+    assert_eq!(v2_buffer.deserialize().unwrap(), v2);
+    assert_eq!(
+        reread::<RecordV1, _>(&v2_buffer),
+        RecordV1 {
+            id: 8,
+            name: "eight".to_string(),
+        }
+    );
+    let view = FlatView::new(v2_buffer);
+    assert_eq!(view.get::<u32>("parent").unwrap(), Some(0));
+    assert_eq!(view.get::<&str>("name").unwrap(), Some("eight"));
+
+    let unpinned = Unpinned { id: 9 };
+    assert_eq!(
+        FlatBuffer::serialize(&unpinned)
+            .unwrap()
+            .deserialize()
+            .unwrap(),
+        unpinned
+    );
+
+    let unmarked = UnmarkedEnum {
+        kind: Some(Ok(1)),
+        value: Ok(2),
+    };
+    assert!(matches!(
+        FlatBuffer::serialize(&unmarked),
+        Err(crate::ser::error::Error::UnmarkedEnum(_))
+    ));
+
+    static OVERLAPPING: FieldIds = FieldIds::new("Overlapping", &[("a", 0, true), ("b", 1, false)]);
+    assert!(std::panic::catch_unwind(|| OVERLAPPING.check()).is_err());
+    static DUPLICATE: FieldIds = FieldIds::new("Duplicate", &[("a", 0, false), ("a", 1, false)]);
+    assert!(std::panic::catch_unwind(|| DUPLICATE.check()).is_err());
+}
+
+/// Read a `T` from untrusted `bytes`.
+fn read_checked<T: for<'de> Deserialize<'de>>(bytes: Vec<u8>) -> crate::de::Result<T> {
+    FlatBuffer::<Serde<T>>::from_vec_slice(VecSlice::from(bytes))?.deserialize()
+}
+
+/// Check that a buffer of `value` is accepted and read back, that every prefix of it is rejected
+/// unless it only cuts off padding, and that every corruption of a single byte is either rejected
+/// or still reads as a `T`, with corruptions of the root offset rejected.
+fn run_verify_test<T: Serialize + for<'de> Deserialize<'de> + Eq + Debug>(value: T) {
+    let bytes = FlatBuffer::serialize(&value).unwrap().as_slice().to_vec();
+    assert_eq!(read_checked::<T>(bytes.clone()).unwrap(), value);
+    for len in 0..bytes.len() {
+        // The first value written ends the buffer and may be followed by alignment padding.
+        if let Ok(buffer) = FlatBuffer::<Serde<T>>::from_vec_slice(bytes[..len].to_vec().into()) {
+            assert!(bytes.len() - len < 16, "prefix of {} bytes", len);
+            assert_eq!(buffer.deserialize().unwrap(), value);
+        }
+    }
+    let mut rejected = 0;
+    for index in 0..bytes.len() {
+        let mut corrupt = bytes.clone();
+        corrupt[index] ^= 0xFF;
+        match FlatBuffer::<Serde<T>>::from_vec_slice(corrupt.into()) {
+            Ok(buffer) => {
+                assert!(index >= 4, "corrupt root offset");
+                buffer.deserialize().unwrap();
+            }
+            Err(_) => rejected += 1,
+        }
+    }
+    assert!(rejected > 4);
+}
+
+#[test]
+fn verify_test() {
+    run_verify_test(Index {
+        name: "fruit".to_string(),
+        counts: [("apple", 5), ("fig", 3)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), *v))
+            .collect(),
+        ids: (0..5)
+            .map(|x| (x, vec![x.to_string(); x as usize]))
+            .collect(),
+    });
+    run_verify_test(RecordV2 {
+        id: 8,
+        parent: Some(0),
+        name: "eight".to_string(),
+        kind: Err("error".to_string()),
+        flag: Some(()),
+    });
+
+    let mut bytes = FlatBuffer::serialize(&RecordV1 {
+        id: 1,
+        name: "one".to_string(),
+    })
+    .unwrap()
+    .as_slice()
+    .to_vec();
+    bytes[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        FlatBuffer::<Serde<RecordV1>>::from_vec_slice(bytes.into()),
+        Err(Error::Invalid(InvalidFlatbuffer::RangeOutOfBounds { .. }))
+    ));
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Stats {
+    hits: u64,
+    ratio: f32,
+    scores: Vec<i16>,
+    best: Option<u32>,
+    name: String,
+    enabled: bool,
+}
+
+#[test]
+fn mutate_test() {
+    let stats = Stats {
+        hits: 3,
+        ratio: 0.5,
+        scores: vec![1, 2, 3],
+        best: Some(2),
+        name: "stats".to_string(),
+        enabled: true,
+    };
+    let mut buffer = FlatBuffer::serialize(&stats).unwrap();
+    buffer.mutate_field("hits", &4u64).unwrap();
+    buffer.mutate_field("ratio", &0.25f32).unwrap();
+    buffer.mutate_field("scores[1]", &-7i16).unwrap();
+    buffer.mutate_field("best", &9u32).unwrap();
+    buffer.mutate_field("enabled", &false).unwrap();
+    assert_eq!(
+        buffer.deserialize().unwrap(),
+        Stats {
+            hits: 4,
+            ratio: 0.25,
+            scores: vec![1, -7, 3],
+            best: Some(9),
+            name: "stats".to_string(),
+            enabled: false,
+        }
+    );
+    assert!(matches!(
+        buffer.mutate_field("hits", &1u32),
+        Err(Error::ScalarMismatch { .. })
+    ));
+    assert!(matches!(
+        buffer.mutate_field("best", &1.5f32),
+        Err(Error::ScalarMismatch { .. })
+    ));
+    assert!(matches!(
+        buffer.mutate_field("enabled", &2u8),
+        Err(Error::ScalarMismatch { .. })
+    ));
+    assert!(matches!(
+        buffer.mutate_field("name", &1u32),
+        Err(Error::Unsupported)
+    ));
+    assert!(matches!(
+        buffer.mutate_field("hits", &"x"),
+        Err(Error::Unsupported)
+    ));
+
+    let mut buffer = FlatBuffer::serialize(&Stats {
+        hits: 0,
+        ratio: 0.0,
+        scores: vec![],
+        best: None,
+        name: "".to_string(),
+        enabled: false,
+    })
+    .unwrap();
+    assert!(matches!(
+        buffer.mutate_field("hits", &1u64),
+        Err(Error::MissingSlot(_))
+    ));
+    assert!(matches!(
+        buffer.mutate_field("best", &1u32),
+        Err(Error::MissingSlot(_))
+    ));
+
+    let mut buffer = FlatBuffer::serialize(&RecordV2 {
+        id: 0,
+        parent: Some(0),
+        name: "zero".to_string(),
+        kind: Ok(0),
+        flag: None,
+    })
+    .unwrap();
+    buffer.mutate_field("parent", &3u32).unwrap();
+    assert_eq!(buffer.deserialize().unwrap().parent, Some(3));
+}
+
 fn run_serde_test<T: Serialize + for<'de> Deserialize<'de> + Eq + Debug + HasTypeTag>(value: T) {
     let mut fbb = FlatBufferBuilder::new();
     let any = AnyFlat::serialize(&mut fbb, &value).unwrap();
@@ -254,14 +693,70 @@ fn run_serde_test<T: Serialize + for<'de> Deserialize<'de> + Eq + Debug + HasTyp
 }
 
 #[test]
-fn serde_test() {
-    crate::REGISTRY.build();
-    run_serde_test(42u8);
+fn serde_test() { run_serde_test(42u8); }
+
+#[test]
+fn any_test() {
+    let mut fbb = FlatBufferBuilder::new();
+    let any = AnyFlat::serialize_any(&mut fbb, &"hello".to_string()).unwrap();
+    fbb.finish_minimal(any);
+    let any = root::<AnyFlat>(fbb.finished_data()).unwrap();
+    let value = any.deserialize_any().unwrap();
+    assert_eq!(value.downcast_ref::<String>().unwrap(), "hello");
+
+    let mut fbb = FlatBufferBuilder::new();
+    assert!(AnyFlat::serialize_any(&mut fbb, &Unpinned { id: 1 }).is_err());
+
+    let unregistered = TypeTagHash::new("unregistered");
+    let mut fbb = FlatBufferBuilder::new();
+    serialize_raw(&mut fbb, &vec![1u128, 2, 3]).unwrap();
+    let data = serialize_raw(
+        &mut fbb,
+        &RecordV1 {
+            id: 5,
+            name: "five".to_string(),
+        },
+    )
+    .unwrap();
+    let any = AnyFlat::create_raw(&mut fbb, unregistered, data);
+    fbb.finish_minimal(any);
+    let any = root::<AnyFlat>(fbb.finished_data()).unwrap();
+    let unknown = any.deserialize_any().unwrap();
+    assert_eq!(
+        unknown
+            .downcast_ref::<UnknownFlat>()
+            .unwrap()
+            .type_tag_hash(),
+        unregistered
+    );
+
+    // Point `data` back into the table, and then past the end of the buffer.
+    let field = any.table.loc + any.table.vtable().get(AnyFlat::VT_DATA) as usize;
+    for offset in [0u32, u32::MAX] {
+        let mut bytes = fbb.finished_data().to_vec();
+        bytes[field..field + 4].copy_from_slice(&offset.to_le_bytes());
+        let any = unsafe { root_unchecked::<AnyFlat>(&bytes) };
+        assert!(matches!(any.deserialize_any(), Err(Error::Invalid(_))));
+    }
+
+    let mut fbb = FlatBufferBuilder::new();
+    fbb.push(7u8);
+    let any = AnyFlat::serialize_any(&mut fbb, &*unknown).unwrap();
+    fbb.finish_minimal(any);
+    let any = root::<AnyFlat>(fbb.finished_data()).unwrap();
+    assert_eq!(any.type_tag_hash(), Some(&unregistered));
+    let data = unsafe { any.data_raw::<FlatUnion>() }.unwrap();
+    assert_eq!(
+        deserialize_raw::<RecordV1>(data.buf, data.loc).unwrap(),
+        RecordV1 {
+            id: 5,
+            name: "five".to_string(),
+        }
+    );
 }
 
 #[test]
 fn flat_test() {
-    crate::REGISTRY.build();
     let mut fbb = FlatBufferBuilder::new();
     let value = 42u8;
     let any = fbb.push(value);
@@ -276,7 +771,6 @@ fn flat_test() {
 
 #[test]
 fn flat_test2() {
-    crate::REGISTRY.build();
     let mut fbb = FlatBufferBuilder::new();
     let value = 42u8;
     let any = fbb.push(value);
@@ -18,6 +18,7 @@ impl VecSlice {
     }
     pub fn from_vec(vec: Vec<u8>, head: usize) -> Self { VecSlice { vec, head } }
     pub fn len(&self) -> usize { self.vec.len() - self.head }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
     pub fn into_vec(self) -> Vec<u8> { self.vec }
     pub fn clear(&mut self) {
         self.vec.clear();
@@ -52,6 +53,10 @@ impl AsRef<[u8]> for VecSlice {
     fn as_ref(&self) -> &[u8] { &self.vec[self.head..] }
 }
 
+impl AsMut<[u8]> for VecSlice {
+    fn as_mut(&mut self) -> &mut [u8] { &mut self.vec[self.head..] }
+}
+
 impl Serialize for VecSlice {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
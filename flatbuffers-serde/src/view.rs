@@ -0,0 +1,637 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use flatbuffers::{Follow, ForwardsUOffset};
+use serde::de::{DeserializeSeed, SeqAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize, Serialize};
+
+use crate::buffer::{FlatBuffer, Serde};
+use crate::de::error::Error;
+use crate::de::identity::IdentityDeserializer;
+use crate::de::none::NoneDeserializer;
+use crate::de::skip::SkipDeserializer;
+use crate::de::table::TableDeserializer;
+use crate::de::vector::VectorDeserializer;
+use crate::de::verify::{check_range, FollowChecked};
+use crate::de::wrapper::{Deserializer, FlatDeserializer};
+use crate::flat_util::{Flat128, FlatUnion};
+use crate::ser::content::Content;
+use crate::slots::FieldIds;
+
+/// Reads individual fields of a `T` written by [`serialize_raw`](crate::ser::serialize_raw)
+/// without deserializing the rest of it.
+///
+/// Fields are found by running `T`'s `Deserialize` impl against the buffer, skipping every slot
+/// before the requested one without reading it. Once the field has been read the rest of the
+/// buffer is left unread and `T` itself is never built, which usually ends `T`'s impl with an
+/// error that is discarded.
+pub struct FlatView<T> {
+    buffer: FlatBuffer<Serde<T>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PathSegment {
+    /// A field of a struct.
+    Field(String),
+    /// An element of a sequence or tuple.
+    Index(usize),
+}
+
+impl<T> FlatView<T> {
+    pub fn new(buffer: FlatBuffer<Serde<T>>) -> Self { FlatView { buffer } }
+    pub fn into_inner(self) -> FlatBuffer<Serde<T>> { self.buffer }
+    /// Read the value at `path`, such as `a.b[3].c`, which must be of type `U`. Returns `None` if
+    /// the path passes through an absent `Option` or an index past the end of a sequence.
+    pub fn get<'a, U: Deserialize<'a>>(&'a self, path: &str) -> crate::de::Result<Option<U>>
+    where
+        T: Deserialize<'a>,
+    {
+        Ok(self.follow(path, ValueTarget(None))?.0)
+    }
+    /// Find the map at `path` for lookups by key, which requires that it was written with
+    /// [`Options::sort_maps`](crate::Options::sort_maps).
+    pub fn map<'a, K, V>(&'a self, path: &str) -> crate::de::Result<Option<FlatMapView<'a, K, V>>>
+    where
+        T: Deserialize<'a>,
+    {
+        Ok(self.follow(path, MapTarget(None))?.0)
+    }
+    fn follow<'a, O: Target<'a>>(&'a self, path: &str, target: O) -> crate::de::Result<O>
+    where
+        T: Deserialize<'a>,
+    {
+        follow_path::<T, O>(self.buffer.root(), path, target)
+    }
+}
+
+/// Run `T`'s `Deserialize` impl against `root`, passing the value at `path` to `target`.
+fn follow_path<'a, T: Deserialize<'a>, O: Target<'a>>(
+    root: FlatUnion<'a>,
+    path: &str,
+    target: O,
+) -> crate::de::Result<O> {
+    let path = parse_path(path)?;
+    let mut out = PathOut {
+        target,
+        done: false,
+    };
+    let result = T::deserialize(PathDeserializer {
+        imp: IdentityDeserializer::follow(root.buf, root.loc),
+        path: &path,
+        out: &mut out,
+    });
+    // Whatever `T` makes of the reading stopping early does not matter.
+    if !out.done {
+        result?;
+    }
+    Ok(out.target)
+}
+
+/// Find the fixed-size scalar at `path` in a `T`, returning its location and type, or `None` if
+/// it was never written.
+pub(crate) fn locate_scalar<'a, T: Deserialize<'a>>(
+    root: FlatUnion<'a>,
+    path: &str,
+) -> crate::de::Result<Option<(usize, ScalarKind)>> {
+    Ok(follow_path::<T, _>(root, path, ScalarTarget(None))?.0)
+}
+
+/// The entries of a map with sorted keys, which are found by binary search without reading the
+/// rest of the map.
+#[derive(Debug)]
+pub struct FlatMapView<'de, K, V> {
+    entries: VectorDeserializer<'de>,
+    phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'de, K, V> FlatMapView<'de, K, V> {
+    pub fn len(&self) -> usize { self.entries.len() }
+    pub fn is_empty(&self) -> bool { self.entries.len() == 0 }
+    /// Read the value for `key`, comparing it with the keys in the map as
+    /// [`Options::sort_maps`](crate::Options::sort_maps) does when sorting them.
+    pub fn get(&self, key: &K) -> crate::de::Result<Option<V>>
+    where
+        K: Serialize + Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        let key = Content::new(key)?;
+        let mut start = 0;
+        let mut end = self.entries.len();
+        while start < end {
+            let middle = start + (end - start) / 2;
+            let mut entry = self
+                .entries
+                .get::<ForwardsUOffset<TableDeserializer>>(middle)?;
+            let found = K::deserialize(Deserializer::new(&mut entry))?;
+            match Content::new(&found)?.cmp(&key) {
+                Ordering::Less => start = middle + 1,
+                Ordering::Greater => end = middle,
+                Ordering::Equal => return Ok(Some(V::deserialize(Deserializer::new(&mut entry))?)),
+            }
+        }
+        Ok(None)
+    }
+}
+
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>, Error> {
+    let bad = || Error::BadPath(format!("cannot parse `{}`", path));
+    let mut segments = vec![];
+    for (i, part) in path.split('.').enumerate() {
+        let (name, mut indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !name.is_empty() {
+            segments.push(PathSegment::Field(name.to_string()));
+        } else if i > 0 || indices.is_empty() {
+            return Err(bad());
+        }
+        while !indices.is_empty() {
+            let end = indices.find(']').ok_or_else(bad)?;
+            let index = indices[1..end].parse().map_err(|_| bad())?;
+            segments.push(PathSegment::Index(index));
+            indices = &indices[end + 1..];
+            if !indices.is_empty() && !indices.starts_with('[') {
+                return Err(bad());
+            }
+        }
+    }
+    Ok(segments)
+}
+
+impl Display for ScalarKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ScalarKind::Bool => "bool",
+            ScalarKind::I8 => "i8",
+            ScalarKind::I16 => "i16",
+            ScalarKind::I32 => "i32",
+            ScalarKind::I64 => "i64",
+            ScalarKind::I128 => "i128",
+            ScalarKind::U8 => "u8",
+            ScalarKind::U16 => "u16",
+            ScalarKind::U32 => "u32",
+            ScalarKind::U64 => "u64",
+            ScalarKind::U128 => "u128",
+            ScalarKind::F32 => "f32",
+            ScalarKind::F64 => "f64",
+            ScalarKind::Char => "char",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathSegment::Field(x) => write!(f, ".{}", x),
+            PathSegment::Index(x) => write!(f, "[{}]", x),
+        }
+    }
+}
+
+/// Receives the value at the end of a path, along with the seed that `T`'s `Deserialize` impl
+/// uses for it.
+trait Target<'de> {
+    fn visit<I: FlatDeserializer<'de>, S: DeserializeSeed<'de>>(
+        &mut self,
+        imp: I,
+        seed: S,
+    ) -> Result<(), Error>;
+}
+
+/// The target of a path, and whether following the path is done, after which nothing else is
+/// read.
+struct PathOut<O> {
+    target: O,
+    done: bool,
+}
+
+struct ValueTarget<U>(Option<U>);
+
+struct MapTarget<'de, K, V>(Option<FlatMapView<'de, K, V>>);
+
+/// The location and type of a fixed-size scalar.
+struct ScalarTarget(Option<(usize, ScalarKind)>);
+
+/// The type of a fixed-size scalar.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ScalarKind {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+}
+
+impl<'de, U: Deserialize<'de>> Target<'de> for ValueTarget<U> {
+    fn visit<I: FlatDeserializer<'de>, S: DeserializeSeed<'de>>(
+        &mut self,
+        imp: I,
+        seed: S,
+    ) -> Result<(), Error> {
+        self.0 = Some(U::deserialize(Deserializer::new(imp))?);
+        Ok(())
+    }
+}
+
+impl<'de, K, V> Target<'de> for MapTarget<'de, K, V> {
+    fn visit<I: FlatDeserializer<'de>, S: DeserializeSeed<'de>>(
+        &mut self,
+        imp: I,
+        seed: S,
+    ) -> Result<(), Error> {
+        self.0 = imp
+            .deserialize_variable::<VectorDeserializer>()?
+            .map(|entries| FlatMapView {
+                entries,
+                phantom: PhantomData,
+            });
+        Ok(())
+    }
+}
+
+impl<'de> Target<'de> for ScalarTarget {
+    fn visit<I: FlatDeserializer<'de>, S: DeserializeSeed<'de>>(
+        &mut self,
+        imp: I,
+        seed: S,
+    ) -> Result<(), Error> {
+        seed.deserialize(ScalarDeserializer { imp, out: self })?;
+        Ok(())
+    }
+}
+
+/// Occupies as much space as `T`, but follows to its location instead of its value.
+#[repr(transparent)]
+struct Located<T>(T);
+
+impl<'de, T> Follow<'de> for Located<T> {
+    type Inner = usize;
+    fn follow(buf: &'de [u8], loc: usize) -> Self::Inner { loc }
+}
+
+impl<'de, T> FollowChecked<'de> for Located<T> {
+    fn follow_checked(buf: &'de [u8], loc: usize) -> Result<Self::Inner, Error> {
+        check_range(buf, loc, size_of::<T>())?;
+        Ok(loc)
+    }
+}
+
+/// Records where the fixed-size scalar that the field's own `Deserialize` impl asks for is,
+/// looking through newtypes and into present `Option`s.
+struct ScalarDeserializer<'o, I> {
+    imp: I,
+    out: &'o mut ScalarTarget,
+}
+
+impl<'de, 'o, I: FlatDeserializer<'de>> ScalarDeserializer<'o, I> {
+    fn locate<T: 'de>(self, kind: ScalarKind) -> Result<(), Error> {
+        self.out.0 = self
+            .imp
+            .deserialize_fixed::<Located<T>>()?
+            .map(|loc| (loc, kind));
+        Ok(())
+    }
+}
+
+/// Follows a non-empty `path` into the value that `imp` points to, passing the value at the end
+/// of the path to `out`. Reading stops once the value is found or known to be absent.
+struct PathDeserializer<'p, I, O> {
+    imp: I,
+    path: &'p [PathSegment],
+    out: &'p mut PathOut<O>,
+}
+
+/// The elements of a table or vector, where only `target` is on the path.
+struct PathSeqAccess<'p, C, O> {
+    container: C,
+    position: usize,
+    target: usize,
+    path: &'p [PathSegment],
+    out: &'p mut PathOut<O>,
+}
+
+/// A table or vector whose elements are read in order.
+trait Container {
+    /// Called before reading or skipping each element.
+    fn next_element(&mut self) -> Result<(), Error>;
+}
+
+impl<'de> Container for TableDeserializer<'de> {
+    fn next_element(&mut self) -> Result<(), Error> { self.next_field() }
+}
+
+impl<'de> Container for VectorDeserializer<'de> {
+    fn next_element(&mut self) -> Result<(), Error> { Ok(()) }
+}
+
+fn mismatch(segment: &PathSegment) -> Error {
+    Error::BadPath(format!("cannot follow `{}`", segment))
+}
+
+impl<'p, I, O> PathDeserializer<'p, I, O> {
+    fn index(&self, len: usize) -> Result<usize, Error> {
+        match &self.path[0] {
+            PathSegment::Index(index) if *index < len => Ok(*index),
+            segment => Err(mismatch(segment)),
+        }
+    }
+}
+
+impl<'de, 'p, I: FlatDeserializer<'de>, O: Target<'de>> PathDeserializer<'p, I, O> {
+    fn table<V: Visitor<'de>>(
+        self,
+        target: usize,
+        pinned: Option<(&'static FieldIds, &'static [&'static str])>,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if let Some(mut table) = self.imp.deserialize_variable::<TableDeserializer>()? {
+            if let Some((ids, fields)) = pinned {
+                table.pin(ids, fields);
+            }
+            visitor.visit_seq(PathSeqAccess {
+                container: table,
+                position: 0,
+                target,
+                path: &self.path[1..],
+                out: self.out,
+            })
+        } else {
+            self.out.done = true;
+            visitor.visit_seq(NoneDeserializer)
+        }
+    }
+}
+
+impl<'de, 'p, C, O> SeqAccess<'de> for PathSeqAccess<'p, C, O>
+where
+    C: Container,
+    for<'c> &'c mut C: FlatDeserializer<'de>,
+    O: Target<'de>,
+{
+    type Error = Error;
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let position = self.position;
+        self.position += 1;
+        if position <= self.target {
+            self.container.next_element()?;
+        }
+        if position < self.target {
+            let skip = SkipDeserializer::new(&mut self.container);
+            Ok(Some(seed.deserialize(Deserializer::new(skip))?))
+        } else if position == self.target {
+            let imp = &mut self.container;
+            if self.path.is_empty() {
+                self.out.target.visit(imp, seed)?;
+                self.out.done = true;
+                Ok(None)
+            } else {
+                Ok(Some(seed.deserialize(PathDeserializer {
+                    imp,
+                    path: self.path,
+                    out: &mut *self.out,
+                })?))
+            }
+        } else {
+            self.out.done = true;
+            Ok(None)
+        }
+    }
+}
+
+impl<'de, 'p, I: FlatDeserializer<'de>, O: Target<'de>> serde::Deserializer<'de>
+    for PathDeserializer<'p, I, O>
+{
+    type Error = Error;
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(mismatch(&self.path[0]))
+    }
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Some(imp) = self.imp.follow_option()? {
+            visitor.visit_some(PathDeserializer {
+                imp,
+                path: self.path,
+                out: self.out,
+            })
+        } else {
+            visitor.visit_none()
+        }
+    }
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let target = self.index(usize::MAX)?;
+        match self.imp.deserialize_variable::<VectorDeserializer>()? {
+            Some(vector) if target < vector.len() => visitor.visit_seq(PathSeqAccess {
+                container: vector,
+                position: 0,
+                target,
+                path: &self.path[1..],
+                out: self.out,
+            }),
+            _ => visitor.visit_seq(NoneDeserializer),
+        }
+    }
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let target = self.index(len)?;
+        self.table(target, None, visitor)
+    }
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let target = match &self.path[0] {
+            PathSegment::Field(field) => fields
+                .iter()
+                .position(|x| x == field)
+                .ok_or_else(|| Error::BadPath(format!("no field `{}` in `{}`", field, name)))?,
+            segment => return Err(mismatch(segment)),
+        };
+        let pinned = FieldIds::lookup(name).map(|ids| (ids, fields));
+        self.table(target, pinned, visitor)
+    }
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, 'o, I: FlatDeserializer<'de>> serde::Deserializer<'de> for ScalarDeserializer<'o, I> {
+    type Error = Error;
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<bool>(ScalarKind::Bool)?;
+        visitor.visit_bool(false)
+    }
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<i8>(ScalarKind::I8)?;
+        visitor.visit_i8(0)
+    }
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<i16>(ScalarKind::I16)?;
+        visitor.visit_i16(0)
+    }
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<i32>(ScalarKind::I32)?;
+        visitor.visit_i32(0)
+    }
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<i64>(ScalarKind::I64)?;
+        visitor.visit_i64(0)
+    }
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<Flat128>(ScalarKind::I128)?;
+        visitor.visit_i128(0)
+    }
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<u8>(ScalarKind::U8)?;
+        visitor.visit_u8(0)
+    }
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<u16>(ScalarKind::U16)?;
+        visitor.visit_u16(0)
+    }
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<u32>(ScalarKind::U32)?;
+        visitor.visit_u32(0)
+    }
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<u64>(ScalarKind::U64)?;
+        visitor.visit_u64(0)
+    }
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<Flat128>(ScalarKind::U128)?;
+        visitor.visit_u128(0)
+    }
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<f32>(ScalarKind::F32)?;
+        visitor.visit_f32(0.0)
+    }
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<f64>(ScalarKind::F64)?;
+        visitor.visit_f64(0.0)
+    }
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.locate::<u32>(ScalarKind::Char)?;
+        visitor.visit_char('\0')
+    }
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Some(imp) = self.imp.follow_option()? {
+            visitor.visit_some(ScalarDeserializer { imp, out: self.out })
+        } else {
+            visitor.visit_none()
+        }
+    }
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
@@ -2,14 +2,8 @@
 #![allow(unused_imports)]
 
 use flatbuffers_serde::tag::TYPE_TAGS;
-use registry::registry;
-
-registry! {
-    require flatbuffers_serde;
-}
 
 #[test]
 fn test_type_tag() {
-    REGISTRY.build();
     println!("{:#?}", TYPE_TAGS);
 }
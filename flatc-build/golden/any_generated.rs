@@ -0,0 +1,366 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+
+
+use std::mem;
+use std::cmp::Ordering;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+// struct TypeTagHash, aligned to 1
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct TypeTagHash(pub [u8; 16]);
+impl Default for TypeTagHash { 
+  fn default() -> Self { 
+    Self([0; 16])
+  }
+}
+impl std::fmt::Debug for TypeTagHash {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.debug_struct("TypeTagHash")
+      .field("v", &self.v())
+      .finish()
+  }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for TypeTagHash {}
+impl flatbuffers::SafeSliceAccess for TypeTagHash {}
+impl<'a> flatbuffers::Follow<'a> for TypeTagHash {
+  type Inner = &'a TypeTagHash;
+  #[inline]
+  fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    <&'a TypeTagHash>::follow(buf, loc)
+  }
+}
+impl<'a> flatbuffers::Follow<'a> for &'a TypeTagHash {
+  type Inner = &'a TypeTagHash;
+  #[inline]
+  fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+    flatbuffers::follow_cast_ref::<TypeTagHash>(buf, loc)
+  }
+}
+impl<'b> flatbuffers::Push for TypeTagHash {
+    type Output = TypeTagHash;
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(self as *const TypeTagHash as *const u8, Self::size())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+impl<'b> flatbuffers::Push for &'b TypeTagHash {
+    type Output = TypeTagHash;
+
+    #[inline]
+    fn push(&self, dst: &mut [u8], _rest: &[u8]) {
+        let src = unsafe {
+            ::std::slice::from_raw_parts(*self as *const TypeTagHash as *const u8, Self::size())
+        };
+        dst.copy_from_slice(src);
+    }
+}
+
+impl<'a> flatbuffers::Verifiable for TypeTagHash {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.in_buffer::<Self>(pos)
+  }
+}
+impl<'a> TypeTagHash {
+  #[allow(clippy::too_many_arguments)]
+  pub fn new(
+    v: &[u8; 16],
+  ) -> Self {
+    let mut s = Self([0; 16]);
+    s.set_v(&v);
+    s
+  }
+
+  pub fn v(&'a self) -> flatbuffers::Array<'a, u8, 16> {
+    flatbuffers::Array::follow(&self.0, 0)
+  }
+
+  pub fn set_v(&mut self, items: &[u8; 16]) {
+    flatbuffers::emplace_scalar_array(&mut self.0, 0, items);
+  }
+
+}
+
+pub enum AnyFlatOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct AnyFlat<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for AnyFlat<'a> {
+    type Inner = AnyFlat<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> AnyFlat<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        AnyFlat { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args AnyFlatArgs<'args>) -> flatbuffers::WIPOffset<AnyFlat<'bldr>> {
+      let mut builder = AnyFlatBuilder::new(_fbb);
+      if let Some(x) = args.data { builder.add_data(x); }
+      if let Some(x) = args.type_tag_hash { builder.add_type_tag_hash(x); }
+      builder.finish()
+    }
+
+    pub const VT_TYPE_TAG_HASH: flatbuffers::VOffsetT = 4;
+    pub const VT_DATA: flatbuffers::VOffsetT = 6;
+
+  #[inline]
+  pub fn type_tag_hash(&self) -> Option<&'a TypeTagHash> {
+    self._tab.get::<TypeTagHash>(AnyFlat::VT_TYPE_TAG_HASH, None)
+  }
+  #[inline]
+  pub fn data(&self) -> Option<AnyFlatRaw<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<AnyFlatRaw>>(AnyFlat::VT_DATA, None)
+  }
+}
+
+impl flatbuffers::Verifiable for AnyFlat<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<TypeTagHash>(&"type_tag_hash", Self::VT_TYPE_TAG_HASH, false)?
+     .visit_field::<flatbuffers::ForwardsUOffset<AnyFlatRaw>>(&"data", Self::VT_DATA, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct AnyFlatArgs<'a> {
+    pub type_tag_hash: Option<&'a TypeTagHash>,
+    pub data: Option<flatbuffers::WIPOffset<AnyFlatRaw<'a>>>,
+}
+impl<'a> Default for AnyFlatArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        AnyFlatArgs {
+            type_tag_hash: None,
+            data: None,
+        }
+    }
+}
+pub struct AnyFlatBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> AnyFlatBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_type_tag_hash(&mut self, type_tag_hash: &TypeTagHash) {
+    self.fbb_.push_slot_always::<&TypeTagHash>(AnyFlat::VT_TYPE_TAG_HASH, type_tag_hash);
+  }
+  #[inline]
+  pub fn add_data(&mut self, data: flatbuffers::WIPOffset<AnyFlatRaw<'b >>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<AnyFlatRaw>>(AnyFlat::VT_DATA, data);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> AnyFlatBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    AnyFlatBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<AnyFlat<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for AnyFlat<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("AnyFlat");
+      ds.field("type_tag_hash", &self.type_tag_hash());
+      ds.field("data", &self.data());
+      ds.finish()
+  }
+}
+pub enum AnyLazyOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct AnyLazy<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for AnyLazy<'a> {
+    type Inner = AnyLazy<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> AnyLazy<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        AnyLazy { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args AnyLazyArgs<'args>) -> flatbuffers::WIPOffset<AnyLazy<'bldr>> {
+      let mut builder = AnyLazyBuilder::new(_fbb);
+      if let Some(x) = args.bytes { builder.add_bytes(x); }
+      builder.finish()
+    }
+
+    pub const VT_BYTES: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn bytes(&self) -> Option<&'a [u8]> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, u8>>>(AnyLazy::VT_BYTES, None).map(|v| v.safe_slice())
+  }
+}
+
+impl flatbuffers::Verifiable for AnyLazy<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, u8>>>(&"bytes", Self::VT_BYTES, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct AnyLazyArgs<'a> {
+    pub bytes: Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, u8>>>,
+}
+impl<'a> Default for AnyLazyArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        AnyLazyArgs {
+            bytes: None,
+        }
+    }
+}
+pub struct AnyLazyBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> AnyLazyBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_bytes(&mut self, bytes: flatbuffers::WIPOffset<flatbuffers::Vector<'b , u8>>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<_>>(AnyLazy::VT_BYTES, bytes);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> AnyLazyBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    AnyLazyBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<AnyLazy<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for AnyLazy<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("AnyLazy");
+      ds.field("bytes", &self.bytes());
+      ds.finish()
+  }
+}
+pub enum AnyFlatRawOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct AnyFlatRaw<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for AnyFlatRaw<'a> {
+    type Inner = AnyFlatRaw<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> AnyFlatRaw<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        AnyFlatRaw { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        _args: &'args AnyFlatRawArgs) -> flatbuffers::WIPOffset<AnyFlatRaw<'bldr>> {
+      let mut builder = AnyFlatRawBuilder::new(_fbb);
+      builder.finish()
+    }
+
+}
+
+impl flatbuffers::Verifiable for AnyFlatRaw<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct AnyFlatRawArgs {
+}
+impl<'a> Default for AnyFlatRawArgs {
+    #[inline]
+    fn default() -> Self {
+        AnyFlatRawArgs {
+        }
+    }
+}
+pub struct AnyFlatRawBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> AnyFlatRawBuilder<'a, 'b> {
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> AnyFlatRawBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    AnyFlatRawBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<AnyFlatRaw<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for AnyFlatRaw<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("AnyFlatRaw");
+      ds.finish()
+  }
+}
@@ -0,0 +1,102 @@
+// automatically generated by the FlatBuffers compiler, do not modify
+
+
+
+use crate::any_generated::*;
+use std::mem;
+use std::cmp::Ordering;
+
+extern crate flatbuffers;
+use self::flatbuffers::{EndianScalar, Follow};
+
+pub enum TestOffset {}
+#[derive(Copy, Clone, PartialEq)]
+
+pub struct Test<'a> {
+  pub _tab: flatbuffers::Table<'a>,
+}
+
+impl<'a> flatbuffers::Follow<'a> for Test<'a> {
+    type Inner = Test<'a>;
+    #[inline]
+    fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+        Self { _tab: flatbuffers::Table { buf, loc } }
+    }
+}
+
+impl<'a> Test<'a> {
+    #[inline]
+    pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
+        Test { _tab: table }
+    }
+    #[allow(unused_mut)]
+    pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(
+        _fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,
+        args: &'args TestArgs<'args>) -> flatbuffers::WIPOffset<Test<'bldr>> {
+      let mut builder = TestBuilder::new(_fbb);
+      if let Some(x) = args.any { builder.add_any(x); }
+      builder.finish()
+    }
+
+    pub const VT_ANY: flatbuffers::VOffsetT = 4;
+
+  #[inline]
+  pub fn any(&self) -> Option<AnyFlat<'a>> {
+    self._tab.get::<flatbuffers::ForwardsUOffset<AnyFlat>>(Test::VT_ANY, None)
+  }
+}
+
+impl flatbuffers::Verifiable for Test<'_> {
+  #[inline]
+  fn run_verifier(
+    v: &mut flatbuffers::Verifier, pos: usize
+  ) -> Result<(), flatbuffers::InvalidFlatbuffer> {
+    use self::flatbuffers::Verifiable;
+    v.visit_table(pos)?
+     .visit_field::<flatbuffers::ForwardsUOffset<AnyFlat>>(&"any", Self::VT_ANY, false)?
+     .finish();
+    Ok(())
+  }
+}
+pub struct TestArgs<'a> {
+    pub any: Option<flatbuffers::WIPOffset<AnyFlat<'a>>>,
+}
+impl<'a> Default for TestArgs<'a> {
+    #[inline]
+    fn default() -> Self {
+        TestArgs {
+            any: None,
+        }
+    }
+}
+pub struct TestBuilder<'a: 'b, 'b> {
+  fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,
+  start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,
+}
+impl<'a: 'b, 'b> TestBuilder<'a, 'b> {
+  #[inline]
+  pub fn add_any(&mut self, any: flatbuffers::WIPOffset<AnyFlat<'b >>) {
+    self.fbb_.push_slot_always::<flatbuffers::WIPOffset<AnyFlat>>(Test::VT_ANY, any);
+  }
+  #[inline]
+  pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> TestBuilder<'a, 'b> {
+    let start = _fbb.start_table();
+    TestBuilder {
+      fbb_: _fbb,
+      start_: start,
+    }
+  }
+  #[inline]
+  pub fn finish(self) -> flatbuffers::WIPOffset<Test<'a>> {
+    let o = self.fbb_.end_table(self.start_);
+    flatbuffers::WIPOffset::new(o.value())
+  }
+}
+
+impl std::fmt::Debug for Test<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut ds = f.debug_struct("Test");
+      ds.field("any", &self.any());
+      ds.finish()
+  }
+}
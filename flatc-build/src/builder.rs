@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fmt, fs, io};
+
+use crate::{parser, rust};
+
+#[derive(Debug)]
+pub enum Error {
+    /// An input directory could not be read or an output file could not be written.
+    Io { path: PathBuf, error: io::Error },
+    /// No output directory was given and `OUT_DIR` is not set.
+    MissingOutDir,
+    /// The `flatc` executable could not be run.
+    Spawn { flatc: PathBuf, error: io::Error },
+    /// Compiling `file` failed. `output` holds everything the compiler printed.
+    Compile { file: PathBuf, output: String },
+    /// Two input files would be written to the same output file.
+    DuplicateOutput {
+        output: PathBuf,
+        first: PathBuf,
+        second: PathBuf,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            Error::MissingOutDir => write!(f, "missing environment variable 'OUT_DIR'"),
+            Error::Spawn { flatc, error } => {
+                write!(f, "failed to run '{}': {}", flatc.display(), error)
+            }
+            Error::Compile { file, output } => {
+                write!(f, "failed to compile '{}':\n{}", file.display(), output)
+            }
+            Error::DuplicateOutput {
+                output,
+                first,
+                second,
+            } => write!(
+                f,
+                "'{}' and '{}' both generate '{}'",
+                first.display(),
+                second.display(),
+                output.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Compiles the `.fbs` files in a set of directories to Rust, either with `flatc` or with the
+/// schema compiler in this crate.
+/// ```no_run
+/// # use flatc_build::FlatcBuilder;
+/// FlatcBuilder::new()
+///     .input_dir("schema/")
+///     .include_path("../common/schema/")
+///     .recursive(true)
+///     .flag("--gen-name-strings")
+///     .output_layout(|file| file.with_extension("rs"))
+///     .compile()
+///     .unwrap();
+/// ```
+pub struct FlatcBuilder {
+    input_dirs: Vec<PathBuf>,
+    include_paths: Vec<PathBuf>,
+    recursive: bool,
+    flags: Vec<OsString>,
+    flatc: Option<PathBuf>,
+    out_dir: Option<PathBuf>,
+    output_layout: Box<dyn Fn(&Path) -> PathBuf>,
+}
+
+impl Default for FlatcBuilder {
+    fn default() -> Self { FlatcBuilder::new() }
+}
+
+impl FlatcBuilder {
+    pub fn new() -> Self {
+        FlatcBuilder {
+            input_dirs: vec![],
+            include_paths: vec![],
+            recursive: false,
+            flags: vec![],
+            flatc: Some(PathBuf::from("flatc")),
+            out_dir: None,
+            output_layout: Box::new(default_layout),
+        }
+    }
+    pub fn input_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.input_dirs.push(dir.into());
+        self
+    }
+    /// A directory to search for `include`d schemas, passed to `flatc` as `-I`.
+    pub fn include_path(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.include_paths.push(dir.into());
+        self
+    }
+    /// Whether to compile the schemas in subdirectories of the input directories too.
+    pub fn recursive(&mut self, recursive: bool) -> &mut Self {
+        self.recursive = recursive;
+        self
+    }
+    /// An extra argument for `flatc`, such as `--gen-object-api`.
+    pub fn flag(&mut self, flag: impl Into<OsString>) -> &mut Self {
+        self.flags.push(flag.into());
+        self
+    }
+    /// The `flatc` executable to run. Defaults to `flatc` on `PATH`.
+    pub fn flatc(&mut self, flatc: impl Into<PathBuf>) -> &mut Self {
+        self.flatc = Some(flatc.into());
+        self
+    }
+    /// Generate code with the schema compiler in this crate instead of running `flatc`. Only
+    /// `--gen-name-strings` is supported as a [flag](Self::flag) then.
+    pub fn builtin_compiler(&mut self) -> &mut Self {
+        self.flatc = None;
+        self
+    }
+    /// Where to write the generated code. Defaults to `OUT_DIR`.
+    pub fn out_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.out_dir = Some(dir.into());
+        self
+    }
+    /// Maps the path of each schema, relative to its input directory, to the path of its
+    /// generated code, relative to the output directory. Defaults to `<stem>_generated.rs`.
+    pub fn output_layout(&mut self, layout: impl Fn(&Path) -> PathBuf + 'static) -> &mut Self {
+        self.output_layout = Box::new(layout);
+        self
+    }
+
+    /// Compiles every schema and returns the paths of the generated files.
+    pub fn compile(&self) -> Result<Vec<PathBuf>, Error> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(Error::MissingOutDir)?,
+        };
+        let mut outputs: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let mut jobs = vec![];
+        for input_dir in &self.input_dirs {
+            println!("cargo:rerun-if-changed={}", input_dir.display());
+            let mut files = vec![];
+            self.walk(input_dir, &mut files)?;
+            files.sort();
+            for file in files {
+                let relative = file.strip_prefix(input_dir).unwrap_or(&file);
+                let output = out_dir.join((self.output_layout)(relative));
+                if let Some(first) = outputs.insert(output.clone(), file.clone()) {
+                    return Err(Error::DuplicateOutput {
+                        output,
+                        first,
+                        second: file,
+                    });
+                }
+                jobs.push((file, output));
+            }
+        }
+        for (file, output) in &jobs {
+            println!("cargo:rerun-if-changed={}", file.display());
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent).map_err(|error| Error::Io {
+                    path: parent.to_path_buf(),
+                    error,
+                })?;
+            }
+            match &self.flatc {
+                Some(flatc) => self.run_flatc(flatc, file, output)?,
+                None => self.generate(file, output)?,
+            }
+        }
+        Ok(jobs.into_iter().map(|(_, output)| output).collect())
+    }
+
+    fn walk(&self, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+        let io_error = |error| Error::Io {
+            path: dir.to_path_buf(),
+            error,
+        };
+        for entry in fs::read_dir(dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.is_dir() {
+                if self.recursive {
+                    self.walk(&path, files)?;
+                }
+            } else if path.extension() == Some(OsStr::new("fbs")) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    fn run_flatc(&self, flatc: &Path, file: &Path, output: &Path) -> Result<(), Error> {
+        let out_dir = output.parent().unwrap_or(Path::new(""));
+        let mut command = Command::new(flatc);
+        command.arg("--rust").arg("-o").arg(out_dir);
+        for include_path in &self.include_paths {
+            command.arg("-I").arg(include_path);
+        }
+        command.args(&self.flags).arg(file);
+        let result = command.output().map_err(|error| Error::Spawn {
+            flatc: flatc.to_path_buf(),
+            error,
+        })?;
+        if !result.status.success() {
+            let mut output = String::from_utf8_lossy(&result.stdout).into_owned();
+            output.push_str(&String::from_utf8_lossy(&result.stderr));
+            return Err(Error::Compile {
+                file: file.to_path_buf(),
+                output,
+            });
+        }
+        let generated = out_dir.join(default_layout(file).file_name().unwrap());
+        if generated != output {
+            fs::rename(&generated, output).map_err(|error| Error::Io {
+                path: generated,
+                error,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn generate(&self, file: &Path, output: &Path) -> Result<(), Error> {
+        let compile_error = |message: String| Error::Compile {
+            file: file.to_path_buf(),
+            output: message,
+        };
+        let mut options = rust::Options::default();
+        for flag in &self.flags {
+            match flag.to_str() {
+                Some("--gen-name-strings") => options.name_strings = true,
+                _ => {
+                    return Err(compile_error(format!(
+                        "unsupported flag without flatc: {:?}",
+                        flag
+                    )))
+                }
+            }
+        }
+        let schema = parser::parse_file_with_includes(file, &self.include_paths)
+            .map_err(|e| compile_error(e.to_string()))?;
+        for include in schema.all_includes() {
+            println!("cargo:rerun-if-changed={}", include.path.display());
+        }
+        let code =
+            rust::generate_with(&schema, &options).map_err(|e| compile_error(e.to_string()))?;
+        fs::write(output, code).map_err(|error| Error::Io {
+            path: output.to_path_buf(),
+            error,
+        })
+    }
+}
+
+fn default_layout(file: &Path) -> PathBuf {
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    PathBuf::from(format!("{}_generated.rs", stem))
+}
@@ -0,0 +1,740 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(PathBuf, io::Error),
+    Syntax {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            ParseError::Syntax {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Bool,
+    Byte,
+    UByte,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Long,
+    ULong,
+    Float,
+    Double,
+    String,
+    Vector(Box<Type>),
+    Array(Box<Type>, usize),
+    Named(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i128),
+    Float(String),
+    Bool(bool),
+    Ident(String),
+    Null,
+}
+
+#[derive(Clone, Debug)]
+pub struct Attribute {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub doc: Vec<String>,
+    pub name: String,
+    pub ty: Type,
+    pub default: Option<Value>,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EnumVal {
+    pub doc: Vec<String>,
+    pub name: String,
+    /// The referenced type of a union variant.
+    pub ty: Option<String>,
+    pub value: i128,
+}
+
+#[derive(Clone, Debug)]
+pub enum DeclKind {
+    Table(Vec<Field>),
+    Struct(Vec<Field>),
+    Enum(Type, Vec<EnumVal>),
+    Union(Vec<EnumVal>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Decl {
+    pub doc: Vec<String>,
+    pub namespace: Vec<String>,
+    pub name: String,
+    pub attributes: Vec<Attribute>,
+    pub kind: DeclKind,
+}
+
+/// A parsed `.fbs` file. Declarations of included files are kept separately so that code is
+/// only generated for the file itself.
+#[derive(Clone, Debug, Default)]
+pub struct Schema {
+    pub path: PathBuf,
+    pub decls: Vec<Decl>,
+    pub includes: Vec<Schema>,
+    pub root_type: Option<String>,
+    pub file_identifier: Option<String>,
+    pub file_extension: Option<String>,
+    pub attributes: Vec<String>,
+}
+
+impl Attribute {
+    pub fn find<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+        attributes.iter().find(|a| a.name == name)
+    }
+}
+
+impl Field {
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        Attribute::find(&self.attributes, name)
+    }
+}
+
+impl Schema {
+    /// Every included schema, transitively, each listed once.
+    pub fn all_includes(&self) -> Vec<&Schema> {
+        let mut seen = HashSet::new();
+        let mut result = vec![];
+        let mut stack: Vec<&Schema> = self.includes.iter().rev().collect();
+        while let Some(schema) = stack.pop() {
+            if seen.insert(&schema.path) {
+                result.push(schema);
+                stack.extend(schema.includes.iter().rev());
+            }
+        }
+        result
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Integer(i128),
+    Float(String),
+    Doc(String),
+    Punct(char),
+    Eof,
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    source: &'a str,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Lexer {
+            chars: source.char_indices().peekable(),
+            source,
+            line: 1,
+        }
+    }
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+    fn peek(&mut self) -> Option<char> { self.chars.peek().map(|(_, c)| *c) }
+    fn pos(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map(|(i, _)| *i)
+            .unwrap_or(self.source.len())
+    }
+    fn tokens(mut self) -> Result<Vec<(Token, usize)>, (usize, String)> {
+        let mut tokens = vec![];
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            }
+            let line = self.line;
+            let start = self.pos();
+            let c = match self.bump() {
+                None => {
+                    tokens.push((Token::Eof, line));
+                    return Ok(tokens);
+                }
+                Some(c) => c,
+            };
+            let token = match c {
+                '/' if self.peek() == Some('/') => {
+                    self.bump();
+                    let doc = self.peek() == Some('/');
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.bump();
+                    }
+                    if !doc {
+                        continue;
+                    }
+                    Token::Doc(self.source[start + 3..self.pos()].trim_end().to_string())
+                }
+                '/' if self.peek() == Some('*') => {
+                    self.bump();
+                    let mut last = ' ';
+                    loop {
+                        match self.bump() {
+                            None => return Err((line, "unterminated comment".to_string())),
+                            Some('/') if last == '*' => break,
+                            Some(c) => last = c,
+                        }
+                    }
+                    continue;
+                }
+                '"' => {
+                    let mut value = String::new();
+                    loop {
+                        match self.bump() {
+                            None | Some('\n') => {
+                                return Err((line, "unterminated string".to_string()))
+                            }
+                            Some('"') => break,
+                            Some('\\') => match self.bump() {
+                                Some('n') => value.push('\n'),
+                                Some('t') => value.push('\t'),
+                                Some(c) => value.push(c),
+                                None => return Err((line, "unterminated string".to_string())),
+                            },
+                            Some(c) => value.push(c),
+                        }
+                    }
+                    Token::Str(value)
+                }
+                c if c.is_ascii_alphabetic() || c == '_' => {
+                    while self
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                    {
+                        self.bump();
+                    }
+                    Token::Ident(self.source[start..self.pos()].to_string())
+                }
+                c if c.is_ascii_digit()
+                    || ((c == '-' || c == '+')
+                        && self
+                            .peek()
+                            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.'))
+                    || (c == '.' && self.peek().is_some_and(|c| c.is_ascii_digit())) =>
+                {
+                    while self
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                        || (self.peek().is_some_and(|c| c == '-' || c == '+')
+                            && self.source[..self.pos()].ends_with(['e', 'E'])
+                            && !self.source[start..].starts_with("0x"))
+                    {
+                        self.bump();
+                    }
+                    let text = &self.source[start..self.pos()];
+                    match parse_number(text) {
+                        Some(token) => token,
+                        None => return Err((line, format!("bad number `{}`", text))),
+                    }
+                }
+                c if "{}[]():;,=.".contains(c) => Token::Punct(c),
+                c => return Err((line, format!("unexpected character `{}`", c))),
+            };
+            tokens.push((token, line));
+        }
+    }
+}
+
+fn parse_number(text: &str) -> Option<Token> {
+    let (negative, digits) = match text.as_bytes().first()? {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let sign = if negative { -1 } else { 1 };
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        return Some(Token::Integer(sign * i128::from_str_radix(hex, 16).ok()?));
+    }
+    if let Ok(value) = digits.parse::<i128>() {
+        return Some(Token::Integer(sign * value));
+    }
+    match digits {
+        "inf" | "infinity" | "nan" => return Some(Token::Float(text.to_string())),
+        _ => {}
+    }
+    digits.parse::<f64>().ok()?;
+    Some(Token::Float(text.to_string()))
+}
+
+struct Parser<'a> {
+    path: &'a Path,
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    namespace: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError::Syntax {
+            path: self.path.to_path_buf(),
+            line: self.tokens[self.index].1,
+            message: message.into(),
+        })
+    }
+    fn peek(&self) -> &Token { &self.tokens[self.index].0 }
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.index].0.clone();
+        if token != Token::Eof {
+            self.index += 1;
+        }
+        token
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if *self.peek() == Token::Punct(c) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`, found {:?}", c, self.peek()))
+        }
+    }
+    fn ident(&mut self) -> Result<String, ParseError> {
+        match self.next() {
+            Token::Ident(ident) => Ok(ident),
+            token => {
+                self.index -= 1;
+                self.error(format!("expected identifier, found {:?}", token))
+            }
+        }
+    }
+    fn string(&mut self) -> Result<String, ParseError> {
+        match self.next() {
+            Token::Str(s) => Ok(s),
+            token => {
+                self.index -= 1;
+                self.error(format!("expected string, found {:?}", token))
+            }
+        }
+    }
+    fn qualified_ident(&mut self) -> Result<String, ParseError> {
+        let mut result = self.ident()?;
+        while self.eat('.') {
+            result.push('.');
+            result.push_str(&self.ident()?);
+        }
+        Ok(result)
+    }
+    fn doc(&mut self) -> Vec<String> {
+        let mut doc = vec![];
+        while let Token::Doc(line) = self.peek() {
+            doc.push(line.clone());
+            self.index += 1;
+        }
+        doc
+    }
+    fn integer(&mut self) -> Result<i128, ParseError> {
+        match self.next() {
+            Token::Integer(value) => Ok(value),
+            token => {
+                self.index -= 1;
+                self.error(format!("expected integer, found {:?}", token))
+            }
+        }
+    }
+    fn metadata(&mut self) -> Result<Vec<Attribute>, ParseError> {
+        let mut attributes = vec![];
+        if !self.eat('(') {
+            return Ok(attributes);
+        }
+        loop {
+            let name = self.ident()?;
+            let value = if self.eat(':') {
+                Some(match self.next() {
+                    Token::Ident(x) | Token::Str(x) | Token::Float(x) => x,
+                    Token::Integer(x) => x.to_string(),
+                    token => {
+                        self.index -= 1;
+                        return self.error(format!("expected attribute value, found {:?}", token));
+                    }
+                })
+            } else {
+                None
+            };
+            attributes.push(Attribute { name, value });
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect(')')?;
+        Ok(attributes)
+    }
+    fn ty(&mut self) -> Result<Type, ParseError> {
+        if self.eat('[') {
+            let element = self.ty()?;
+            let result = if self.eat(':') {
+                let len = self.integer()?;
+                if len <= 0 {
+                    return self.error("array length must be positive");
+                }
+                Type::Array(Box::new(element), len as usize)
+            } else {
+                Type::Vector(Box::new(element))
+            };
+            self.expect(']')?;
+            return Ok(result);
+        }
+        let name = self.qualified_ident()?;
+        Ok(match name.as_str() {
+            "bool" => Type::Bool,
+            "byte" | "int8" => Type::Byte,
+            "ubyte" | "uint8" => Type::UByte,
+            "short" | "int16" => Type::Short,
+            "ushort" | "uint16" => Type::UShort,
+            "int" | "int32" => Type::Int,
+            "uint" | "uint32" => Type::UInt,
+            "long" | "int64" => Type::Long,
+            "ulong" | "uint64" => Type::ULong,
+            "float" | "float32" => Type::Float,
+            "double" | "float64" => Type::Double,
+            "string" => Type::String,
+            _ => Type::Named(name),
+        })
+    }
+    fn value(&mut self) -> Result<Value, ParseError> {
+        Ok(match self.next() {
+            Token::Integer(x) => Value::Integer(x),
+            Token::Float(x) => Value::Float(x),
+            Token::Ident(x) if x == "true" => Value::Bool(true),
+            Token::Ident(x) if x == "false" => Value::Bool(false),
+            Token::Ident(x) if x == "null" => Value::Null,
+            Token::Ident(x) if x == "inf" || x == "infinity" || x == "nan" => Value::Float(x),
+            Token::Ident(x) => Value::Ident(x),
+            token => {
+                self.index -= 1;
+                return self.error(format!("expected default value, found {:?}", token));
+            }
+        })
+    }
+    fn fields(&mut self) -> Result<Vec<Field>, ParseError> {
+        self.expect('{')?;
+        let mut fields = vec![];
+        loop {
+            let doc = self.doc();
+            if self.eat('}') {
+                return Ok(fields);
+            }
+            let name = self.ident()?;
+            self.expect(':')?;
+            let ty = self.ty()?;
+            let default = if self.eat('=') {
+                Some(self.value()?)
+            } else {
+                None
+            };
+            let attributes = self.metadata()?;
+            self.expect(';')?;
+            fields.push(Field {
+                doc,
+                name,
+                ty,
+                default,
+                attributes,
+            });
+        }
+    }
+    fn enum_vals(&mut self, union: bool, bit_flags: bool) -> Result<Vec<EnumVal>, ParseError> {
+        self.expect('{')?;
+        let mut values = vec![];
+        let mut next = if union { 1 } else { 0 };
+        loop {
+            let doc = self.doc();
+            if self.eat('}') {
+                break;
+            }
+            let mut name = self.qualified_ident()?;
+            let mut ty = None;
+            if union {
+                if self.eat(':') {
+                    ty = Some(self.qualified_ident()?);
+                } else {
+                    ty = Some(name.clone());
+                    name = name.replace('.', "_");
+                }
+            }
+            let mut value = if self.eat('=') { self.integer()? } else { next };
+            next = value + 1;
+            if bit_flags {
+                if !(0..128).contains(&value) {
+                    return self.error(format!("bit flag `{}` out of range", name));
+                }
+                value = 1 << value;
+            }
+            let _ = self.metadata()?;
+            values.push(EnumVal {
+                doc,
+                name,
+                ty,
+                value,
+            });
+            if !self.eat(',') {
+                self.doc();
+                self.expect('}')?;
+                break;
+            }
+        }
+        Ok(values)
+    }
+    fn skip_block(&mut self) -> Result<(), ParseError> {
+        self.expect('{')?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Token::Punct('{') => depth += 1,
+                Token::Punct('}') => depth -= 1,
+                Token::Eof => return self.error("unexpected end of file"),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    fn schema(&mut self, schema: &mut Schema, context: &mut Context) -> Result<(), ParseError> {
+        loop {
+            let doc = self.doc();
+            let keyword = match self.next() {
+                Token::Eof => return Ok(()),
+                Token::Ident(keyword) => keyword,
+                token => {
+                    self.index -= 1;
+                    return self.error(format!("expected declaration, found {:?}", token));
+                }
+            };
+            match keyword.as_str() {
+                "include" => {
+                    let name = self.string()?;
+                    self.expect(';')?;
+                    if let Some(include) = self.include(&name, context)? {
+                        schema.includes.push(include);
+                    }
+                }
+                "native_include" => {
+                    self.string()?;
+                    self.expect(';')?;
+                }
+                "namespace" => {
+                    self.namespace = if *self.peek() == Token::Punct(';') {
+                        vec![]
+                    } else {
+                        self.qualified_ident()?
+                            .split('.')
+                            .map(str::to_string)
+                            .collect()
+                    };
+                    self.expect(';')?;
+                }
+                "attribute" => {
+                    let name = match self.next() {
+                        Token::Ident(x) | Token::Str(x) => x,
+                        token => {
+                            self.index -= 1;
+                            return self
+                                .error(format!("expected attribute name, found {:?}", token));
+                        }
+                    };
+                    schema.attributes.push(name);
+                    self.expect(';')?;
+                }
+                "root_type" => {
+                    schema.root_type = Some(self.qualified_ident()?);
+                    self.expect(';')?;
+                }
+                "file_identifier" => {
+                    let identifier = self.string()?;
+                    if identifier.len() != 4 {
+                        return self.error("file_identifier must be exactly 4 bytes");
+                    }
+                    schema.file_identifier = Some(identifier);
+                    self.expect(';')?;
+                }
+                "file_extension" => {
+                    schema.file_extension = Some(self.string()?);
+                    self.expect(';')?;
+                }
+                "rpc_service" => {
+                    self.ident()?;
+                    self.skip_block()?;
+                }
+                "table" | "struct" => {
+                    let name = self.ident()?;
+                    let attributes = self.metadata()?;
+                    let fields = self.fields()?;
+                    let kind = if keyword == "table" {
+                        DeclKind::Table(fields)
+                    } else {
+                        DeclKind::Struct(fields)
+                    };
+                    schema.decls.push(Decl {
+                        doc,
+                        namespace: self.namespace.clone(),
+                        name,
+                        attributes,
+                        kind,
+                    });
+                }
+                "enum" => {
+                    let name = self.ident()?;
+                    self.expect(':')?;
+                    let underlying = self.ty()?;
+                    let attributes = self.metadata()?;
+                    let bit_flags = Attribute::find(&attributes, "bit_flags").is_some();
+                    let values = self.enum_vals(false, bit_flags)?;
+                    schema.decls.push(Decl {
+                        doc,
+                        namespace: self.namespace.clone(),
+                        name,
+                        attributes,
+                        kind: DeclKind::Enum(underlying, values),
+                    });
+                }
+                "union" => {
+                    let name = self.ident()?;
+                    let attributes = self.metadata()?;
+                    let values = self.enum_vals(true, false)?;
+                    schema.decls.push(Decl {
+                        doc,
+                        namespace: self.namespace.clone(),
+                        name,
+                        attributes,
+                        kind: DeclKind::Union(values),
+                    });
+                }
+                _ => {
+                    self.index -= 1;
+                    return self.error(format!("unknown declaration `{}`", keyword));
+                }
+            }
+        }
+    }
+    fn include(&self, name: &str, context: &mut Context) -> Result<Option<Schema>, ParseError> {
+        let relative = self.path.parent().unwrap_or(Path::new("")).join(name);
+        let path = std::iter::once(relative)
+            .chain(context.include_paths.iter().map(|dir| dir.join(name)))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| PathBuf::from(name));
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| ParseError::Io(path.clone(), e))?;
+        if !context.seen.insert(canonical) {
+            return Ok(None);
+        }
+        parse_file_inner(&path, context).map(Some)
+    }
+}
+
+struct Context<'a> {
+    include_paths: &'a [PathBuf],
+    seen: HashSet<PathBuf>,
+}
+
+/// Parses a schema and, recursively, everything it includes. Includes are resolved relative to
+/// the including file, falling back to the working directory.
+pub fn parse_file(path: &Path) -> Result<Schema, ParseError> { parse_file_with_includes(path, &[]) }
+
+/// Like [parse_file], but also looks for includes in each of `include_paths`, in order, before
+/// falling back to the working directory.
+pub fn parse_file_with_includes(
+    path: &Path,
+    include_paths: &[PathBuf],
+) -> Result<Schema, ParseError> {
+    let mut context = Context {
+        include_paths,
+        seen: HashSet::new(),
+    };
+    if let Ok(canonical) = path.canonicalize() {
+        context.seen.insert(canonical);
+    }
+    parse_file_inner(path, &mut context)
+}
+
+fn parse_file_inner(path: &Path, context: &mut Context) -> Result<Schema, ParseError> {
+    let source = fs::read_to_string(path).map_err(|e| ParseError::Io(path.to_path_buf(), e))?;
+    let mut schema = Schema {
+        path: path.to_path_buf(),
+        ..Schema::default()
+    };
+    parse_source(path, &source, &mut schema, context)?;
+    Ok(schema)
+}
+
+fn parse_source(
+    path: &Path,
+    source: &str,
+    schema: &mut Schema,
+    context: &mut Context,
+) -> Result<(), ParseError> {
+    let tokens = Lexer::new(source)
+        .tokens()
+        .map_err(|(line, message)| ParseError::Syntax {
+            path: path.to_path_buf(),
+            line,
+            message,
+        })?;
+    Parser {
+        path,
+        tokens,
+        index: 0,
+        namespace: vec![],
+    }
+    .schema(schema, context)
+}
+
+/// Parses a schema held in memory. Includes are resolved as in [parse_file], relative to `path`.
+pub fn parse_str(path: &Path, source: &str) -> Result<Schema, ParseError> {
+    let mut schema = Schema {
+        path: path.to_path_buf(),
+        ..Schema::default()
+    };
+    let mut context = Context {
+        include_paths: &[],
+        seen: HashSet::new(),
+    };
+    parse_source(path, source, &mut schema, &mut context)?;
+    Ok(schema)
+}
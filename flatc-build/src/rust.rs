@@ -0,0 +1,1648 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use crate::parser::{Attribute, Decl, DeclKind, EnumVal, Field, Schema, Type, Value};
+
+#[derive(Debug)]
+pub struct GenerateError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+type Result<T> = std::result::Result<T, GenerateError>;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "try",
+    "type", "unsafe", "use", "where", "while", "yield",
+];
+
+pub fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let prev = i.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(i + 1);
+            if prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+                || (prev.is_some_and(|p| p.is_ascii_uppercase())
+                    && next.is_some_and(|n| n.is_ascii_lowercase()))
+            {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn escape(name: String) -> String {
+    if KEYWORDS.contains(&name.as_str()) {
+        name + "_"
+    } else {
+        name
+    }
+}
+
+fn scalar(ty: &Type) -> Option<(&'static str, usize)> {
+    Some(match ty {
+        Type::Bool => ("bool", 1),
+        Type::Byte => ("i8", 1),
+        Type::UByte => ("u8", 1),
+        Type::Short => ("i16", 2),
+        Type::UShort => ("u16", 2),
+        Type::Int => ("i32", 4),
+        Type::UInt => ("u32", 4),
+        Type::Long => ("i64", 8),
+        Type::ULong => ("u64", 8),
+        Type::Float => ("f32", 4),
+        Type::Double => ("f64", 8),
+        _ => return None,
+    })
+}
+
+/// How a field's type appears in the generated code, relative to the module it is used from.
+enum Kind<'s> {
+    Scalar(&'static str, usize),
+    Enum(String, usize, &'s Decl),
+    Struct(String, &'s Decl),
+    Table(String),
+    Union(String, &'s Decl),
+    /// The type field stored alongside a union field, with the path of the union's enum.
+    UnionType(String),
+    String,
+    Vector(String),
+    Array(Box<Kind<'s>>, usize),
+}
+
+struct TableField<'s> {
+    field: &'s Field,
+    name: String,
+    vt: String,
+    voffset: usize,
+    kind: Kind<'s>,
+}
+
+struct Module<'s> {
+    name: String,
+    decls: Vec<&'s Decl>,
+    children: Vec<Module<'s>>,
+}
+
+/// Switches for [generate_with], named after the `flatc` flags they correspond to.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// `--gen-name-strings`: give each table and struct a `get_fully_qualified_name`.
+    pub name_strings: bool,
+}
+
+struct Generator<'s> {
+    schema: &'s Schema,
+    options: &'s Options,
+    /// Every declaration in scope, with the module it is generated in if it was included.
+    decls: Vec<(&'s Decl, Option<String>)>,
+    out: String,
+}
+
+/// Generates the Rust module for a schema, in the same shape `flatc --rust` would. Types from
+/// included schemas are expected to be generated alongside it as `crate::<stem>_generated`.
+pub fn generate(schema: &Schema) -> Result<String> { generate_with(schema, &Options::default()) }
+
+pub fn generate_with(schema: &Schema, options: &Options) -> Result<String> {
+    let mut decls: Vec<(&Decl, Option<String>)> = schema.decls.iter().map(|d| (d, None)).collect();
+    for include in schema.all_includes() {
+        let stem = match include.path.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) => stem.to_string(),
+            None => {
+                return Err(GenerateError {
+                    path: include.path.clone(),
+                    message: "bad file name".to_string(),
+                })
+            }
+        };
+        decls.extend(
+            include
+                .decls
+                .iter()
+                .map(|d| (d, Some(format!("{}_generated", stem)))),
+        );
+    }
+    let mut generator = Generator {
+        schema,
+        options,
+        decls,
+        out: String::new(),
+    };
+    generator.file()?;
+    Ok(generator.out)
+}
+
+impl<'s> Generator<'s> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(GenerateError {
+            path: self.schema.path.clone(),
+            message: message.into(),
+        })
+    }
+
+    fn line(&mut self, depth: usize, line: impl AsRef<str>) {
+        let line = line.as_ref();
+        if !line.is_empty() {
+            for _ in 0..depth {
+                self.out.push_str("    ");
+            }
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    fn doc(&mut self, depth: usize, doc: &[String]) {
+        for line in doc {
+            self.line(depth, format!("///{}", line));
+        }
+    }
+
+    fn file(&mut self) -> Result<()> {
+        self.line(
+            0,
+            "// automatically generated by flatc-build, do not modify",
+        );
+        self.imports(0);
+        let mut root = Module {
+            name: String::new(),
+            decls: vec![],
+            children: vec![],
+        };
+        for decl in &self.schema.decls {
+            let mut module = &mut root;
+            for part in &decl.namespace {
+                let name = snake_case(part);
+                let index = match module.children.iter().position(|m| m.name == name) {
+                    Some(index) => index,
+                    None => {
+                        module.children.push(Module {
+                            name,
+                            decls: vec![],
+                            children: vec![],
+                        });
+                        module.children.len() - 1
+                    }
+                };
+                module = &mut module.children[index];
+            }
+            module.decls.push(decl);
+        }
+        self.module(&root, &mut vec![])
+    }
+
+    /// The imports `flatc` starts every module with, which the generated code relies on.
+    fn imports(&mut self, depth: usize) {
+        self.line(depth, "");
+        self.line(depth, "use std::mem;");
+        self.line(depth, "use std::cmp::Ordering;");
+        self.line(depth, "");
+        self.line(depth, "extern crate flatbuffers;");
+        self.line(depth, "use self::flatbuffers::{EndianScalar, Follow};");
+    }
+
+    fn module(&mut self, module: &Module<'s>, namespace: &mut Vec<String>) -> Result<()> {
+        let depth = namespace.len();
+        // Like `flatc`, emit the enums and unions first, then the structs, then the tables.
+        let rank = |decl: &Decl| match decl.kind {
+            DeclKind::Enum(..) | DeclKind::Union(_) => 0,
+            DeclKind::Struct(_) => 1,
+            DeclKind::Table(_) => 2,
+        };
+        let mut decls = module.decls.clone();
+        decls.sort_by_key(|decl| rank(decl));
+        for decl in decls {
+            self.line(depth, "");
+            match &decl.kind {
+                DeclKind::Table(fields) => self.table(depth, decl, fields)?,
+                DeclKind::Struct(fields) => self.structure(depth, decl, fields)?,
+                DeclKind::Enum(underlying, values) => {
+                    let (ty, size) = match scalar(underlying) {
+                        Some(x)
+                            if *underlying != Type::Bool
+                                && *underlying != Type::Float
+                                && *underlying != Type::Double =>
+                        {
+                            x
+                        }
+                        _ => return self.error(format!("enum `{}` must be integral", decl.name)),
+                    };
+                    self.enumeration(depth, decl, ty, size, values)?
+                }
+                DeclKind::Union(values) => {
+                    let mut values = values.clone();
+                    values.insert(
+                        0,
+                        EnumVal {
+                            doc: vec![],
+                            name: "NONE".to_string(),
+                            ty: None,
+                            value: 0,
+                        },
+                    );
+                    self.enumeration(depth, decl, "u8", 1, &values)?
+                }
+            }
+        }
+        if let Some(root) = &self.schema.root_type {
+            let decl = self.resolve_root(root)?;
+            if decl
+                .namespace
+                .iter()
+                .map(|n| snake_case(n))
+                .eq(namespace.iter().cloned())
+            {
+                self.root(depth, decl)?;
+            }
+        }
+        for child in &module.children {
+            self.line(depth, "");
+            self.line(
+                depth,
+                "#[allow(unused_imports, dead_code, non_snake_case, non_camel_case_types)]",
+            );
+            self.line(depth, format!("pub mod {} {{", child.name));
+            self.imports(depth + 1);
+            namespace.push(child.name.clone());
+            self.module(child, namespace)?;
+            namespace.pop();
+            self.line(depth, "}");
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str, namespace: &[String]) -> Option<&'s Decl> {
+        let parts: Vec<&str> = name.split('.').collect();
+        let (last, prefix) = parts.split_last()?;
+        for i in (0..=namespace.len()).rev() {
+            let candidate = namespace[..i]
+                .iter()
+                .map(String::as_str)
+                .chain(prefix.iter().cloned());
+            if let Some((decl, _)) = self.decls.iter().find(|(d, _)| {
+                d.name == *last && d.namespace.iter().map(String::as_str).eq(candidate.clone())
+            }) {
+                return Some(decl);
+            }
+        }
+        None
+    }
+
+    fn resolve_root(&self, name: &str) -> Result<&'s Decl> {
+        let decl = match self.lookup(name, &[]) {
+            Some(decl) => Some(decl),
+            None => {
+                let mut found = self.schema.decls.iter().filter(|d| d.name == name);
+                match (found.next(), found.next()) {
+                    (Some(decl), None) => Some(decl),
+                    _ => None,
+                }
+            }
+        };
+        match decl {
+            Some(decl) if matches!(decl.kind, DeclKind::Table(_)) => Ok(decl),
+            _ => self.error(format!("root_type `{}` is not a table", name)),
+        }
+    }
+
+    /// The path to `decl` from the module generated for `from`. Included declarations are named
+    /// through `crate::`, since the including module may be nested anywhere.
+    fn path(&self, decl: &Decl, from: &[String]) -> String {
+        let mut path = String::new();
+        match self.decls.iter().find(|(d, _)| std::ptr::eq(*d, decl)) {
+            Some((_, Some(module))) => {
+                path.push_str("crate::");
+                path.push_str(module);
+                path.push_str("::");
+            }
+            _ => {
+                for _ in from {
+                    path.push_str("super::");
+                }
+            }
+        }
+        for part in &decl.namespace {
+            path.push_str(&snake_case(part));
+            path.push_str("::");
+        }
+        path.push_str(&decl.name);
+        path
+    }
+
+    fn kind(&self, ty: &Type, from: &[String]) -> Result<Kind<'s>> {
+        if let Some((rust, size)) = scalar(ty) {
+            return Ok(Kind::Scalar(rust, size));
+        }
+        Ok(match ty {
+            Type::String => Kind::String,
+            Type::Vector(element) => {
+                let element = match self.kind(element, from)? {
+                    Kind::Scalar(rust, _) => rust.to_string(),
+                    Kind::Enum(path, ..) | Kind::Struct(path, _) => path,
+                    Kind::Table(path) => format!("flatbuffers::ForwardsUOffset<{}<'a>>", path),
+                    Kind::String => "flatbuffers::ForwardsUOffset<&'a str>".to_string(),
+                    _ => return self.error("unsupported vector element type"),
+                };
+                Kind::Vector(element)
+            }
+            Type::Array(element, len) => Kind::Array(Box::new(self.kind(element, from)?), *len),
+            Type::Named(name) => {
+                let decl = match self.lookup(name, from) {
+                    Some(decl) => decl,
+                    None => return self.error(format!("unknown type `{}`", name)),
+                };
+                let path = self.path(decl, from);
+                match &decl.kind {
+                    DeclKind::Table(_) => Kind::Table(path),
+                    DeclKind::Struct(_) => Kind::Struct(path, decl),
+                    DeclKind::Enum(underlying, _) => match scalar(underlying) {
+                        Some((_, size)) => Kind::Enum(path, size, decl),
+                        None => return self.error(format!("enum `{}` must be integral", name)),
+                    },
+                    DeclKind::Union(_) => Kind::Union(path, decl),
+                }
+            }
+            _ => unreachable!(),
+        })
+    }
+
+    fn default(&self, field: &Field, kind: &Kind<'s>) -> Result<String> {
+        let value = field.default.as_ref();
+        match kind {
+            Kind::Scalar("bool", _) => Ok(match value {
+                None | Some(Value::Bool(false)) | Some(Value::Integer(0)) => "false",
+                Some(Value::Bool(true)) | Some(Value::Integer(1)) => "true",
+                _ => return self.error(format!("bad default for `{}`", field.name)),
+            }
+            .to_string()),
+            Kind::Scalar(rust @ ("f32" | "f64"), _) => Ok(match value {
+                None => "0.0".to_string(),
+                Some(Value::Integer(x)) => format!("{}.0", x),
+                Some(Value::Float(x)) => match x.trim_start_matches('+') {
+                    "inf" | "infinity" => format!("{}::INFINITY", rust),
+                    "-inf" | "-infinity" => format!("{}::NEG_INFINITY", rust),
+                    "nan" | "-nan" => format!("{}::NAN", rust),
+                    x if x.contains(['.', 'e', 'E']) => x.to_string(),
+                    x => format!("{}.0", x),
+                },
+                _ => return self.error(format!("bad default for `{}`", field.name)),
+            }),
+            Kind::Scalar(_, _) => match value {
+                None => Ok("0".to_string()),
+                Some(Value::Integer(x)) => Ok(x.to_string()),
+                _ => self.error(format!("bad default for `{}`", field.name)),
+            },
+            Kind::Enum(path, _, decl) => match value {
+                None => Ok(format!("{}(0)", path)),
+                Some(Value::Integer(x)) => Ok(format!("{}({})", path, x)),
+                Some(Value::Ident(x)) => match &decl.kind {
+                    DeclKind::Enum(_, values) if values.iter().any(|v| v.name == *x) => {
+                        Ok(format!("{}::{}", path, x))
+                    }
+                    _ => self.error(format!("`{}` is not a value of `{}`", x, decl.name)),
+                },
+                _ => self.error(format!("bad default for `{}`", field.name)),
+            },
+            Kind::UnionType(path) => Ok(format!("{}::NONE", path)),
+            _ => match value {
+                None => Ok(String::new()),
+                _ => self.error(format!("`{}` cannot have a default value", field.name)),
+            },
+        }
+    }
+
+    fn table_fields(&self, decl: &'s Decl, fields: &'s [Field]) -> Result<Vec<TableField<'s>>> {
+        let ids = fields
+            .iter()
+            .filter(|f| f.attribute("id").is_some())
+            .count();
+        if ids != 0 && ids != fields.len() {
+            return self.error(format!(
+                "either all or no fields of `{}` need an id",
+                decl.name
+            ));
+        }
+        let mut result = vec![];
+        let mut next = 0;
+        let mut used = vec![];
+        for field in fields {
+            let kind = self.kind(&field.ty, &decl.namespace)?;
+            if let Kind::Array(..) = kind {
+                return self.error(format!(
+                    "arrays are only allowed in structs: `{}`",
+                    field.name
+                ));
+            }
+            let slot = match field.attribute("id") {
+                Some(Attribute {
+                    value: Some(id), ..
+                }) => match id.parse::<usize>() {
+                    Ok(id) => id,
+                    Err(_) => return self.error(format!("bad id for `{}`", field.name)),
+                },
+                Some(_) => return self.error(format!("missing id for `{}`", field.name)),
+                None => {
+                    next += if let Kind::Union(..) = kind { 2 } else { 1 };
+                    next - 1
+                }
+            };
+            let name = escape(snake_case(&field.name));
+            let vt = format!("VT_{}", snake_case(&field.name).to_ascii_uppercase());
+            if let Kind::Union(path, _) = &kind {
+                if slot == 0 {
+                    return self.error(format!("union `{}` needs an id of at least 1", field.name));
+                }
+                used.push(slot - 1);
+                result.push(TableField {
+                    field,
+                    name: format!("{}_type", snake_case(&field.name)),
+                    vt: format!("{}_TYPE", vt),
+                    voffset: 4 + 2 * (slot - 1),
+                    kind: Kind::UnionType(format!("{}Type", path)),
+                });
+            }
+            used.push(slot);
+            result.push(TableField {
+                field,
+                name,
+                vt,
+                voffset: 4 + 2 * slot,
+                kind,
+            });
+        }
+        used.sort_unstable();
+        if used.windows(2).any(|w| w[0] == w[1]) {
+            return self.error(format!("duplicate field id in `{}`", decl.name));
+        }
+        Ok(result)
+    }
+
+    fn table(&mut self, depth: usize, decl: &'s Decl, fields: &'s [Field]) -> Result<()> {
+        let d = depth;
+        let name = &decl.name;
+        let fields: Vec<TableField> = self
+            .table_fields(decl, fields)?
+            .into_iter()
+            .filter(|f| f.field.attribute("deprecated").is_none())
+            .collect();
+        let has_refs = fields.iter().any(|f| {
+            matches!(
+                f.kind,
+                Kind::Struct(..) | Kind::Table(_) | Kind::String | Kind::Vector(_)
+            )
+        });
+        let args_lifetime = if has_refs { "<'a>" } else { "" };
+        let args_create = if has_refs { "<'args>" } else { "" };
+
+        self.line(d, format!("pub enum {}Offset {{}}", name));
+        self.doc(d, &decl.doc);
+        self.line(d, "#[derive(Copy, Clone, PartialEq)]");
+        self.line(d, format!("pub struct {}<'a> {{", name));
+        self.line(d + 1, "pub _tab: flatbuffers::Table<'a>,");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(
+            d,
+            format!("impl<'a> flatbuffers::Follow<'a> for {}<'a> {{", name),
+        );
+        self.line(d + 1, format!("type Inner = {}<'a>;", name));
+        self.line(d + 1, "#[inline]");
+        self.line(
+            d + 1,
+            "fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {",
+        );
+        self.line(d + 2, "Self { _tab: flatbuffers::Table { buf, loc } }");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(d, format!("impl<'a> {}<'a> {{", name));
+        self.name_string(d + 1, decl);
+        self.line(d + 1, "#[inline]");
+        self.line(
+            d + 1,
+            "pub fn init_from_table(table: flatbuffers::Table<'a>) -> Self {",
+        );
+        self.line(d + 2, format!("{} {{ _tab: table }}", name));
+        self.line(d + 1, "}");
+        self.line(d + 1, "#[allow(unused_mut)]");
+        self.line(
+            d + 1,
+            "pub fn create<'bldr: 'args, 'args: 'mut_bldr, 'mut_bldr>(",
+        );
+        self.line(
+            d + 2,
+            "_fbb: &'mut_bldr mut flatbuffers::FlatBufferBuilder<'bldr>,",
+        );
+        let args = if fields.is_empty() { "_args" } else { "args" };
+        self.line(
+            d + 2,
+            format!("{}: &'args {}Args{},", args, name, args_create),
+        );
+        self.line(
+            d + 1,
+            format!(") -> flatbuffers::WIPOffset<{}<'bldr>> {{", name),
+        );
+        self.line(
+            d + 2,
+            format!("let mut builder = {}Builder::new(_fbb);", name),
+        );
+        // `flatc` adds the fields from the largest to the smallest, counting structs as offsets.
+        for size in [8, 4, 2, 1] {
+            for field in fields.iter().rev() {
+                let field_size = match &field.kind {
+                    Kind::UnionType(_) => 1,
+                    Kind::Scalar(_, size) | Kind::Enum(_, size, _) => *size,
+                    _ => 4,
+                };
+                if field_size != size {
+                    continue;
+                }
+                if is_optional_arg(field) {
+                    self.line(
+                        d + 2,
+                        format!(
+                            "if let Some(x) = args.{} {{ builder.add_{}(x); }}",
+                            field.name, field.name
+                        ),
+                    );
+                } else {
+                    self.line(
+                        d + 2,
+                        format!("builder.add_{}(args.{});", field.name, field.name),
+                    );
+                }
+            }
+        }
+        self.line(d + 2, "builder.finish()");
+        self.line(d + 1, "}");
+        self.line(d, "");
+        for field in &fields {
+            self.line(
+                d + 1,
+                format!(
+                    "pub const {}: flatbuffers::VOffsetT = {};",
+                    field.vt, field.voffset
+                ),
+            );
+        }
+        self.line(d, "");
+        for field in &fields {
+            self.accessor(d + 1, decl, field)?;
+        }
+        self.line(d, "}");
+        self.line(d, "");
+
+        self.line(
+            d,
+            format!("impl flatbuffers::Verifiable for {}<'_> {{", name),
+        );
+        self.line(d + 1, "#[inline]");
+        self.line(d + 1, "fn run_verifier(");
+        self.line(d + 2, "v: &mut flatbuffers::Verifier,");
+        self.line(d + 2, "pos: usize,");
+        self.line(d + 1, ") -> Result<(), flatbuffers::InvalidFlatbuffer> {");
+        self.line(d + 2, "use self::flatbuffers::Verifiable;");
+        self.line(d + 2, "v.visit_table(pos)?");
+        for field in &fields {
+            let required = field.field.attribute("required").is_some();
+            let verify = match &field.kind {
+                Kind::UnionType(_) => continue,
+                Kind::Union(path, union) => {
+                    self.line(
+                        d + 3,
+                        format!(
+                            ".visit_union::<{}Type, _>(&\"{}_type\", Self::{}_TYPE, &\"{}\", Self::{}, {}, |key, v, pos| {{",
+                            path, field.field.name, field.vt, field.field.name, field.vt, required
+                        ),
+                    );
+                    self.line(d + 4, "match key {");
+                    if let DeclKind::Union(values) = &union.kind {
+                        for value in values {
+                            let target = self.union_target(union, value)?;
+                            self.line(
+                                d + 5,
+                                format!(
+                                    "{}Type::{} => v.verify_union_variant::<flatbuffers::ForwardsUOffset<{}>>(\"{}Type::{}\", pos),",
+                                    path, value.name, target, union.name, value.name
+                                ),
+                            );
+                        }
+                    }
+                    self.line(d + 5, "_ => Ok(()),");
+                    self.line(d + 4, "}");
+                    self.line(d + 3, "})?");
+                    continue;
+                }
+                Kind::Scalar(rust, _) => rust.to_string(),
+                Kind::Enum(path, ..) | Kind::Struct(path, _) => path.clone(),
+                Kind::Table(path) => format!("flatbuffers::ForwardsUOffset<{}>", path),
+                Kind::String => "flatbuffers::ForwardsUOffset<&str>".to_string(),
+                Kind::Vector(element) => format!(
+                    "flatbuffers::ForwardsUOffset<flatbuffers::Vector<'_, {}>>",
+                    element.replace("'a", "'_")
+                ),
+                Kind::Array(..) => unreachable!(),
+            };
+            self.line(
+                d + 3,
+                format!(
+                    ".visit_field::<{}>(&\"{}\", Self::{}, {})?",
+                    verify, field.field.name, field.vt, required
+                ),
+            );
+        }
+        self.line(d + 3, ".finish();");
+        self.line(d + 2, "Ok(())");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+
+        self.line(d, format!("pub struct {}Args{} {{", name, args_lifetime));
+        for field in &fields {
+            self.line(d + 1, format!("pub {}: {},", field.name, args_type(field)));
+        }
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(
+            d,
+            format!("impl<'a> Default for {}Args{} {{", name, args_lifetime),
+        );
+        self.line(d + 1, "#[inline]");
+        self.line(d + 1, "fn default() -> Self {");
+        self.line(d + 2, format!("{}Args {{", name));
+        for field in &fields {
+            let value = if is_optional_arg(field) {
+                "None".to_string()
+            } else {
+                self.default(field.field, &field.kind)?
+            };
+            self.line(d + 3, format!("{}: {},", field.name, value));
+        }
+        self.line(d + 2, "}");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+
+        self.line(d, format!("pub struct {}Builder<'a: 'b, 'b> {{", name));
+        self.line(d + 1, "fbb_: &'b mut flatbuffers::FlatBufferBuilder<'a>,");
+        self.line(
+            d + 1,
+            "start_: flatbuffers::WIPOffset<flatbuffers::TableUnfinishedWIPOffset>,",
+        );
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(d, format!("impl<'a: 'b, 'b> {}Builder<'a, 'b> {{", name));
+        for field in &fields {
+            let arg = &field.name;
+            let vt = format!("{}::{}", name, field.vt);
+            let (param, push) = match &field.kind {
+                Kind::UnionType(path) => (
+                    path.clone(),
+                    format!("push_slot::<{}>({}, {}, {}::NONE)", path, vt, arg, path),
+                ),
+                Kind::Scalar(rust, _) if matches!(field.field.default, Some(Value::Null)) => (
+                    rust.to_string(),
+                    format!("push_slot_always::<{}>({}, {})", rust, vt, arg),
+                ),
+                Kind::Scalar(rust, _) => (
+                    rust.to_string(),
+                    format!(
+                        "push_slot::<{}>({}, {}, {})",
+                        rust,
+                        vt,
+                        arg,
+                        self.default(field.field, &field.kind)?
+                    ),
+                ),
+                Kind::Enum(path, ..) => (
+                    path.clone(),
+                    format!(
+                        "push_slot::<{}>({}, {}, {})",
+                        path,
+                        vt,
+                        arg,
+                        self.default(field.field, &field.kind)?
+                    ),
+                ),
+                Kind::Struct(path, _) => (
+                    format!("&{}", path),
+                    format!("push_slot_always::<&{}>({}, {})", path, vt, arg),
+                ),
+                Kind::Table(path) => (
+                    format!("flatbuffers::WIPOffset<{}<'b>>", path),
+                    format!(
+                        "push_slot_always::<flatbuffers::WIPOffset<{}>>({}, {})",
+                        path, vt, arg
+                    ),
+                ),
+                Kind::Union(..) => (
+                    "flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>".to_string(),
+                    format!(
+                        "push_slot_always::<flatbuffers::WIPOffset<_>>({}, {})",
+                        vt, arg
+                    ),
+                ),
+                Kind::String => (
+                    "flatbuffers::WIPOffset<&'b str>".to_string(),
+                    format!(
+                        "push_slot_always::<flatbuffers::WIPOffset<_>>({}, {})",
+                        vt, arg
+                    ),
+                ),
+                Kind::Vector(element) => (
+                    format!(
+                        "flatbuffers::WIPOffset<flatbuffers::Vector<'b, {}>>",
+                        element.replace("'a", "'b")
+                    ),
+                    format!(
+                        "push_slot_always::<flatbuffers::WIPOffset<_>>({}, {})",
+                        vt, arg
+                    ),
+                ),
+                Kind::Array(..) => unreachable!(),
+            };
+            self.line(d + 1, "#[inline]");
+            self.line(
+                d + 1,
+                format!("pub fn add_{}(&mut self, {}: {}) {{", arg, arg, param),
+            );
+            self.line(d + 2, format!("self.fbb_.{};", push));
+            self.line(d + 1, "}");
+        }
+        self.line(d + 1, "#[inline]");
+        self.line(
+            d + 1,
+            format!(
+                "pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a>) -> {}Builder<'a, 'b> {{",
+                name
+            ),
+        );
+        self.line(d + 2, "let start = _fbb.start_table();");
+        self.line(
+            d + 2,
+            format!("{}Builder {{ fbb_: _fbb, start_: start }}", name),
+        );
+        self.line(d + 1, "}");
+        self.line(d + 1, "#[inline]");
+        self.line(
+            d + 1,
+            format!(
+                "pub fn finish(self) -> flatbuffers::WIPOffset<{}<'a>> {{",
+                name
+            ),
+        );
+        self.line(d + 2, "let o = self.fbb_.end_table(self.start_);");
+        for field in &fields {
+            if field.field.attribute("required").is_some()
+                && !matches!(field.kind, Kind::UnionType(_))
+            {
+                self.line(
+                    d + 2,
+                    format!(
+                        "self.fbb_.required(o, {}::{}, \"{}\");",
+                        name, field.vt, field.field.name
+                    ),
+                );
+            }
+        }
+        self.line(d + 2, "flatbuffers::WIPOffset::new(o.value())");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+
+        self.line(d, format!("impl std::fmt::Debug for {}<'_> {{", name));
+        self.line(
+            d + 1,
+            "fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {",
+        );
+        self.line(d + 2, format!("let mut ds = f.debug_struct(\"{}\");", name));
+        for field in &fields {
+            if let Kind::Union(..) = field.kind {
+                continue;
+            }
+            self.line(
+                d + 2,
+                format!(
+                    "ds.field(\"{}\", &self.{}());",
+                    field.name.trim_end_matches('_'),
+                    field.name
+                ),
+            );
+        }
+        self.line(d + 2, "ds.finish()");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        Ok(())
+    }
+
+    fn union_target(&self, union: &Decl, value: &EnumVal) -> Result<String> {
+        let ty = value.ty.as_deref().unwrap();
+        match self.lookup(ty, &union.namespace) {
+            Some(decl) if matches!(decl.kind, DeclKind::Table(_)) => {
+                Ok(self.path(decl, &union.namespace))
+            }
+            _ => self.error(format!("union variant `{}` must be a table", ty)),
+        }
+    }
+
+    fn accessor(&mut self, d: usize, decl: &'s Decl, field: &TableField<'s>) -> Result<()> {
+        let vt = format!("{}::{}", decl.name, field.vt);
+        let required = field.field.attribute("required").is_some();
+        self.doc(d, &field.field.doc);
+        self.line(d, "#[inline]");
+        let (ret, get, unwrap) = match &field.kind {
+            Kind::UnionType(path) => (
+                path.clone(),
+                format!("get::<{}>({}, Some({}::NONE))", path, vt, path),
+                ".unwrap()",
+            ),
+            Kind::Scalar(rust, _) if matches!(field.field.default, Some(Value::Null)) => (
+                format!("Option<{}>", rust),
+                format!("get::<{}>({}, None)", rust, vt),
+                "",
+            ),
+            Kind::Scalar(rust, _) => (
+                rust.to_string(),
+                format!(
+                    "get::<{}>({}, Some({}))",
+                    rust,
+                    vt,
+                    self.default(field.field, &field.kind)?
+                ),
+                ".unwrap()",
+            ),
+            Kind::Enum(path, ..) => (
+                path.clone(),
+                format!(
+                    "get::<{}>({}, Some({}))",
+                    path,
+                    vt,
+                    self.default(field.field, &field.kind)?
+                ),
+                ".unwrap()",
+            ),
+            Kind::Struct(path, _) => (
+                format!("&'a {}", path),
+                format!("get::<{}>({}, None)", path, vt),
+                "",
+            ),
+            Kind::Table(path) => (
+                format!("{}<'a>", path),
+                format!(
+                    "get::<flatbuffers::ForwardsUOffset<{}>>({}, None)",
+                    path, vt
+                ),
+                "",
+            ),
+            Kind::Union(..) => (
+                "flatbuffers::Table<'a>".to_string(),
+                format!(
+                    "get::<flatbuffers::ForwardsUOffset<flatbuffers::Table<'a>>>({}, None)",
+                    vt
+                ),
+                "",
+            ),
+            Kind::String => (
+                "&'a str".to_string(),
+                format!("get::<flatbuffers::ForwardsUOffset<&str>>({}, None)", vt),
+                "",
+            ),
+            Kind::Vector(element) if self.is_slice(&field.field.ty, &decl.namespace) => (
+                format!("&'a [{}]", element),
+                format!(
+                    "get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, {}>>>({}, None).map(|v| v.safe_slice())",
+                    element, vt
+                ),
+                "",
+            ),
+            Kind::Vector(element) => (
+                format!("flatbuffers::Vector<'a, {}>", element),
+                format!(
+                    "get::<flatbuffers::ForwardsUOffset<flatbuffers::Vector<'a, {}>>>({}, None)",
+                    element, vt
+                ),
+                "",
+            ),
+            Kind::Array(..) => unreachable!(),
+        };
+        let (ret, unwrap) = if unwrap.is_empty() && !ret.starts_with("Option<") {
+            if required {
+                (ret, ".unwrap()")
+            } else {
+                (format!("Option<{}>", ret), "")
+            }
+        } else {
+            (ret, unwrap)
+        };
+        self.line(d, format!("pub fn {}(&self) -> {} {{", field.name, ret));
+        self.line(d + 1, format!("self._tab.{}{}", get, unwrap));
+        self.line(d, "}");
+        if let Kind::Union(path, union) = &field.kind {
+            let values = match &union.kind {
+                DeclKind::Union(values) => values,
+                _ => unreachable!(),
+            };
+            let value_call = if required {
+                format!("Some(self.{}())", field.name)
+            } else {
+                format!("self.{}()", field.name)
+            };
+            for value in values {
+                let target = self.union_target(union, value)?;
+                self.line(d, "#[inline]");
+                self.line(d, "#[allow(non_snake_case)]");
+                self.line(
+                    d,
+                    format!(
+                        "pub fn {}_as_{}(&self) -> Option<{}<'a>> {{",
+                        field.name,
+                        snake_case(&value.name),
+                        target
+                    ),
+                );
+                self.line(
+                    d + 1,
+                    format!(
+                        "if self.{}_type() == {}Type::{} {{",
+                        snake_case(&field.field.name),
+                        path,
+                        value.name
+                    ),
+                );
+                self.line(
+                    d + 2,
+                    format!("{}.map({}::init_from_table)", value_call, target),
+                );
+                self.line(d + 1, "} else {");
+                self.line(d + 2, "None");
+                self.line(d + 1, "}");
+                self.line(d, "}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a vector is read as a slice, as `flatc` does for bytes, bools and structs.
+    fn is_slice(&self, ty: &Type, from: &[String]) -> bool {
+        match ty {
+            Type::Vector(element) => {
+                matches!(**element, Type::Byte | Type::UByte | Type::Bool)
+                    || matches!(self.kind(element, from), Ok(Kind::Struct(..)))
+            }
+            _ => false,
+        }
+    }
+
+    fn name_string(&mut self, d: usize, decl: &Decl) {
+        if self.options.name_strings {
+            let mut name = decl.namespace.join(".");
+            if !name.is_empty() {
+                name.push('.');
+            }
+            name.push_str(&decl.name);
+            self.line(
+                d,
+                "pub const fn get_fully_qualified_name() -> &'static str {",
+            );
+            self.line(d + 1, format!("{:?}", name));
+            self.line(d, "}");
+        }
+    }
+
+    /// Computes the size, alignment and field offsets of a struct.
+    fn layout(
+        &self,
+        decl: &'s Decl,
+        stack: &mut Vec<String>,
+    ) -> Result<(usize, usize, Vec<usize>)> {
+        let fields = match &decl.kind {
+            DeclKind::Struct(fields) => fields,
+            _ => return self.error(format!("`{}` is not a struct", decl.name)),
+        };
+        if stack.contains(&decl.name) {
+            return self.error(format!("struct `{}` contains itself", decl.name));
+        }
+        stack.push(decl.name.clone());
+        let mut size = 0usize;
+        let mut align = 1;
+        let mut offsets = vec![];
+        for field in fields {
+            let kind = self.kind(&field.ty, &decl.namespace)?;
+            let (field_size, field_align) = self.inline_size(&kind, field, stack)?;
+            size = size.next_multiple_of(field_align);
+            offsets.push(size);
+            size += field_size;
+            align = align.max(field_align);
+        }
+        if let Some(Attribute {
+            value: Some(force), ..
+        }) = Attribute::find(&decl.attributes, "force_align")
+        {
+            match force.parse::<usize>() {
+                Ok(force) if force.is_power_of_two() && force >= align && force <= 256 => {
+                    align = force
+                }
+                _ => return self.error(format!("bad force_align on `{}`", decl.name)),
+            }
+        }
+        size = size.next_multiple_of(align);
+        stack.pop();
+        Ok((size, align, offsets))
+    }
+
+    fn inline_size(
+        &self,
+        kind: &Kind<'s>,
+        field: &Field,
+        stack: &mut Vec<String>,
+    ) -> Result<(usize, usize)> {
+        match kind {
+            Kind::Scalar(_, size) | Kind::Enum(_, size, _) => Ok((*size, *size)),
+            Kind::Struct(_, decl) => {
+                let (size, align, _) = self.layout(decl, stack)?;
+                Ok((size, align))
+            }
+            Kind::Array(element, len) => {
+                if let Kind::Array(..) = **element {
+                    return self.error(format!("nested array `{}`", field.name));
+                }
+                let (size, align) = self.inline_size(element, field, stack)?;
+                Ok((size * len, align))
+            }
+            _ => self.error(format!(
+                "struct field `{}` must be a scalar, enum, struct or array",
+                field.name
+            )),
+        }
+    }
+
+    fn structure(&mut self, d: usize, decl: &'s Decl, fields: &'s [Field]) -> Result<()> {
+        let name = &decl.name;
+        let (size, align, offsets) = self.layout(decl, &mut vec![])?;
+        let mut members = vec![];
+        for (field, offset) in fields.iter().zip(offsets) {
+            members.push((field, offset, self.kind(&field.ty, &decl.namespace)?));
+        }
+        self.line(d, format!("// struct {}, aligned to {}", name, align));
+        self.doc(d, &decl.doc);
+        self.line(d, "#[repr(transparent)]");
+        self.line(d, "#[derive(Clone, Copy, PartialEq)]");
+        self.line(d, format!("pub struct {}(pub [u8; {}]);", name, size));
+        self.line(d, format!("impl Default for {} {{", name));
+        self.line(d + 1, "fn default() -> Self {");
+        self.line(d + 2, format!("Self([0; {}])", size));
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, format!("impl std::fmt::Debug for {} {{", name));
+        self.line(
+            d + 1,
+            "fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {",
+        );
+        self.line(d + 2, format!("f.debug_struct(\"{}\")", name));
+        for (field, _, _) in &members {
+            self.line(
+                d + 3,
+                format!(
+                    ".field(\"{}\", &self.{}())",
+                    field.name,
+                    escape(snake_case(&field.name))
+                ),
+            );
+        }
+        self.line(d + 3, ".finish()");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(
+            d,
+            format!("impl flatbuffers::SimpleToVerifyInSlice for {} {{}}", name),
+        );
+        self.line(
+            d,
+            format!("impl flatbuffers::SafeSliceAccess for {} {{}}", name),
+        );
+        self.line(
+            d,
+            format!("impl<'a> flatbuffers::Follow<'a> for {} {{", name),
+        );
+        self.line(d + 1, format!("type Inner = &'a {};", name));
+        self.line(d + 1, "#[inline]");
+        self.line(
+            d + 1,
+            "fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {",
+        );
+        self.line(d + 2, format!("<&'a {}>::follow(buf, loc)", name));
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(
+            d,
+            format!("impl<'a> flatbuffers::Follow<'a> for &'a {} {{", name),
+        );
+        self.line(d + 1, format!("type Inner = &'a {};", name));
+        self.line(d + 1, "#[inline]");
+        self.line(
+            d + 1,
+            "fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {",
+        );
+        self.line(
+            d + 2,
+            format!("flatbuffers::follow_cast_ref::<{}>(buf, loc)", name),
+        );
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        for (target, this) in [
+            (name.to_string(), "self"),
+            (format!("&'b {}", name), "*self"),
+        ] {
+            self.line(d, format!("impl<'b> flatbuffers::Push for {} {{", target));
+            self.line(d + 1, format!("type Output = {};", name));
+            self.line(d + 1, "#[inline]");
+            self.line(d + 1, "fn push(&self, dst: &mut [u8], _rest: &[u8]) {");
+            self.line(d + 2, "let src = unsafe {");
+            self.line(
+                d + 3,
+                format!(
+                    "::std::slice::from_raw_parts({} as *const {} as *const u8, Self::size())",
+                    this, name
+                ),
+            );
+            self.line(d + 2, "};");
+            self.line(d + 2, "dst.copy_from_slice(src);");
+            self.line(d + 1, "}");
+            self.line(d, "}");
+        }
+        self.line(d, "");
+        self.line(
+            d,
+            format!("impl<'a> flatbuffers::Verifiable for {} {{", name),
+        );
+        self.line(d + 1, "#[inline]");
+        self.line(d + 1, "fn run_verifier(");
+        self.line(d + 2, "v: &mut flatbuffers::Verifier,");
+        self.line(d + 2, "pos: usize,");
+        self.line(d + 1, ") -> Result<(), flatbuffers::InvalidFlatbuffer> {");
+        self.line(d + 2, "use self::flatbuffers::Verifiable;");
+        self.line(d + 2, "v.in_buffer::<Self>(pos)");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, format!("impl<'a> {} {{", name));
+        self.line(d + 1, "#[allow(clippy::too_many_arguments)]");
+        self.line(d + 1, "pub fn new(");
+        for (field, _, kind) in &members {
+            self.line(
+                d + 2,
+                format!(
+                    "{}: {},",
+                    escape(snake_case(&field.name)),
+                    self.struct_param(kind)
+                ),
+            );
+        }
+        self.line(d + 1, ") -> Self {");
+        self.line(d + 2, format!("let mut s = Self([0; {}]);", size));
+        for (field, _, kind) in &members {
+            let field_name = escape(snake_case(&field.name));
+            let borrow = if let Kind::Struct(..) | Kind::Array(..) = kind {
+                "&"
+            } else {
+                ""
+            };
+            self.line(
+                d + 2,
+                format!(
+                    "s.set_{}({}{});",
+                    field_name.trim_end_matches('_'),
+                    borrow,
+                    field_name
+                ),
+            );
+        }
+        self.line(d + 2, "s");
+        self.line(d + 1, "}");
+        self.line(d, "");
+        self.name_string(d + 1, decl);
+        for (field, offset, kind) in &members {
+            let field_name = escape(snake_case(&field.name));
+            let setter = format!("set_{}", field_name.trim_end_matches('_'));
+            let (field_size, _) = self.inline_size(kind, field, &mut vec![])?;
+            self.doc(d + 1, &field.doc);
+            match kind {
+                Kind::Struct(path, _) => {
+                    self.line(
+                        d + 1,
+                        format!("pub fn {}(&self) -> &{} {{", field_name, path),
+                    );
+                    self.line(
+                        d + 2,
+                        format!(
+                            "unsafe {{ &*(self.0[{}..].as_ptr() as *const {}) }}",
+                            offset, path
+                        ),
+                    );
+                    self.line(d + 1, "}");
+                    self.line(d, "");
+                    self.line(
+                        d + 1,
+                        format!("pub fn {}(&mut self, x: &{}) {{", setter, path),
+                    );
+                    self.line(
+                        d + 2,
+                        format!(
+                            "self.0[{}..{}+{}].copy_from_slice(&x.0)",
+                            offset, offset, field_size
+                        ),
+                    );
+                    self.line(d + 1, "}");
+                }
+                Kind::Array(element, len) => {
+                    self.line(
+                        d + 1,
+                        format!(
+                            "pub fn {}(&'a self) -> flatbuffers::Array<'a, {}, {}> {{",
+                            field_name,
+                            self.struct_type(element),
+                            len
+                        ),
+                    );
+                    self.line(
+                        d + 2,
+                        format!("flatbuffers::Array::follow(&self.0, {})", offset),
+                    );
+                    self.line(d + 1, "}");
+                    self.line(d, "");
+                    if let Kind::Scalar(..) = **element {
+                        self.line(
+                            d + 1,
+                            format!(
+                                "pub fn {}(&mut self, items: {}) {{",
+                                setter,
+                                self.struct_param(kind)
+                            ),
+                        );
+                        self.line(
+                            d + 2,
+                            format!(
+                                "flatbuffers::emplace_scalar_array(&mut self.0, {}, items);",
+                                offset
+                            ),
+                        );
+                    } else {
+                        self.line(
+                            d + 1,
+                            format!(
+                                "pub fn {}(&mut self, x: {}) {{",
+                                setter,
+                                self.struct_param(kind)
+                            ),
+                        );
+                        self.line(d + 2, "unsafe {");
+                        self.line(d + 3, "std::ptr::copy(");
+                        self.line(d + 4, "x.as_ptr() as *const u8,");
+                        self.line(d + 4, format!("self.0.as_mut_ptr().add({}),", offset));
+                        self.line(d + 4, format!("{},", field_size));
+                        self.line(d + 3, ");");
+                        self.line(d + 2, "}");
+                    }
+                    self.line(d + 1, "}");
+                }
+                _ => {
+                    let ty = self.struct_type(kind);
+                    self.line(d + 1, format!("pub fn {}(&self) -> {} {{", field_name, ty));
+                    self.line(
+                        d + 2,
+                        format!("let mut mem = core::mem::MaybeUninit::<{}>::uninit();", ty),
+                    );
+                    self.line(d + 2, "unsafe {");
+                    self.line(d + 3, "core::ptr::copy_nonoverlapping(");
+                    self.line(d + 4, format!("self.0[{}..].as_ptr(),", offset));
+                    self.line(d + 4, "mem.as_mut_ptr() as *mut u8,");
+                    self.line(d + 4, format!("core::mem::size_of::<{}>(),", ty));
+                    self.line(d + 3, ");");
+                    self.line(d + 3, "mem.assume_init()");
+                    self.line(d + 2, "}.from_little_endian()");
+                    self.line(d + 1, "}");
+                    self.line(d, "");
+                    self.line(d + 1, format!("pub fn {}(&mut self, x: {}) {{", setter, ty));
+                    self.line(d + 2, "let x_le = x.to_little_endian();");
+                    self.line(d + 2, "unsafe {");
+                    self.line(d + 3, "core::ptr::copy_nonoverlapping(");
+                    self.line(d + 4, format!("&x_le as *const {} as *const u8,", ty));
+                    self.line(d + 4, format!("self.0[{}..].as_mut_ptr(),", offset));
+                    self.line(d + 4, format!("core::mem::size_of::<{}>(),", ty));
+                    self.line(d + 3, ");");
+                    self.line(d + 2, "}");
+                    self.line(d + 1, "}");
+                }
+            }
+            self.line(d, "");
+        }
+        self.line(d, "}");
+        Ok(())
+    }
+
+    fn struct_type(&self, kind: &Kind) -> String {
+        match kind {
+            Kind::Scalar(rust, _) => rust.to_string(),
+            Kind::Enum(path, ..) | Kind::Struct(path, _) => path.clone(),
+            Kind::Array(element, len) => format!("[{}; {}]", self.struct_type(element), len),
+            _ => unreachable!(),
+        }
+    }
+
+    fn struct_param(&self, kind: &Kind) -> String {
+        match kind {
+            Kind::Struct(path, _) => format!("&{}", path),
+            Kind::Array(..) => format!("&{}", self.struct_type(kind)),
+            _ => self.struct_type(kind),
+        }
+    }
+
+    fn enumeration(
+        &mut self,
+        d: usize,
+        decl: &'s Decl,
+        rust: &str,
+        size: usize,
+        values: &[EnumVal],
+    ) -> Result<()> {
+        let name = match decl.kind {
+            DeclKind::Union(_) => format!("{}Type", decl.name),
+            _ => decl.name.clone(),
+        };
+        let (min, max) = match rust {
+            "i8" => (i8::MIN as i128, i8::MAX as i128),
+            "u8" => (0, u8::MAX as i128),
+            "i16" => (i16::MIN as i128, i16::MAX as i128),
+            "u16" => (0, u16::MAX as i128),
+            "i32" => (i32::MIN as i128, i32::MAX as i128),
+            "u32" => (0, u32::MAX as i128),
+            "i64" => (i64::MIN as i128, i64::MAX as i128),
+            _ => (0, u64::MAX as i128),
+        };
+        for value in values {
+            if value.value < min || value.value > max {
+                return self.error(format!(
+                    "`{}::{}` does not fit in {}",
+                    decl.name, value.name, rust
+                ));
+            }
+        }
+        self.doc(d, &decl.doc);
+        self.line(
+            d,
+            "#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]",
+        );
+        self.line(d, "#[repr(transparent)]");
+        self.line(d, format!("pub struct {}(pub {});", name, rust));
+        self.line(d, "");
+        self.line(d, "#[allow(non_upper_case_globals)]");
+        self.line(d, format!("impl {} {{", name));
+        for value in values {
+            self.doc(d + 1, &value.doc);
+            self.line(
+                d + 1,
+                format!("pub const {}: Self = Self({});", value.name, value.value),
+            );
+        }
+        self.line(d, "");
+        let min_value = values.iter().map(|v| v.value).min().unwrap_or(0);
+        let max_value = values.iter().map(|v| v.value).max().unwrap_or(0);
+        self.line(
+            d + 1,
+            format!("pub const ENUM_MIN: {} = {};", rust, min_value),
+        );
+        self.line(
+            d + 1,
+            format!("pub const ENUM_MAX: {} = {};", rust, max_value),
+        );
+        let all: Vec<String> = values.iter().map(|v| format!("Self::{}", v.name)).collect();
+        self.line(
+            d + 1,
+            format!(
+                "pub const ENUM_VALUES: &'static [Self] = &[{}];",
+                all.join(", ")
+            ),
+        );
+        self.line(d + 1, "/// Returns the variant's name or None if unknown.");
+        self.line(d + 1, "pub fn variant_name(self) -> Option<&'static str> {");
+        self.line(d + 2, "match self {");
+        for value in values {
+            self.line(
+                d + 3,
+                format!("Self::{} => Some(\"{}\"),", value.name, value.name),
+            );
+        }
+        self.line(d + 3, "_ => None,");
+        self.line(d + 2, "}");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(d, format!("impl std::fmt::Debug for {} {{", name));
+        self.line(
+            d + 1,
+            "fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {",
+        );
+        self.line(d + 2, "if let Some(name) = self.variant_name() {");
+        self.line(d + 3, "f.write_str(name)");
+        self.line(d + 2, "} else {");
+        self.line(d + 3, "write!(f, \"<UNKNOWN {:?}>\", self.0)");
+        self.line(d + 2, "}");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(
+            d,
+            format!("impl<'a> flatbuffers::Follow<'a> for {} {{", name),
+        );
+        self.line(d + 1, "type Inner = Self;");
+        self.line(d + 1, "#[inline]");
+        self.line(
+            d + 1,
+            "fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {",
+        );
+        self.line(d + 2, format!("let mut bytes = [0; {}];", size));
+        self.line(
+            d + 2,
+            format!("bytes.copy_from_slice(&buf[loc..loc + {}]);", size),
+        );
+        self.line(d + 2, format!("Self({}::from_le_bytes(bytes))", rust));
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(d, format!("impl flatbuffers::Push for {} {{", name));
+        self.line(d + 1, format!("type Output = {};", name));
+        self.line(d + 1, "#[inline]");
+        self.line(d + 1, "fn push(&self, dst: &mut [u8], _rest: &[u8]) {");
+        self.line(d + 2, "dst.copy_from_slice(&self.0.to_le_bytes());");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(d, format!("impl flatbuffers::EndianScalar for {} {{", name));
+        self.line(d + 1, "#[inline]");
+        self.line(d + 1, "fn to_little_endian(self) -> Self {");
+        self.line(d + 2, format!("Self({}::to_le(self.0))", rust));
+        self.line(d + 1, "}");
+        self.line(d + 1, "#[inline]");
+        self.line(d + 1, "#[allow(clippy::wrong_self_convention)]");
+        self.line(d + 1, "fn from_little_endian(self) -> Self {");
+        self.line(d + 2, format!("Self({}::from_le(self.0))", rust));
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(d, format!("impl flatbuffers::Verifiable for {} {{", name));
+        self.line(d + 1, "#[inline]");
+        self.line(d + 1, "fn run_verifier(");
+        self.line(d + 2, "v: &mut flatbuffers::Verifier,");
+        self.line(d + 2, "pos: usize,");
+        self.line(d + 1, ") -> Result<(), flatbuffers::InvalidFlatbuffer> {");
+        self.line(d + 2, "v.in_buffer::<Self>(pos)");
+        self.line(d + 1, "}");
+        self.line(d, "}");
+        self.line(d, "");
+        self.line(
+            d,
+            format!("impl flatbuffers::SimpleToVerifyInSlice for {} {{}}", name),
+        );
+        if let DeclKind::Union(_) = decl.kind {
+            self.line(d, format!("pub struct {}UnionTableOffset {{}}", decl.name));
+        }
+        Ok(())
+    }
+
+    fn root(&mut self, d: usize, decl: &'s Decl) -> Result<()> {
+        let name = &decl.name;
+        let snake = snake_case(name);
+        let upper = snake.to_ascii_uppercase();
+        self.line(d, "");
+        self.line(d, "#[inline]");
+        self.line(
+            d,
+            format!(
+                "pub fn root_as_{}(buf: &[u8]) -> Result<{}<'_>, flatbuffers::InvalidFlatbuffer> {{",
+                snake, name
+            ),
+        );
+        self.line(d + 1, format!("flatbuffers::root::<{}>(buf)", name));
+        self.line(d, "}");
+        self.line(d, "#[inline]");
+        self.line(
+            d,
+            format!(
+                "pub fn size_prefixed_root_as_{}(buf: &[u8]) -> Result<{}<'_>, flatbuffers::InvalidFlatbuffer> {{",
+                snake, name
+            ),
+        );
+        self.line(
+            d + 1,
+            format!("flatbuffers::size_prefixed_root::<{}>(buf)", name),
+        );
+        self.line(d, "}");
+        self.line(d, "#[inline]");
+        self.line(d, "/// # Safety");
+        self.line(
+            d,
+            "/// The buffer must contain a valid root table; it is not verified.",
+        );
+        self.line(
+            d,
+            format!(
+                "pub unsafe fn root_as_{}_unchecked(buf: &[u8]) -> {}<'_> {{",
+                snake, name
+            ),
+        );
+        self.line(
+            d + 1,
+            format!("flatbuffers::root_unchecked::<{}>(buf)", name),
+        );
+        self.line(d, "}");
+        let identifier = match &self.schema.file_identifier {
+            Some(identifier) => {
+                self.line(
+                    d,
+                    format!("pub const {}_IDENTIFIER: &str = {:?};", upper, identifier),
+                );
+                self.line(d, "#[inline]");
+                self.line(
+                    d,
+                    format!(
+                        "pub fn {}_buffer_has_identifier(buf: &[u8]) -> bool {{",
+                        snake
+                    ),
+                );
+                self.line(
+                    d + 1,
+                    format!(
+                        "flatbuffers::buffer_has_identifier(buf, {}_IDENTIFIER, false)",
+                        upper
+                    ),
+                );
+                self.line(d, "}");
+                format!("Some({}_IDENTIFIER)", upper)
+            }
+            None => "None".to_string(),
+        };
+        if let Some(extension) = &self.schema.file_extension {
+            self.line(
+                d,
+                format!("pub const {}_EXTENSION: &str = {:?};", upper, extension),
+            );
+        }
+        for (prefix, method) in [("", "finish"), ("size_prefixed_", "finish_size_prefixed")] {
+            self.line(d, "#[inline]");
+            self.line(d, format!("pub fn finish_{}{}_buffer<'a>(", prefix, snake));
+            self.line(d + 1, "fbb: &mut flatbuffers::FlatBufferBuilder<'a>,");
+            self.line(
+                d + 1,
+                format!("root: flatbuffers::WIPOffset<{}<'a>>,", name),
+            );
+            self.line(d, ") {");
+            self.line(d + 1, format!("fbb.{}(root, {});", method, identifier));
+            self.line(d, "}");
+        }
+        Ok(())
+    }
+}
+
+fn is_optional_arg(field: &TableField) -> bool {
+    match &field.kind {
+        Kind::Scalar(..) => matches!(field.field.default, Some(Value::Null)),
+        Kind::Enum(..) | Kind::UnionType(_) => false,
+        _ => true,
+    }
+}
+
+fn args_type(field: &TableField) -> String {
+    match &field.kind {
+        Kind::Scalar(rust, _) if matches!(field.field.default, Some(Value::Null)) => {
+            format!("Option<{}>", rust)
+        }
+        Kind::Scalar(rust, _) => rust.to_string(),
+        Kind::Enum(path, ..) | Kind::UnionType(path) => path.clone(),
+        Kind::Struct(path, _) => format!("Option<&'a {}>", path),
+        Kind::Table(path) => format!("Option<flatbuffers::WIPOffset<{}<'a>>>", path),
+        Kind::Union(..) => {
+            "Option<flatbuffers::WIPOffset<flatbuffers::UnionWIPOffset>>".to_string()
+        }
+        Kind::String => "Option<flatbuffers::WIPOffset<&'a str>>".to_string(),
+        Kind::Vector(element) => format!(
+            "Option<flatbuffers::WIPOffset<flatbuffers::Vector<'a, {}>>>",
+            element
+        ),
+        Kind::Array(..) => unreachable!(),
+    }
+}
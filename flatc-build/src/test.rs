@@ -0,0 +1,257 @@
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use crate::parser::{parse_file, parse_str, DeclKind, ParseError, Type};
+use crate::rust::generate;
+use crate::{Error, FlatcBuilder};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("flatc-build-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn parse_test_schema() {
+    let schema = parse_file(Path::new("../flatbuffers-serde/schema/test.fbs")).unwrap();
+    assert_eq!(schema.includes.len(), 1);
+    let any = &schema.includes[0];
+    let names: Vec<&str> = any.decls.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["AnyFlat", "AnyLazy", "TypeTagHash", "AnyFlatRaw"]);
+    match &any.decls[2].kind {
+        DeclKind::Struct(fields) => {
+            assert_eq!(fields[0].ty, Type::Array(Box::new(Type::UByte), 16))
+        }
+        _ => panic!(),
+    }
+    let code = generate(&schema).unwrap();
+    assert!(code.contains("pub fn any(&self) -> Option<crate::any_generated::AnyFlat<'a>> {"));
+    assert!(code.contains("pub struct TestArgs<'a> {"));
+}
+
+#[test]
+fn generate_any_schema() {
+    let schema = parse_file(Path::new("../flatbuffers-serde/schema/any.fbs")).unwrap();
+    let code = generate(&schema).unwrap();
+    assert!(code.contains("pub struct TypeTagHash(pub [u8; 16]);"));
+    assert!(code.contains("pub const VT_DATA: flatbuffers::VOffsetT = 6;"));
+    assert!(code.contains("pub fn v(&'a self) -> flatbuffers::Array<'a, u8, 16> {"));
+}
+
+/// The tokens of generated code, ignoring formatting, comments, trailing commas and whether types
+/// from included schemas are imported or named by path.
+fn tokens(code: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = vec![];
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        } else if c == '/' && chars.peek() == Some(&'/') {
+            chars.by_ref().find(|&c| c == '\n');
+        } else if c == '"' {
+            let mut token = c.to_string();
+            while let Some(c) = chars.next() {
+                token.push(c);
+                match c {
+                    '\\' => token.extend(chars.next()),
+                    '"' => break,
+                    _ => {}
+                }
+            }
+            tokens.push(token);
+        } else if c.is_alphanumeric() || c == '_' {
+            let mut token = c.to_string();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        } else if matches!(c, ')' | ']' | '}') && tokens.last().is_some_and(|t| t == ",") {
+            *tokens.last_mut().unwrap() = c.to_string();
+        } else {
+            tokens.push(c.to_string());
+        }
+    }
+    let mut result: Vec<String> = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        let included = tokens[i] == "crate"
+            && tokens.get(i + 3).is_some_and(|t| t.ends_with("_generated"))
+            && tokens[i + 1..i + 3] == [":", ":"];
+        if !included {
+            result.push(tokens[i].clone());
+            i += 1;
+        } else if result.last().is_some_and(|t| t == "use") {
+            result.pop();
+            i += 8;
+        } else {
+            i += 6;
+        }
+    }
+    result
+}
+
+/// `golden/` holds the output of `flatc --rust` 2.0.0 for the same schemas.
+#[test]
+fn generate_matches_flatc() {
+    for name in ["any", "test"] {
+        let schema = format!("../flatbuffers-serde/schema/{}.fbs", name);
+        let code = generate(&parse_file(Path::new(&schema)).unwrap()).unwrap();
+        let golden = format!("golden/{}_generated.rs", name);
+        let (code, golden) = (tokens(&code), tokens(&fs::read_to_string(golden).unwrap()));
+        if let Some(i) = (0..code.len().max(golden.len())).find(|&i| code.get(i) != golden.get(i)) {
+            let context = |tokens: &[String]| tokens[i.saturating_sub(20)..].join(" ");
+            panic!(
+                "{}.fbs differs from flatc:\n{:.300}\n{:.300}",
+                name,
+                context(&code),
+                context(&golden)
+            );
+        }
+    }
+}
+
+#[test]
+fn generate_namespaces_and_unions() {
+    let schema = parse_str(
+        Path::new("inline.fbs"),
+        r#"
+        namespace Game.Sample;
+        enum Color : byte { Red = 1, Green, Blue = 8 }
+        struct Vec3 (force_align: 16) { x: float; y: float; z: float; }
+        table Weapon { damage: short = 5; }
+        union Equipment { Weapon }
+        table Monster {
+            pos: Vec3;
+            color: Color = Green;
+            equipped: Equipment;
+            name: string (required);
+            old: int (deprecated);
+            inventory: [ubyte];
+        }
+        root_type Monster;
+        file_identifier "MONS";
+        "#,
+    )
+    .unwrap();
+    let code = generate(&schema).unwrap();
+    assert!(code.contains("pub mod game {"));
+    assert!(code.contains("pub mod sample {"));
+    assert!(code.contains("pub struct Vec3(pub [u8; 16]);"));
+    assert!(code.contains("pub const Green: Self = Self(2);"));
+    assert!(code.contains("pub const VT_EQUIPPED_TYPE: flatbuffers::VOffsetT = 8;"));
+    assert!(code.contains("pub const VT_INVENTORY: flatbuffers::VOffsetT = 16;"));
+    assert!(!code.contains("VT_OLD"));
+    assert!(code.contains("pub fn name(&self) -> &'a str {"));
+    assert!(code.contains(
+        "pub fn equipped_as_weapon(&self) -> Option<super::super::game::sample::Weapon<'a>> {"
+    ));
+    assert!(code.contains("self.fbb_.required(o, Monster::VT_NAME, \"name\");"));
+    assert!(code.contains("pub const MONSTER_IDENTIFIER: &str = \"MONS\";"));
+}
+
+#[test]
+fn parse_errors() {
+    match parse_str(Path::new("bad.fbs"), "table A {\n  x: int\n}") {
+        Err(ParseError::Syntax { line, .. }) => assert_eq!(line, 3),
+        result => panic!("{:?}", result),
+    }
+    let schema = parse_str(Path::new("bad.fbs"), "table A { x: Missing; }").unwrap();
+    assert!(generate(&schema).is_err());
+}
+
+#[test]
+fn builder_recursive_layout() {
+    let dir = temp_dir("layout");
+    fs::create_dir_all(dir.join("in/nested")).unwrap();
+    fs::create_dir_all(dir.join("common")).unwrap();
+    fs::write(dir.join("common/shared.fbs"), "table Shared {}").unwrap();
+    fs::write(dir.join("in/top.fbs"), "table Top {}").unwrap();
+    fs::write(
+        dir.join("in/nested/inner.fbs"),
+        "include \"shared.fbs\";\ntable Inner { shared: Shared; }",
+    )
+    .unwrap();
+    let outputs = FlatcBuilder::new()
+        .input_dir(dir.join("in"))
+        .include_path(dir.join("common"))
+        .recursive(true)
+        .builtin_compiler()
+        .flag("--gen-name-strings")
+        .out_dir(dir.join("out"))
+        .output_layout(|file| file.with_extension("rs"))
+        .compile()
+        .unwrap();
+    assert_eq!(
+        outputs,
+        [dir.join("out/nested/inner.rs"), dir.join("out/top.rs")]
+    );
+    let inner = fs::read_to_string(dir.join("out/nested/inner.rs")).unwrap();
+    assert!(inner.contains("crate::shared_generated::Shared"));
+    assert!(inner.contains("\"Inner\""));
+    let outputs = FlatcBuilder::new()
+        .input_dir(dir.join("in"))
+        .builtin_compiler()
+        .out_dir(dir.join("out"))
+        .compile()
+        .unwrap();
+    assert_eq!(outputs, [dir.join("out/top_generated.rs")]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn builder_errors() {
+    let dir = temp_dir("errors");
+    fs::write(dir.join("bad.fbs"), "table Bad { x: Missing; }").unwrap();
+    let mut builder = FlatcBuilder::new();
+    builder.input_dir(&dir).out_dir(dir.join("out"));
+    match builder.builtin_compiler().compile() {
+        Err(Error::Compile { file, output }) => {
+            assert_eq!(file, dir.join("bad.fbs"));
+            assert!(output.contains("unknown type `Missing`"), "{}", output);
+        }
+        result => panic!("{:?}", result),
+    }
+    match builder.flag("--gen-object-api").compile() {
+        Err(Error::Compile { output, .. }) => assert!(output.contains("--gen-object-api")),
+        result => panic!("{:?}", result),
+    }
+    match builder.flatc(dir.join("missing-flatc")).compile() {
+        Err(Error::Spawn { flatc, .. }) => assert_eq!(flatc, dir.join("missing-flatc")),
+        result => panic!("{:?}", result),
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn builder_captures_flatc_output() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("flatc");
+    fs::write(dir.join("a.fbs"), "table A {}").unwrap();
+    let flatc = dir.join("flatc");
+    fs::write(
+        &flatc,
+        "#!/bin/sh\necho \"args: $*\"\necho oops >&2\nexit 1\n",
+    )
+    .unwrap();
+    fs::set_permissions(&flatc, fs::Permissions::from_mode(0o755)).unwrap();
+    let result = FlatcBuilder::new()
+        .input_dir(&dir)
+        .include_path("inc")
+        .flag("--gen-object-api")
+        .flatc(&flatc)
+        .out_dir(dir.join("out"))
+        .compile();
+    match result {
+        Err(Error::Compile { file, output }) => {
+            assert_eq!(file, dir.join("a.fbs"));
+            assert!(output.contains("-I inc --gen-object-api"), "{}", output);
+            assert!(output.contains("oops"), "{}", output);
+        }
+        result => panic!("{:?}", result),
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -7,14 +7,22 @@ use serde::de::{
 };
 use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::error::Error;
+use crate::error::{Error, Path, PathSegment};
+use crate::hash::ContentHasher;
 use crate::{Schema, SchemaId, SchemaMap, SchemaType, VariantSchema};
 
 #[derive(Debug)]
-struct SchemaMapBuilder {
+pub struct SchemaMapBuilder {
     map: HashMap<SchemaId, SchemaType>,
     versions: HashMap<String, usize>,
     ids: HashMap<TypeId, SchemaId>,
+    /// Types whose schema is already known or under construction that are currently being
+    /// visited again, only to produce a value. Reaching one of these again means the type has no
+    /// finite value along this path.
+    revisiting: Vec<TypeId>,
+    /// Enum variants currently being visited, so that revisiting an enum can pick a variant
+    /// that does not recurse back into itself.
+    variants: Vec<(TypeId, u32)>,
 }
 
 struct SchemaBuilder<'b> {
@@ -24,6 +32,7 @@ struct SchemaBuilder<'b> {
 
 struct SeqBuilder<'b> {
     map: &'b mut SchemaMapBuilder,
+    fields: &'static [&'static str],
     seq: Vec<SchemaId>,
     len: usize,
 }
@@ -87,31 +96,71 @@ impl SchemaMapBuilder {
             map: HashMap::new(),
             versions: HashMap::new(),
             ids: HashMap::new(),
+            revisiting: vec![],
+            variants: vec![],
         }
     }
-    fn add<'de, T: Deserialize<'de>>(&mut self) -> Result<SchemaId, Error> {
-        Ok(self.add_impl::<T>()?.0)
+    /// Add `T` and every type it refers to, returning the content-addressed id of `T`.
+    pub fn add<'de, T: Deserialize<'de>>(&mut self) -> Result<SchemaId, Error> {
+        let id = self
+            .add_impl::<T>()
+            .map_err(|e| e.within(PathSegment::Type(short_type_name::<T>().to_string())))?
+            .0;
+        Ok(ContentHasher::new(&self.map).id(id))
     }
     fn get<'de, T: Deserialize<'de> + Any>(&self) -> SchemaId {
-        *self.ids.get(&TypeId::of::<T>()).unwrap()
+        ContentHasher::new(&self.map).id(*self.ids.get(&TypeId::of::<T>()).unwrap())
     }
     fn add_impl<T>(&mut self) -> Result<(SchemaId, T), Error> {
         let type_id = T::maybe_type_id();
         let mut builder = self.schema_builder();
         let result = T::maybe_deserialize(&mut builder)?;
         let id = builder.id.unwrap();
-        assert_eq!(*self.ids.get(&type_id).unwrap(), id);
+        // Transparent wrappers such as `Box<T>` deserialize as `T`.
+        self.ids.entry(type_id).or_insert(id);
         Ok((id, result))
     }
+    /// Produce a value of a type that was already started, without building its schema again.
+    fn revisit<R>(
+        &mut self,
+        type_id: TypeId,
+        f: impl FnOnce(&mut Self) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        if self.revisiting.contains(&type_id) {
+            return Err(Error::Uninhabited {
+                path: Path::default(),
+            });
+        }
+        self.revisiting.push(type_id);
+        let result = f(self);
+        self.revisiting.pop();
+        result
+    }
+    fn visit_variant<R>(
+        &mut self,
+        type_id: TypeId,
+        index: u32,
+        f: impl FnOnce(&mut Self) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        self.variants.push((type_id, index));
+        let result = f(self);
+        self.variants.pop();
+        result
+    }
     fn schema_builder<'c>(&'c mut self) -> SchemaBuilder<'c> {
         SchemaBuilder {
             map: self,
             id: None,
         }
     }
-    fn seq_builder<'c>(&'c mut self, len: usize) -> SeqBuilder<'c> {
+    fn seq_builder<'c>(
+        &'c mut self,
+        fields: &'static [&'static str],
+        len: usize,
+    ) -> SeqBuilder<'c> {
         SeqBuilder {
             map: self,
+            fields,
             seq: vec![],
             len,
         }
@@ -123,7 +172,19 @@ impl SchemaMapBuilder {
             index,
         }
     }
-    pub fn build(self) -> SchemaMap { SchemaMap { map: self.map } }
+    pub fn build(self) -> SchemaMap { ContentHasher::new(&self.map).rewrite().0 }
+}
+
+/// The name of `T` without its module path, e.g. `Vec<alloc::string::String>` rather than
+/// `alloc::vec::Vec<alloc::string::String>`.
+pub(crate) fn short_type_name<T: ?Sized>() -> &'static str { short_name(type_name::<T>()) }
+
+pub(crate) fn short_name(name: &str) -> &str {
+    let base = &name[..name.find('<').unwrap_or(name.len())];
+    match base.rfind("::") {
+        Some(index) => &name[index + 2..],
+        None => name,
+    }
 }
 
 impl<'b> SchemaBuilder<'b> {
@@ -177,7 +238,13 @@ impl<'a, 'b, 'de> SeqAccess<'de> for &'a mut SeqBuilder<'b> {
             Ok(None)
         } else {
             self.len -= 1;
-            let (id, result) = self.map.add_impl::<T::Value>()?;
+            let index = self.seq.len();
+            let (id, result) = self.map.add_impl::<T::Value>().map_err(|e| {
+                e.within(match self.fields.get(index) {
+                    Some(field) => PathSegment::Field(field.to_string()),
+                    None => PathSegment::Index(index),
+                })
+            })?;
             self.seq.push(id);
             Ok(Some(result))
         }
@@ -190,7 +257,9 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        Err(Error::DeserializeAny {
+            path: Path::default(),
+        })
     }
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
@@ -360,7 +429,11 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        self.start::<V>();
+        if !self.start::<V>() {
+            return self.map.revisit(V::Value::maybe_type_id(), |map| {
+                visitor.visit_newtype_struct(&mut map.schema_builder())
+            });
+        }
         let mut inner = self.map.schema_builder();
         let result = visitor.visit_newtype_struct(&mut inner)?;
         let inner = inner.id.unwrap();
@@ -374,9 +447,10 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        self.start::<V>();
-        let schema = <V::Value>::custom_schema(self.map)?;
-        self.finish::<V>(schema);
+        if self.start::<V>() {
+            let schema = <V::Value>::custom_schema(self.map)?;
+            self.finish::<V>(schema);
+        }
         struct Empty;
         impl<'de> SeqAccess<'de> for Empty {
             type Error = Error;
@@ -393,8 +467,12 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        self.start::<V>();
-        let mut inner = self.map.seq_builder(len);
+        if !self.start::<V>() {
+            return self.map.revisit(V::Value::maybe_type_id(), |map| {
+                visitor.visit_seq(&mut map.seq_builder(&[], len))
+            });
+        }
+        let mut inner = self.map.seq_builder(&[], len);
         let result = visitor.visit_seq(&mut inner)?;
         let inner = inner.seq;
         self.finish::<V>(Schema::Tuple(inner));
@@ -409,8 +487,12 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        self.start::<V>();
-        let mut inner = self.map.seq_builder(len);
+        if !self.start::<V>() {
+            return self.map.revisit(V::Value::maybe_type_id(), |map| {
+                visitor.visit_seq(&mut map.seq_builder(&[], len))
+            });
+        }
+        let mut inner = self.map.seq_builder(&[], len);
         let result = visitor.visit_seq(&mut inner)?;
         let inner = inner.seq;
         self.finish::<V>(Schema::TupleStruct {
@@ -423,9 +505,10 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        self.start::<V>();
-        let schema = <V::Value>::custom_schema(self.map)?;
-        self.finish::<V>(schema);
+        if self.start::<V>() {
+            let schema = <V::Value>::custom_schema(self.map)?;
+            self.finish::<V>(schema);
+        }
         struct Empty;
         impl<'de> MapAccess<'de> for Empty {
             type Error = Error;
@@ -453,20 +536,22 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        let building = self.start::<V>();
-        let mut inner = self.map.seq_builder(fields.len());
-        let result = visitor.visit_seq(&mut inner)?;
-        if building {
-            let inner = inner.seq;
-            self.finish::<V>(Schema::Struct {
-                name: name.to_string(),
-                fields: fields
-                    .iter()
-                    .map(|x| x.to_string())
-                    .zip(inner.into_iter())
-                    .collect(),
+        if !self.start::<V>() {
+            return self.map.revisit(V::Value::maybe_type_id(), |map| {
+                visitor.visit_seq(&mut map.seq_builder(fields, fields.len()))
             });
         }
+        let mut inner = self.map.seq_builder(fields, fields.len());
+        let result = visitor.visit_seq(&mut inner)?;
+        let inner = inner.seq;
+        self.finish::<V>(Schema::Struct {
+            name: name.to_string(),
+            fields: fields
+                .iter()
+                .map(|x| x.to_string())
+                .zip(inner.into_iter())
+                .collect(),
+        });
         Ok(result)
     }
     fn deserialize_enum<V>(
@@ -479,15 +564,25 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
         V: Visitor<'de>,
     {
         if variants.is_empty() {
-            todo!("empty enum");
+            return Err(Error::EmptyEnum {
+                path: Path::default(),
+            });
         }
+        let type_id = V::Value::maybe_type_id();
         if self.start::<V>() {
             let mut result = None;
             let mut enums = Vec::with_capacity(variants.len());
             for (i, variant) in variants.iter().enumerate() {
-                let mut builder = self.map.variant_builder(i as u32);
-                result = Some(V::Value::maybe_deserialize(&mut builder)?);
-                enums.push(builder.enu.unwrap());
+                let (value, enu) = self
+                    .map
+                    .visit_variant(type_id, i as u32, |map| {
+                        let mut builder = map.variant_builder(i as u32);
+                        let value = V::Value::maybe_deserialize(&mut builder)?;
+                        Ok((value, builder.enu.unwrap()))
+                    })
+                    .map_err(|e| e.within(PathSegment::Variant(variant.to_string())))?;
+                result = Some(value);
+                enums.push(enu);
             }
             self.finish::<V>(Schema::Enum {
                 enums: variants
@@ -498,21 +593,45 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut SchemaBuilder<'b> {
             });
             Ok(result.unwrap())
         } else {
-            let mut builder = self.map.variant_builder(0);
-            V::Value::maybe_deserialize(&mut builder)
+            // Any variant will do, but one that is already being visited would recurse forever.
+            let mut error = None;
+            for (i, variant) in variants.iter().enumerate() {
+                if self.map.variants.contains(&(type_id, i as u32)) {
+                    continue;
+                }
+                let result = self.map.visit_variant(type_id, i as u32, |map| {
+                    V::Value::maybe_deserialize(&mut map.variant_builder(i as u32))
+                });
+                match result {
+                    Err(e @ Error::Uninhabited { .. }) => {
+                        error = Some(e.within(PathSegment::Variant(variant.to_string())))
+                    }
+                    result => {
+                        return result
+                            .map_err(|e| e.within(PathSegment::Variant(variant.to_string())))
+                    }
+                }
+            }
+            Err(error.unwrap_or(Error::Uninhabited {
+                path: Path::default(),
+            }))
         }
     }
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        Err(Error::DeserializeIdentifier {
+            path: Path::default(),
+        })
     }
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        Err(Error::DeserializeIgnoredAny {
+            path: Path::default(),
+        })
     }
 }
 
@@ -522,7 +641,9 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut VariantBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        Err(Error::DeserializeAny {
+            path: Path::default(),
+        })
     }
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
@@ -707,13 +828,17 @@ impl<'de, 'a, 'b> Deserializer<'de> for &'a mut VariantBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        Err(Error::DeserializeIdentifier {
+            path: Path::default(),
+        })
     }
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        Err(Error::DeserializeIgnoredAny {
+            path: Path::default(),
+        })
     }
 }
 
@@ -742,7 +867,7 @@ impl<'a, 'b, 'de> VariantAccess<'de> for &'a mut VariantBuilder<'b> {
         let mut builder = self.map.schema_builder();
         let result = seed.deserialize(&mut builder)?;
         let id = builder.id.unwrap();
-        assert_eq!(id, *self.map.ids.get(&T::Value::maybe_type_id()).unwrap());
+        self.map.ids.entry(T::Value::maybe_type_id()).or_insert(id);
         self.enu = Some(VariantSchema::Newtype(id));
         Ok(result)
     }
@@ -750,7 +875,7 @@ impl<'a, 'b, 'de> VariantAccess<'de> for &'a mut VariantBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        let mut builder = self.map.seq_builder(len);
+        let mut builder = self.map.seq_builder(&[], len);
         let result = visitor.visit_seq(&mut builder)?;
         self.enu = Some(VariantSchema::Tuple(builder.seq));
         Ok(result)
@@ -763,7 +888,7 @@ impl<'a, 'b, 'de> VariantAccess<'de> for &'a mut VariantBuilder<'b> {
     where
         V: Visitor<'de>,
     {
-        let mut builder = self.map.seq_builder(fields.len());
+        let mut builder = self.map.seq_builder(fields, fields.len());
         let result = visitor.visit_seq(&mut builder)?;
         self.enu = Some(VariantSchema::Struct(
             fields
@@ -1042,3 +1167,197 @@ fn test_simple() {
     }
     assert_eq!(schema, expected);
 }
+
+#[test]
+fn test_errors() {
+    use serde::de::{Error as _, IgnoredAny};
+
+    struct Fails;
+    impl<'de> Deserialize<'de> for Fails {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            Err(D::Error::custom("no schema for Fails"))
+        }
+    }
+
+    #[derive(Deserialize)]
+    enum Inner {
+        Ok,
+        Variant(Fails),
+    }
+
+    #[derive(Deserialize)]
+    struct Outer {
+        ok: u8,
+        field: (u8, u8, u8, Inner),
+    }
+
+    let err = SchemaMapBuilder::new().add::<Outer>().err().unwrap();
+    assert_eq!(
+        err,
+        Error::Custom {
+            path: Path {
+                segments: vec![
+                    PathSegment::Type("Outer".to_string()),
+                    PathSegment::Field("field".to_string()),
+                    PathSegment::Index(3),
+                    PathSegment::Variant("Variant".to_string()),
+                ]
+            },
+            message: "no schema for Fails".to_string(),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "no schema for Fails at Outer.field[3].Variant"
+    );
+
+    #[derive(Deserialize)]
+    struct Ignores(u8, IgnoredAny);
+
+    let err = SchemaMapBuilder::new().add::<Ignores>().err().unwrap();
+    assert_eq!(err.path().to_string(), "Ignores[1]");
+    assert!(matches!(err, Error::DeserializeIgnoredAny { .. }));
+}
+
+#[test]
+fn test_recursive() {
+    use crate::SchemaSerializer;
+
+    #[derive(Serialize, Deserialize)]
+    struct List {
+        value: u8,
+        next: Option<Box<List>>,
+    }
+
+    #[derive(Deserialize)]
+    enum Expr {
+        Neg(Box<Expr>),
+        Add(Box<Expr>, Box<Expr>),
+        Lit(i32),
+    }
+
+    #[derive(Deserialize)]
+    struct Even {
+        next: Option<Box<Odd>>,
+    }
+
+    #[derive(Deserialize)]
+    struct Odd {
+        next: Box<Even>,
+    }
+
+    #[derive(Deserialize)]
+    struct Tree<T> {
+        value: T,
+        children: Vec<Option<Tree<T>>>,
+    }
+
+    #[derive(Deserialize)]
+    struct Point(u8, u8);
+
+    #[derive(Deserialize)]
+    struct Segment(Point, Point);
+
+    let mut builder = SchemaMapBuilder::new();
+    let list_id = builder.add::<List>().unwrap();
+    let expr_id = builder.add::<Expr>().unwrap();
+    let even_id = builder.add::<Even>().unwrap();
+    let odd_id = builder.get::<Odd>();
+    let tree_u8_id = builder.add::<Tree<u8>>().unwrap();
+    let tree_string_id = builder.add::<Tree<String>>().unwrap();
+    let segment_id = builder.add::<Segment>().unwrap();
+    let next_list_id = builder.get::<Option<Box<List>>>();
+    let next_odd_id = builder.get::<Option<Box<Odd>>>();
+    let children_u8_id = builder.get::<Vec<Option<Tree<u8>>>>();
+    let child_u8_id = builder.get::<Option<Tree<u8>>>();
+    let point_id = builder.get::<Point>();
+    let u8_id = builder.get::<u8>();
+    let i32_id = builder.get::<i32>();
+    let map = builder.build();
+
+    assert_eq!(
+        map.get(list_id).schema,
+        Schema::Struct {
+            name: "List".to_string(),
+            fields: vec![
+                ("value".to_string(), u8_id),
+                ("next".to_string(), next_list_id)
+            ],
+        }
+    );
+    assert_eq!(map.get(next_list_id).schema, Schema::Option(list_id));
+
+    assert_eq!(
+        map.get(expr_id).schema,
+        Schema::Enum {
+            enums: vec![
+                ("Neg".to_string(), VariantSchema::Newtype(expr_id)),
+                (
+                    "Add".to_string(),
+                    VariantSchema::Tuple(vec![expr_id, expr_id])
+                ),
+                ("Lit".to_string(), VariantSchema::Newtype(i32_id)),
+            ],
+        }
+    );
+
+    assert_eq!(
+        map.get(even_id).schema,
+        Schema::Struct {
+            name: "Even".to_string(),
+            fields: vec![("next".to_string(), next_odd_id)],
+        }
+    );
+    assert_eq!(map.get(next_odd_id).schema, Schema::Option(odd_id));
+    assert_eq!(
+        map.get(odd_id).schema,
+        Schema::Struct {
+            name: "Odd".to_string(),
+            fields: vec![("next".to_string(), even_id)],
+        }
+    );
+
+    assert_ne!(tree_u8_id, tree_string_id);
+    assert_eq!(
+        map.get(tree_u8_id).schema,
+        Schema::Struct {
+            name: "Tree".to_string(),
+            fields: vec![
+                ("value".to_string(), u8_id),
+                ("children".to_string(), children_u8_id)
+            ],
+        }
+    );
+    assert_eq!(map.get(children_u8_id).schema, Schema::Vec(child_u8_id));
+    assert_eq!(map.get(child_u8_id).schema, Schema::Option(tree_u8_id));
+
+    assert_eq!(
+        map.get(segment_id).schema,
+        Schema::TupleStruct {
+            name: "Segment".to_string(),
+            fields: vec![point_id, point_id],
+        }
+    );
+
+    let mut serializer = SchemaSerializer::new();
+    serializer
+        .add(&List {
+            value: 1,
+            next: Some(Box::new(List {
+                value: 2,
+                next: None,
+            })),
+        })
+        .unwrap();
+    let (samples, roots) = serializer.build().unwrap();
+    let mut builder = SchemaMapBuilder::new();
+    assert_eq!(roots, vec![builder.add::<List>().unwrap()]);
+    assert_eq!(samples, builder.build());
+
+    #[derive(Deserialize)]
+    struct Loop(u8, Box<Loop>);
+
+    let err = SchemaMapBuilder::new().add::<Loop>().err().unwrap();
+    assert!(matches!(err, Error::Uninhabited { .. }));
+    assert_eq!(err.to_string(), "type has no finite values at Loop[1][1]");
+}
@@ -0,0 +1,525 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use crate::{Schema, SchemaId, SchemaMap, SchemaType, VariantSchema};
+
+/// Whether data can still be exchanged across a change when read with
+/// [`SchemaDeserializer`](crate::SchemaDeserializer).
+///
+/// A change is backward-compatible if the new type can read data written by the old type, and
+/// forward-compatible if the old type can read data written by the new type.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+pub enum Compatibility {
+    Full,
+    Backward,
+    Forward,
+    Breaking,
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone)]
+pub enum ChangeKind {
+    FieldAdded { name: String },
+    FieldRemoved { name: String },
+    Retyped { old: String, new: String },
+    VariantAdded { name: String },
+    VariantRemoved { name: String },
+    VariantRenamed { old: String, new: String },
+    Widened { old: usize, new: usize },
+    Narrowed { old: usize, new: usize },
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone)]
+pub struct Change {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub compatibility: Compatibility,
+}
+
+/// The differences between an old and a new [`SchemaMap`], sorted by path.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct CompatReport {
+    pub changes: Vec<Change>,
+}
+
+struct Checker<'a> {
+    old: &'a SchemaMap,
+    new: &'a SchemaMap,
+    /// The pairs being checked further up the stack, so that recursive types stop at the point
+    /// where they recur. A pair reached again through another path is checked again, so that its
+    /// changes are reported under that path too.
+    in_progress: HashSet<(SchemaId, SchemaId)>,
+    changes: Vec<Change>,
+}
+
+impl Compatibility {
+    fn new(backward: bool, forward: bool) -> Self {
+        match (backward, forward) {
+            (true, true) => Compatibility::Full,
+            (true, false) => Compatibility::Backward,
+            (false, true) => Compatibility::Forward,
+            (false, false) => Compatibility::Breaking,
+        }
+    }
+    pub fn is_backward(self) -> bool {
+        matches!(self, Compatibility::Full | Compatibility::Backward)
+    }
+    pub fn is_forward(self) -> bool { matches!(self, Compatibility::Full | Compatibility::Forward) }
+    pub fn and(self, other: Self) -> Self {
+        Compatibility::new(
+            self.is_backward() && other.is_backward(),
+            self.is_forward() && other.is_forward(),
+        )
+    }
+}
+
+impl CompatReport {
+    /// The compatibility of all changes taken together.
+    pub fn compatibility(&self) -> Compatibility {
+        self.changes
+            .iter()
+            .fold(Compatibility::Full, |x, y| x.and(y.compatibility))
+    }
+    pub fn breaking(&self) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(|x| x.compatibility == Compatibility::Breaking)
+    }
+}
+
+/// Compare the type `old_id` in `old` against the type `new_id` in `new`.
+pub fn check_compatibility(
+    old: &SchemaMap,
+    old_id: SchemaId,
+    new: &SchemaMap,
+    new_id: SchemaId,
+) -> CompatReport {
+    let mut checker = Checker {
+        old,
+        new,
+        in_progress: HashSet::new(),
+        changes: vec![],
+    };
+    checker.check("", old_id, new_id);
+    checker.changes.sort();
+    CompatReport {
+        changes: checker.changes,
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn describe(typ: &SchemaType) -> String {
+    if typ.version == 0 {
+        typ.name.clone()
+    } else {
+        format!("{}#{}", typ.name, typ.version)
+    }
+}
+
+impl<'a> Checker<'a> {
+    fn push(&mut self, path: &str, kind: ChangeKind, compatibility: Compatibility) {
+        self.changes.push(Change {
+            path: path.to_string(),
+            kind,
+            compatibility,
+        });
+    }
+    fn is_option(map: &SchemaMap, id: SchemaId) -> bool {
+        matches!(map.get(id).schema, Schema::Option(_))
+    }
+    fn retyped(&mut self, path: &str, old: SchemaId, new: SchemaId, compatibility: Compatibility) {
+        let kind = ChangeKind::Retyped {
+            old: describe(self.old.get(old)),
+            new: describe(self.new.get(new)),
+        };
+        self.push(path, kind, compatibility);
+    }
+    fn check(&mut self, path: &str, old: SchemaId, new: SchemaId) {
+        if !self.in_progress.insert((old, new)) {
+            return;
+        }
+        self.check_schema(path, old, new);
+        self.in_progress.remove(&(old, new));
+    }
+    fn check_schema(&mut self, path: &str, old: SchemaId, new: SchemaId) {
+        let (old_map, new_map) = (self.old, self.new);
+        match (&old_map.get(old).schema, &new_map.get(new).schema) {
+            (Schema::Unsigned(x), Schema::Unsigned(y))
+            | (Schema::Signed(x), Schema::Signed(y))
+            | (Schema::Float(x), Schema::Float(y)) => {
+                if x < y {
+                    let kind = ChangeKind::Widened { old: *x, new: *y };
+                    self.push(path, kind, Compatibility::Backward);
+                } else if x > y {
+                    let kind = ChangeKind::Narrowed { old: *x, new: *y };
+                    self.push(path, kind, Compatibility::Forward);
+                }
+            }
+            (Schema::Bool, Schema::Bool)
+            | (Schema::Char, Schema::Char)
+            | (Schema::String, Schema::String)
+            | (Schema::Bytes, Schema::Bytes)
+            | (Schema::Unit, Schema::Unit)
+            | (Schema::UnitStruct { .. }, Schema::UnitStruct { .. }) => {}
+            (Schema::Option(x), Schema::Option(y)) | (Schema::Vec(x), Schema::Vec(y)) => {
+                self.check(path, *x, *y)
+            }
+            (Schema::NewtypeStruct { value: x, .. }, Schema::NewtypeStruct { value: y, .. }) => {
+                self.check(path, *x, *y)
+            }
+            (_, Schema::Option(y)) => {
+                self.retyped(path, old, new, Compatibility::Backward);
+                self.check(path, old, *y);
+            }
+            (Schema::Map(k1, v1), Schema::Map(k2, v2)) => {
+                self.check(&join(path, "key"), *k1, *k2);
+                self.check(&join(path, "value"), *v1, *v2);
+            }
+            (Schema::Tuple(x), Schema::Tuple(y))
+            | (Schema::TupleStruct { fields: x, .. }, Schema::TupleStruct { fields: y, .. }) => {
+                self.check_tuple(path, x, y)
+            }
+            (Schema::Struct { fields: x, .. }, Schema::Struct { fields: y, .. }) => {
+                self.check_struct(path, x, y)
+            }
+            (Schema::Enum { enums: x }, Schema::Enum { enums: y }) => self.check_enum(path, x, y),
+            _ => self.retyped(path, old, new, Compatibility::Breaking),
+        }
+    }
+    fn check_tuple(&mut self, path: &str, old: &[SchemaId], new: &[SchemaId]) {
+        for (index, (x, y)) in old.iter().zip(new.iter()).enumerate() {
+            self.check(&join(path, &index.to_string()), *x, *y);
+        }
+        for index in new.len()..old.len() {
+            let name = index.to_string();
+            let kind = ChangeKind::FieldRemoved { name: name.clone() };
+            self.push(&join(path, &name), kind, Compatibility::Backward);
+        }
+        for index in old.len()..new.len() {
+            let name = index.to_string();
+            let kind = ChangeKind::FieldAdded { name: name.clone() };
+            self.push(&join(path, &name), kind, Compatibility::Forward);
+        }
+    }
+    fn check_struct(&mut self, path: &str, old: &[(String, SchemaId)], new: &[(String, SchemaId)]) {
+        for (name, x) in old {
+            let field = join(path, name);
+            if let Some((_, y)) = new.iter().find(|(n, _)| n == name) {
+                self.check(&field, *x, *y);
+            } else {
+                let compatibility = Compatibility::new(true, Self::is_option(self.old, *x));
+                let kind = ChangeKind::FieldRemoved { name: name.clone() };
+                self.push(&field, kind, compatibility);
+            }
+        }
+        for (name, y) in new {
+            if !old.iter().any(|(n, _)| n == name) {
+                let compatibility = Compatibility::new(Self::is_option(self.new, *y), true);
+                let kind = ChangeKind::FieldAdded { name: name.clone() };
+                self.push(&join(path, name), kind, compatibility);
+            }
+        }
+    }
+    fn check_enum(
+        &mut self,
+        path: &str,
+        old: &[(String, VariantSchema)],
+        new: &[(String, VariantSchema)],
+    ) {
+        let mut renamed = HashSet::new();
+        for (index, (name, x)) in old.iter().enumerate() {
+            let variant = join(path, name);
+            if let Some((_, y)) = new.iter().find(|(n, _)| n == name) {
+                self.check_variant(&variant, x, y);
+            } else if let Some((other, _)) = new
+                .get(index)
+                .filter(|(n, y)| !old.iter().any(|(o, _)| o == n) && self.same_shape(x, y))
+            {
+                renamed.insert(other.as_str());
+                let kind = ChangeKind::VariantRenamed {
+                    old: name.clone(),
+                    new: other.clone(),
+                };
+                self.push(&variant, kind, Compatibility::Breaking);
+            } else {
+                let kind = ChangeKind::VariantRemoved { name: name.clone() };
+                self.push(&variant, kind, Compatibility::Forward);
+            }
+        }
+        for (name, _) in new {
+            if !old.iter().any(|(n, _)| n == name) && !renamed.contains(name.as_str()) {
+                let kind = ChangeKind::VariantAdded { name: name.clone() };
+                self.push(&join(path, name), kind, Compatibility::Backward);
+            }
+        }
+    }
+    fn same_shape(&self, old: &VariantSchema, new: &VariantSchema) -> bool {
+        match (old, new) {
+            (VariantSchema::Unit, VariantSchema::Unit) => true,
+            (VariantSchema::Newtype(_), VariantSchema::Newtype(_)) => true,
+            (VariantSchema::Tuple(x), VariantSchema::Tuple(y)) => x.len() == y.len(),
+            (VariantSchema::Struct(x), VariantSchema::Struct(y)) => {
+                x.len() == y.len() && x.iter().zip(y.iter()).all(|((a, _), (b, _))| a == b)
+            }
+            _ => false,
+        }
+    }
+    fn check_variant(&mut self, path: &str, old: &VariantSchema, new: &VariantSchema) {
+        match (old, new) {
+            (VariantSchema::Unit, VariantSchema::Unit) => {}
+            (VariantSchema::Newtype(x), VariantSchema::Newtype(y)) => self.check(path, *x, *y),
+            (VariantSchema::Tuple(x), VariantSchema::Tuple(y)) => self.check_tuple(path, x, y),
+            (VariantSchema::Struct(x), VariantSchema::Struct(y)) => self.check_struct(path, x, y),
+            _ => {
+                let kind = ChangeKind::Retyped {
+                    old: variant_kind(old).to_string(),
+                    new: variant_kind(new).to_string(),
+                };
+                self.push(path, kind, Compatibility::Breaking);
+            }
+        }
+    }
+}
+
+fn variant_kind(variant: &VariantSchema) -> &'static str {
+    match variant {
+        VariantSchema::Unit => "unit variant",
+        VariantSchema::Newtype(_) => "newtype variant",
+        VariantSchema::Tuple(_) => "tuple variant",
+        VariantSchema::Struct(_) => "struct variant",
+    }
+}
+
+impl Display for Compatibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compatibility::Full => write!(f, "compatible"),
+            Compatibility::Backward => write!(f, "backward-compatible"),
+            Compatibility::Forward => write!(f, "forward-compatible"),
+            Compatibility::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::FieldAdded { name } => write!(f, "field {} added", name),
+            ChangeKind::FieldRemoved { name } => write!(f, "field {} removed", name),
+            ChangeKind::Retyped { old, new } => write!(f, "retyped from {} to {}", old, new),
+            ChangeKind::VariantAdded { name } => write!(f, "variant {} added", name),
+            ChangeKind::VariantRemoved { name } => write!(f, "variant {} removed", name),
+            ChangeKind::VariantRenamed { old, new } => {
+                write!(f, "variant {} renamed to {}", old, new)
+            }
+            ChangeKind::Widened { old, new } => write!(f, "widened from {} to {} bits", old, new),
+            ChangeKind::Narrowed { old, new } => {
+                write!(f, "narrowed from {} to {} bits", old, new)
+            }
+        }
+    }
+}
+
+impl Display for CompatReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(
+                f,
+                "{}: {} ({})",
+                change.path, change.kind, change.compatibility
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_compatibility() {
+    use crate::builder::SchemaMapBuilder;
+
+    mod old {
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        pub enum Kind {
+            Empty,
+            Count(u8),
+            Old(bool),
+            Dropped,
+        }
+
+        #[derive(Deserialize)]
+        pub struct Message {
+            pub id: u32,
+            pub removed: String,
+            pub optional: Option<u8>,
+            pub retyped: bool,
+            pub kinds: Vec<Kind>,
+        }
+    }
+
+    mod new {
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        pub enum Kind {
+            Empty,
+            Count(u16),
+            New(bool),
+            Added(u8),
+        }
+
+        #[derive(Deserialize)]
+        pub struct Message {
+            pub id: u16,
+            pub retyped: String,
+            pub kinds: Vec<Kind>,
+            pub added: Option<u8>,
+            pub required: u8,
+        }
+    }
+
+    let mut builder = SchemaMapBuilder::new();
+    let old_id = builder.add::<old::Message>().unwrap();
+    let old_map = builder.build();
+    let mut builder = SchemaMapBuilder::new();
+    let new_id = builder.add::<new::Message>().unwrap();
+    let new_map = builder.build();
+
+    let report = check_compatibility(&old_map, old_id, &new_map, new_id);
+    let change = |path: &str, kind, compatibility| Change {
+        path: path.to_string(),
+        kind,
+        compatibility,
+    };
+    assert_eq!(
+        report.changes,
+        vec![
+            change(
+                "added",
+                ChangeKind::FieldAdded {
+                    name: "added".to_string()
+                },
+                Compatibility::Full
+            ),
+            change(
+                "id",
+                ChangeKind::Narrowed { old: 32, new: 16 },
+                Compatibility::Forward
+            ),
+            change(
+                "kinds.Added",
+                ChangeKind::VariantAdded {
+                    name: "Added".to_string()
+                },
+                Compatibility::Backward
+            ),
+            change(
+                "kinds.Count",
+                ChangeKind::Widened { old: 8, new: 16 },
+                Compatibility::Backward
+            ),
+            change(
+                "kinds.Dropped",
+                ChangeKind::VariantRemoved {
+                    name: "Dropped".to_string()
+                },
+                Compatibility::Forward
+            ),
+            change(
+                "kinds.Old",
+                ChangeKind::VariantRenamed {
+                    old: "Old".to_string(),
+                    new: "New".to_string()
+                },
+                Compatibility::Breaking
+            ),
+            change(
+                "optional",
+                ChangeKind::FieldRemoved {
+                    name: "optional".to_string()
+                },
+                Compatibility::Full
+            ),
+            change(
+                "removed",
+                ChangeKind::FieldRemoved {
+                    name: "removed".to_string()
+                },
+                Compatibility::Backward
+            ),
+            change(
+                "required",
+                ChangeKind::FieldAdded {
+                    name: "required".to_string()
+                },
+                Compatibility::Forward
+            ),
+            change(
+                "retyped",
+                ChangeKind::Retyped {
+                    old: "bool".to_string(),
+                    new: "alloc::string::String".to_string()
+                },
+                Compatibility::Breaking
+            ),
+        ]
+    );
+    assert_eq!(report.compatibility(), Compatibility::Breaking);
+}
+
+#[test]
+fn test_shared_subtype() {
+    use crate::builder::SchemaMapBuilder;
+
+    mod old {
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        pub struct Point {
+            pub x: u8,
+        }
+
+        #[derive(Deserialize)]
+        pub struct Line {
+            pub from: Point,
+            pub to: Point,
+        }
+    }
+
+    mod new {
+        use serde::Deserialize;
+
+        #[derive(Deserialize)]
+        pub struct Point {
+            pub x: u16,
+        }
+
+        #[derive(Deserialize)]
+        pub struct Line {
+            pub from: Point,
+            pub to: Point,
+        }
+    }
+
+    let mut builder = SchemaMapBuilder::new();
+    let old_id = builder.add::<old::Line>().unwrap();
+    let old_map = builder.build();
+    let mut builder = SchemaMapBuilder::new();
+    let new_id = builder.add::<new::Line>().unwrap();
+    let new_map = builder.build();
+
+    let report = check_compatibility(&old_map, old_id, &new_map, new_id);
+    let widened = |path: &str| Change {
+        path: path.to_string(),
+        kind: ChangeKind::Widened { old: 8, new: 16 },
+        compatibility: Compatibility::Backward,
+    };
+    assert_eq!(report.changes, vec![widened("from.x"), widened("to.x")]);
+}
@@ -0,0 +1,654 @@
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+use std::slice;
+
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer, StrDeserializer};
+use serde::de::{
+    DeserializeSeed, EnumAccess, Error, IgnoredAny, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::{Deserialize, Deserializer};
+
+use crate::{Schema, SchemaId, SchemaMap, VariantSchema};
+
+/// A [`Deserializer`] that reads a value in the shape recorded by a writer's [`SchemaMap`] and
+/// presents it to the reader as self-describing data.
+///
+/// The `inner` deserializer must be for a non-self-describing format such as
+/// `typetag_static::binary`. Because the writer's schema is known, the reader's type may differ
+/// from the writer's: struct fields the reader no longer has are skipped, missing `Option` fields
+/// default to `None`, and enum variants are matched by name rather than by index.
+///
+/// Structs are read with [`Deserializer::deserialize_tuple`] and struct variants with
+/// [`VariantAccess::tuple_variant`] because the writer's field names are not `'static`. Type
+/// names and variant lists passed to `inner` are empty.
+pub struct SchemaDeserializer<'s, D> {
+    map: &'s SchemaMap,
+    id: SchemaId,
+    inner: D,
+}
+
+struct SchemaSeed<'s, T> {
+    map: &'s SchemaMap,
+    id: SchemaId,
+    seed: T,
+}
+
+enum SeqIds<'s> {
+    Repeat(SchemaId),
+    List(slice::Iter<'s, SchemaId>),
+}
+
+struct SchemaSeqAccess<'s, A> {
+    map: &'s SchemaMap,
+    ids: SeqIds<'s>,
+    seq: A,
+}
+
+struct SchemaMapAccess<'s, A> {
+    map: &'s SchemaMap,
+    key: SchemaId,
+    value: SchemaId,
+    inner: A,
+}
+
+struct SchemaStructAccess<'s, A> {
+    map: &'s SchemaMap,
+    fields: slice::Iter<'s, (String, SchemaId)>,
+    value: Option<SchemaId>,
+    seq: A,
+}
+
+struct SchemaEnumAccess<'s, A> {
+    map: &'s SchemaMap,
+    name: &'s str,
+    schema: &'s VariantSchema,
+    variant: A,
+}
+
+struct OptionVisitor<'s, V> {
+    map: &'s SchemaMap,
+    id: SchemaId,
+    visitor: V,
+}
+
+struct NewtypeVisitor<'s, V> {
+    map: &'s SchemaMap,
+    id: SchemaId,
+    visitor: V,
+    transparent: bool,
+}
+
+struct SeqVisitor<'s, V> {
+    map: &'s SchemaMap,
+    ids: SeqIds<'s>,
+    visitor: V,
+}
+
+struct MapVisitor<'s, V> {
+    map: &'s SchemaMap,
+    key: SchemaId,
+    value: SchemaId,
+    visitor: V,
+}
+
+struct StructVisitor<'s, V> {
+    map: &'s SchemaMap,
+    fields: &'s [(String, SchemaId)],
+    visitor: V,
+}
+
+struct EnumVisitor<'s, V> {
+    map: &'s SchemaMap,
+    enums: &'s [(String, VariantSchema)],
+    visitor: V,
+}
+
+impl<'s, D> SchemaDeserializer<'s, D> {
+    pub fn new(map: &'s SchemaMap, id: SchemaId, inner: D) -> Self {
+        SchemaDeserializer { map, id, inner }
+    }
+}
+
+/// Deserialize a `T` from `inner`, which was written with the schema `id` in `map`.
+pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+    map: &SchemaMap,
+    id: SchemaId,
+    inner: D,
+) -> Result<T, D::Error> {
+    T::deserialize(SchemaDeserializer::new(map, id, inner))
+}
+
+fn lookup<E: Error>(map: &SchemaMap, id: SchemaId) -> Result<&Schema, E> {
+    map.map
+        .get(&id)
+        .map(|x| &x.schema)
+        .ok_or_else(|| E::custom(format_args!("missing schema for {:?}", id)))
+}
+
+fn drain_seq<'de, A: SeqAccess<'de>>(mut seq: A) -> Result<(), A::Error> {
+    while let Some(IgnoredAny) = seq.next_element()? {}
+    Ok(())
+}
+
+fn drain_map<'de, A: MapAccess<'de>>(mut map: A) -> Result<(), A::Error> {
+    while let Some((IgnoredAny, IgnoredAny)) = map.next_entry()? {}
+    Ok(())
+}
+
+impl<'de, 's, T: DeserializeSeed<'de>> DeserializeSeed<'de> for SchemaSeed<'s, T> {
+    type Value = T::Value;
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.seed
+            .deserialize(SchemaDeserializer::new(self.map, self.id, deserializer))
+    }
+}
+
+impl<'de, 's, D: Deserializer<'de>> Deserializer<'de> for SchemaDeserializer<'s, D> {
+    type Error = D::Error;
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let map = self.map;
+        match lookup(map, self.id)? {
+            Schema::Bool => self.inner.deserialize_bool(visitor),
+            Schema::Unsigned(8) => self.inner.deserialize_u8(visitor),
+            Schema::Unsigned(16) => self.inner.deserialize_u16(visitor),
+            Schema::Unsigned(32) => self.inner.deserialize_u32(visitor),
+            Schema::Unsigned(64) => self.inner.deserialize_u64(visitor),
+            Schema::Unsigned(128) => self.inner.deserialize_u128(visitor),
+            Schema::Signed(8) => self.inner.deserialize_i8(visitor),
+            Schema::Signed(16) => self.inner.deserialize_i16(visitor),
+            Schema::Signed(32) => self.inner.deserialize_i32(visitor),
+            Schema::Signed(64) => self.inner.deserialize_i64(visitor),
+            Schema::Signed(128) => self.inner.deserialize_i128(visitor),
+            Schema::Float(32) => self.inner.deserialize_f32(visitor),
+            Schema::Float(64) => self.inner.deserialize_f64(visitor),
+            schema @ (Schema::Unsigned(_) | Schema::Signed(_) | Schema::Float(_)) => Err(
+                D::Error::custom(format_args!("unsupported width in {:?}", schema)),
+            ),
+            Schema::Char => self.inner.deserialize_char(visitor),
+            Schema::String => self.inner.deserialize_string(visitor),
+            Schema::Bytes => self.inner.deserialize_byte_buf(visitor),
+            Schema::Option(id) => self.inner.deserialize_option(OptionVisitor {
+                map,
+                id: *id,
+                visitor,
+            }),
+            Schema::Unit => self.inner.deserialize_unit(visitor),
+            Schema::UnitStruct { .. } => self.inner.deserialize_unit_struct("", visitor),
+            Schema::NewtypeStruct { value, .. } => self.inner.deserialize_newtype_struct(
+                "",
+                NewtypeVisitor {
+                    map,
+                    id: *value,
+                    visitor,
+                    transparent: true,
+                },
+            ),
+            Schema::Enum { enums } => self.inner.deserialize_enum(
+                "",
+                &[],
+                EnumVisitor {
+                    map,
+                    enums,
+                    visitor,
+                },
+            ),
+            Schema::Vec(id) => self.inner.deserialize_seq(SeqVisitor {
+                map,
+                ids: SeqIds::Repeat(*id),
+                visitor,
+            }),
+            Schema::Map(key, value) => self.inner.deserialize_map(MapVisitor {
+                map,
+                key: *key,
+                value: *value,
+                visitor,
+            }),
+            Schema::Tuple(ids) => self.inner.deserialize_tuple(
+                ids.len(),
+                SeqVisitor {
+                    map,
+                    ids: SeqIds::List(ids.iter()),
+                    visitor,
+                },
+            ),
+            Schema::TupleStruct { fields, .. } => self.inner.deserialize_tuple_struct(
+                "",
+                fields.len(),
+                SeqVisitor {
+                    map,
+                    ids: SeqIds::List(fields.iter()),
+                    visitor,
+                },
+            ),
+            Schema::Struct { fields, .. } => self.inner.deserialize_tuple(
+                fields.len(),
+                StructVisitor {
+                    map,
+                    fields,
+                    visitor,
+                },
+            ),
+        }
+    }
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match lookup(self.map, self.id)? {
+            Schema::Option(_) => self.deserialize_any(visitor),
+            _ => visitor.visit_some(self),
+        }
+    }
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match lookup(self.map, self.id)? {
+            Schema::NewtypeStruct { value, .. } => self.inner.deserialize_newtype_struct(
+                "",
+                NewtypeVisitor {
+                    map: self.map,
+                    id: *value,
+                    visitor,
+                    transparent: false,
+                },
+            ),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(IgnoredAny)?;
+        visitor.visit_unit()
+    }
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct enum identifier
+    }
+}
+
+impl<'de, 's, A: SeqAccess<'de>> SeqAccess<'de> for SchemaSeqAccess<'s, A> {
+    type Error = A::Error;
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let id = match &mut self.ids {
+            SeqIds::Repeat(id) => *id,
+            SeqIds::List(ids) => match ids.next() {
+                Some(id) => *id,
+                None => return Ok(None),
+            },
+        };
+        self.seq.next_element_seed(SchemaSeed {
+            map: self.map,
+            id,
+            seed,
+        })
+    }
+    fn size_hint(&self) -> Option<usize> {
+        match &self.ids {
+            SeqIds::Repeat(_) => self.seq.size_hint(),
+            SeqIds::List(ids) => Some(ids.len()),
+        }
+    }
+}
+
+impl<'de, 's, A: MapAccess<'de>> MapAccess<'de> for SchemaMapAccess<'s, A> {
+    type Error = A::Error;
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.inner.next_key_seed(SchemaSeed {
+            map: self.map,
+            id: self.key,
+            seed,
+        })
+    }
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.inner.next_value_seed(SchemaSeed {
+            map: self.map,
+            id: self.value,
+            seed,
+        })
+    }
+    fn size_hint(&self) -> Option<usize> { self.inner.size_hint() }
+}
+
+impl<'de, 's, A: SeqAccess<'de>> MapAccess<'de> for SchemaStructAccess<'s, A> {
+    type Error = A::Error;
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if let Some((name, id)) = self.fields.next() {
+            self.value = Some(*id);
+            let name: StrDeserializer<Self::Error> = name.as_str().into_deserializer();
+            Ok(Some(seed.deserialize(name)?))
+        } else {
+            Ok(None)
+        }
+    }
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let id = self
+            .value
+            .take()
+            .ok_or_else(|| A::Error::custom("next_value_seed called before next_key_seed"))?;
+        self.seq
+            .next_element_seed(SchemaSeed {
+                map: self.map,
+                id,
+                seed,
+            })?
+            .ok_or_else(|| A::Error::custom("struct ended before all fields were read"))
+    }
+    fn size_hint(&self) -> Option<usize> { Some(self.fields.len()) }
+}
+
+impl<'de, 's, A: VariantAccess<'de>> EnumAccess<'de> for SchemaEnumAccess<'s, A> {
+    type Error = A::Error;
+    type Variant = Self;
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let name: StrDeserializer<Self::Error> = self.name.into_deserializer();
+        Ok((seed.deserialize(name)?, self))
+    }
+}
+
+impl<'de, 's, A: VariantAccess<'de>> VariantAccess<'de> for SchemaEnumAccess<'s, A> {
+    type Error = A::Error;
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.schema {
+            VariantSchema::Unit => self.variant.unit_variant(),
+            _ => self
+                .newtype_variant_seed(PhantomData::<IgnoredAny>)
+                .map(|_| ()),
+        }
+    }
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let map = self.map;
+        match self.schema {
+            VariantSchema::Newtype(id) => {
+                self.variant
+                    .newtype_variant_seed(SchemaSeed { map, id: *id, seed })
+            }
+            VariantSchema::Tuple(ids) => self.variant.tuple_variant(
+                ids.len(),
+                SeqVisitor {
+                    map,
+                    ids: SeqIds::List(ids.iter()),
+                    visitor: SeedVisitor(seed),
+                },
+            ),
+            VariantSchema::Struct(fields) => self.variant.tuple_variant(
+                fields.len(),
+                StructVisitor {
+                    map,
+                    fields,
+                    visitor: SeedVisitor(seed),
+                },
+            ),
+            VariantSchema::Unit => Err(A::Error::custom(format_args!(
+                "expected newtype variant, {} is a unit variant",
+                self.name
+            ))),
+        }
+    }
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.struct_variant(&[], visitor)
+    }
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let map = self.map;
+        match self.schema {
+            VariantSchema::Tuple(ids) => self.variant.tuple_variant(
+                ids.len(),
+                SeqVisitor {
+                    map,
+                    ids: SeqIds::List(ids.iter()),
+                    visitor,
+                },
+            ),
+            VariantSchema::Struct(fields) => self.variant.tuple_variant(
+                fields.len(),
+                StructVisitor {
+                    map,
+                    fields,
+                    visitor,
+                },
+            ),
+            VariantSchema::Unit | VariantSchema::Newtype(_) => Err(A::Error::custom(format_args!(
+                "expected tuple or struct variant for {}",
+                self.name
+            ))),
+        }
+    }
+}
+
+/// Adapts a [`DeserializeSeed`] into a [`Visitor`] so a newtype variant in the reader can accept a
+/// tuple or struct variant from the writer.
+struct SeedVisitor<T>(T);
+
+impl<'de, T: DeserializeSeed<'de>> Visitor<'de> for SeedVisitor<T> {
+    type Value = T::Value;
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result { write!(f, "a variant") }
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.0.deserialize(SeqAccessDeserializer::new(seq))
+    }
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.0.deserialize(MapAccessDeserializer::new(map))
+    }
+}
+
+impl<'de, 's, V: Visitor<'de>> Visitor<'de> for OptionVisitor<'s, V> {
+    type Value = V::Value;
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result { self.visitor.expecting(f) }
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> { self.visitor.visit_none() }
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        self.visitor
+            .visit_some(SchemaDeserializer::new(self.map, self.id, d))
+    }
+}
+
+impl<'de, 's, V: Visitor<'de>> Visitor<'de> for NewtypeVisitor<'s, V> {
+    type Value = V::Value;
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result { self.visitor.expecting(f) }
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        let d = SchemaDeserializer::new(self.map, self.id, d);
+        if self.transparent {
+            d.deserialize_any(self.visitor)
+        } else {
+            self.visitor.visit_newtype_struct(d)
+        }
+    }
+}
+
+impl<'de, 's, V: Visitor<'de>> Visitor<'de> for SeqVisitor<'s, V> {
+    type Value = V::Value;
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result { self.visitor.expecting(f) }
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        let mut access = SchemaSeqAccess {
+            map: self.map,
+            ids: self.ids,
+            seq,
+        };
+        let result = self.visitor.visit_seq(&mut access)?;
+        drain_seq(access)?;
+        Ok(result)
+    }
+}
+
+impl<'de, 's, V: Visitor<'de>> Visitor<'de> for MapVisitor<'s, V> {
+    type Value = V::Value;
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result { self.visitor.expecting(f) }
+    fn visit_map<A: MapAccess<'de>>(self, inner: A) -> Result<Self::Value, A::Error> {
+        let mut access = SchemaMapAccess {
+            map: self.map,
+            key: self.key,
+            value: self.value,
+            inner,
+        };
+        let result = self.visitor.visit_map(&mut access)?;
+        drain_map(access)?;
+        Ok(result)
+    }
+}
+
+impl<'de, 's, V: Visitor<'de>> Visitor<'de> for StructVisitor<'s, V> {
+    type Value = V::Value;
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result { self.visitor.expecting(f) }
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        let mut access = SchemaStructAccess {
+            map: self.map,
+            fields: self.fields.iter(),
+            value: None,
+            seq,
+        };
+        let result = self.visitor.visit_map(&mut access)?;
+        drain_map(access)?;
+        Ok(result)
+    }
+}
+
+impl<'de, 's, V: Visitor<'de>> Visitor<'de> for EnumVisitor<'s, V> {
+    type Value = V::Value;
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result { self.visitor.expecting(f) }
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (index, variant) = data.variant_seed(PhantomData::<u32>)?;
+        let (name, schema) = self
+            .enums
+            .get(index as usize)
+            .ok_or_else(|| A::Error::custom(format_args!("unknown variant index {}", index)))?;
+        self.visitor.visit_enum(SchemaEnumAccess {
+            map: self.map,
+            name,
+            schema,
+            variant,
+        })
+    }
+}
+
+#[test]
+fn test_evolution() {
+    use bincode::Options;
+
+    use crate::builder::SchemaMapBuilder;
+
+    mod old {
+        use std::collections::HashMap;
+
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize)]
+        pub enum Kind {
+            Empty,
+            Count(u8),
+            Pair(u16, u16),
+            Named { x: bool },
+        }
+
+        #[derive(Serialize, Deserialize)]
+        pub struct Message {
+            pub id: u32,
+            pub removed: String,
+            pub kinds: Vec<Kind>,
+            pub tuple: (u8, bool),
+            pub lookup: HashMap<u8, String>,
+        }
+    }
+
+    mod new {
+        use std::collections::HashMap;
+
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+        pub enum Kind {
+            Named { x: bool },
+            Added,
+            Pair(u16, u16),
+            Count(u64),
+            Empty,
+        }
+
+        #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+        pub struct Message {
+            pub kinds: Vec<Kind>,
+            pub id: u64,
+            pub added: Option<String>,
+            pub tuple: (u8,),
+            pub lookup: HashMap<u8, String>,
+        }
+    }
+
+    let mut builder = SchemaMapBuilder::new();
+    let id = builder.add::<old::Message>().unwrap();
+    let map = builder.build();
+    let options = bincode::DefaultOptions::new().with_fixint_encoding();
+    let bytes = options
+        .serialize(&old::Message {
+            id: 7,
+            removed: "gone".to_string(),
+            kinds: vec![
+                old::Kind::Empty,
+                old::Kind::Count(3),
+                old::Kind::Pair(4, 5),
+                old::Kind::Named { x: true },
+            ],
+            tuple: (9, true),
+            lookup: vec![(1, "one".to_string())].into_iter().collect(),
+        })
+        .unwrap();
+    let mut inner = bincode::Deserializer::from_slice(&bytes, options);
+    let actual: new::Message = deserialize(&map, id, &mut inner).unwrap();
+    assert_eq!(
+        actual,
+        new::Message {
+            kinds: vec![
+                new::Kind::Empty,
+                new::Kind::Count(3),
+                new::Kind::Pair(4, 5),
+                new::Kind::Named { x: true },
+            ],
+            id: 7,
+            added: None,
+            tuple: (9,),
+            lookup: vec![(1, "one".to_string())].into_iter().collect(),
+        }
+    );
+}
@@ -0,0 +1,405 @@
+use std::fmt::{Display, Formatter};
+
+use crate::error::{Error, Path, PathSegment};
+use crate::print::rust_name;
+use crate::{Schema, SchemaId, SchemaMap, VariantSchema};
+
+/// A value decoded by [`dump`], annotated with the names and widths from its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DumpValue {
+    Bool(bool),
+    /// An unsigned integer and its width in bits.
+    Unsigned(usize, u128),
+    /// A signed integer and its width in bits.
+    Signed(usize, i128),
+    /// A float and its width in bits.
+    Float(usize, f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    Option(Option<Box<DumpValue>>),
+    Unit,
+    Seq(Vec<DumpValue>),
+    Map(Vec<(DumpValue, DumpValue)>),
+    Tuple(Vec<DumpValue>),
+    Struct {
+        name: String,
+        fields: DumpFields,
+    },
+    Variant {
+        name: String,
+        variant: String,
+        fields: DumpFields,
+    },
+}
+
+/// The contents of a struct or enum variant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DumpFields {
+    Unit,
+    Newtype(Box<DumpValue>),
+    Tuple(Vec<DumpValue>),
+    Named(Vec<(String, DumpValue)>),
+}
+
+/// Decode `bytes`, written by `typetag_static::binary::serialize` for the type `id`, without
+/// access to the original Rust type.
+///
+/// The result prints as a Rust-like expression with integer and float literals suffixed by their
+/// width, e.g. `Error { code: 503u32, fatal: false }`.
+pub fn dump(map: &SchemaMap, id: SchemaId, bytes: &[u8]) -> Result<DumpValue, Error> {
+    let mut reader = Reader { map, bytes };
+    let value = reader
+        .value(id)
+        .and_then(|value| match reader.bytes.len() {
+            0 => Ok(value),
+            n => malformed(format!("{} trailing bytes", n)),
+        })
+        .map_err(|e| e.within(PathSegment::Type(rust_name(&map.get(id).name))))?;
+    Ok(value)
+}
+
+fn malformed<T>(message: String) -> Result<T, Error> {
+    Err(Error::Malformed {
+        path: Path::default(),
+        message,
+    })
+}
+
+/// The most elements that take no bytes, such as `()`, that a sequence or map may hold.
+const MAX_EMPTY_ELEMENTS: usize = 1 << 16;
+
+struct Reader<'a> {
+    map: &'a SchemaMap,
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < len {
+            return malformed("unexpected end of input".to_string());
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+    fn integer(&mut self, bits: usize) -> Result<u128, Error> {
+        let mut buf = [0u8; 16];
+        buf[..bits / 8].copy_from_slice(self.take(bits / 8)?);
+        Ok(u128::from_le_bytes(buf))
+    }
+    fn length(&mut self) -> Result<usize, Error> {
+        let len = self.integer(64)?;
+        match usize::try_from(len) {
+            Ok(len) => Ok(len),
+            Err(_) => malformed(format!("length {} is too large", len)),
+        }
+    }
+    fn byte_buf(&mut self) -> Result<Vec<u8>, Error> {
+        let len = self.length()?;
+        Ok(self.take(len)?.to_vec())
+    }
+    fn value(&mut self, id: SchemaId) -> Result<DumpValue, Error> {
+        let map = self.map;
+        let typ = map.get(id);
+        Ok(match &typ.schema {
+            Schema::Bool => match self.take(1)?[0] {
+                0 => DumpValue::Bool(false),
+                1 => DumpValue::Bool(true),
+                x => return malformed(format!("invalid bool {}", x)),
+            },
+            Schema::Unsigned(bits) => DumpValue::Unsigned(*bits, self.integer(*bits)?),
+            Schema::Signed(bits) => {
+                let shift = 128 - bits;
+                DumpValue::Signed(*bits, ((self.integer(*bits)? << shift) as i128) >> shift)
+            }
+            Schema::Float(32) => {
+                DumpValue::Float(32, f32::from_bits(self.integer(32)? as u32) as f64)
+            }
+            Schema::Float(bits) => {
+                DumpValue::Float(*bits, f64::from_bits(self.integer(64)? as u64))
+            }
+            Schema::Char => {
+                let x = self.integer(32)? as u32;
+                match char::from_u32(x) {
+                    Some(c) => DumpValue::Char(c),
+                    None => return malformed(format!("invalid char {:#x}", x)),
+                }
+            }
+            Schema::String => match String::from_utf8(self.byte_buf()?) {
+                Ok(x) => DumpValue::String(x),
+                Err(e) => return malformed(e.to_string()),
+            },
+            Schema::Bytes => DumpValue::Bytes(self.byte_buf()?),
+            Schema::Option(x) => match self.take(1)?[0] {
+                0 => DumpValue::Option(None),
+                1 => DumpValue::Option(Some(Box::new(self.value(*x)?))),
+                x => return malformed(format!("invalid option tag {}", x)),
+            },
+            Schema::Unit => DumpValue::Unit,
+            Schema::UnitStruct { name } => DumpValue::Struct {
+                name: name.clone(),
+                fields: DumpFields::Unit,
+            },
+            Schema::NewtypeStruct { name, value } => DumpValue::Struct {
+                name: name.clone(),
+                fields: DumpFields::Newtype(Box::new(self.value(*value)?)),
+            },
+            Schema::Vec(x) => DumpValue::Seq(self.elements(|reader| reader.value(*x))?),
+            Schema::Map(k, v) => {
+                DumpValue::Map(self.elements(|reader| Ok((reader.value(*k)?, reader.value(*v)?)))?)
+            }
+            Schema::Tuple(fields) => DumpValue::Tuple(self.tuple(fields)?),
+            Schema::TupleStruct { name, fields } => DumpValue::Struct {
+                name: name.clone(),
+                fields: DumpFields::Tuple(self.tuple(fields)?),
+            },
+            Schema::Struct { name, fields } => DumpValue::Struct {
+                name: name.clone(),
+                fields: DumpFields::Named(self.named(fields)?),
+            },
+            Schema::Enum { enums } => {
+                let index = self.integer(32)?;
+                let (variant, schema) = match enums.get(index as usize) {
+                    Some(x) => x,
+                    None => return malformed(format!("unknown variant index {}", index)),
+                };
+                let fields = match schema {
+                    VariantSchema::Unit => Ok(DumpFields::Unit),
+                    VariantSchema::Newtype(x) => {
+                        self.value(*x).map(|x| DumpFields::Newtype(Box::new(x)))
+                    }
+                    VariantSchema::Tuple(x) => self.tuple(x).map(DumpFields::Tuple),
+                    VariantSchema::Struct(x) => self.named(x).map(DumpFields::Named),
+                }
+                .map_err(|e| e.within(PathSegment::Variant(variant.clone())))?;
+                DumpValue::Variant {
+                    name: rust_name(&typ.name),
+                    variant: variant.clone(),
+                    fields,
+                }
+            }
+        })
+    }
+    /// Read a length and then that many elements with `element`.
+    fn elements<T>(
+        &mut self,
+        mut element: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let len = self.length()?;
+        let mut result = Vec::with_capacity(len.min(self.bytes.len()));
+        for index in 0..len {
+            let remaining = self.bytes.len();
+            result.push(element(self).map_err(|e| e.within(PathSegment::Index(index)))?);
+            // Elements that take no bytes, such as `()`, never run out of input, so a corrupt
+            // length would fill memory with them.
+            if self.bytes.len() == remaining && len > MAX_EMPTY_ELEMENTS {
+                return malformed(format!("length {} is too large for empty elements", len));
+            }
+        }
+        Ok(result)
+    }
+    fn tuple(&mut self, fields: &[SchemaId]) -> Result<Vec<DumpValue>, Error> {
+        let mut result = Vec::with_capacity(fields.len());
+        for (index, id) in fields.iter().enumerate() {
+            result.push(
+                self.value(*id)
+                    .map_err(|e| e.within(PathSegment::Index(index)))?,
+            );
+        }
+        Ok(result)
+    }
+    fn named(&mut self, fields: &[(String, SchemaId)]) -> Result<Vec<(String, DumpValue)>, Error> {
+        let mut result = Vec::with_capacity(fields.len());
+        for (name, id) in fields {
+            let value = self
+                .value(*id)
+                .map_err(|e| e.within(PathSegment::Field(name.clone())))?;
+            result.push((name.clone(), value));
+        }
+        Ok(result)
+    }
+}
+
+fn indent(f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+    write!(f, "{:1$}", "", depth * 4)
+}
+
+/// Write `items` between `open` and `close`, one per line.
+fn block<T>(
+    f: &mut Formatter<'_>,
+    depth: usize,
+    open: &str,
+    close: &str,
+    items: &[T],
+    mut item: impl FnMut(&mut Formatter<'_>, &T) -> std::fmt::Result,
+) -> std::fmt::Result {
+    if items.is_empty() {
+        return write!(f, "{}{}", open, close);
+    }
+    writeln!(f, "{}", open)?;
+    for x in items {
+        indent(f, depth + 1)?;
+        item(f, x)?;
+        writeln!(f, ",")?;
+    }
+    indent(f, depth)?;
+    write!(f, "{}", close)
+}
+
+impl DumpValue {
+    fn write(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        match self {
+            DumpValue::Bool(x) => write!(f, "{}", x),
+            DumpValue::Unsigned(bits, x) => write!(f, "{}u{}", x, bits),
+            DumpValue::Signed(bits, x) => write!(f, "{}i{}", x, bits),
+            DumpValue::Float(bits, x) => write!(f, "{:?}f{}", x, bits),
+            DumpValue::Char(x) => write!(f, "{:?}", x),
+            DumpValue::String(x) => write!(f, "{:?}", x),
+            DumpValue::Bytes(x) => write!(f, "b\"{}\"", x.escape_ascii()),
+            DumpValue::Option(None) => write!(f, "None"),
+            DumpValue::Option(Some(x)) => {
+                write!(f, "Some(")?;
+                x.write(f, depth)?;
+                write!(f, ")")
+            }
+            DumpValue::Unit => write!(f, "()"),
+            DumpValue::Seq(x) => block(f, depth, "[", "]", x, |f, x| x.write(f, depth + 1)),
+            DumpValue::Map(x) => block(f, depth, "{", "}", x, |f, (k, v)| {
+                k.write(f, depth + 1)?;
+                write!(f, ": ")?;
+                v.write(f, depth + 1)
+            }),
+            DumpValue::Tuple(x) => block(f, depth, "(", ")", x, |f, x| x.write(f, depth + 1)),
+            DumpValue::Struct { name, fields } => {
+                write!(f, "{}", name)?;
+                fields.write(f, depth)
+            }
+            DumpValue::Variant {
+                name,
+                variant,
+                fields,
+            } => {
+                write!(f, "{}::{}", name, variant)?;
+                fields.write(f, depth)
+            }
+        }
+    }
+}
+
+impl DumpFields {
+    fn write(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        match self {
+            DumpFields::Unit => Ok(()),
+            DumpFields::Newtype(x) => {
+                write!(f, "(")?;
+                x.write(f, depth)?;
+                write!(f, ")")
+            }
+            DumpFields::Tuple(x) => block(f, depth, "(", ")", x, |f, x| x.write(f, depth + 1)),
+            DumpFields::Named(x) => block(f, depth, " {", "}", x, |f, (name, x)| {
+                write!(f, "{}: ", name)?;
+                x.write(f, depth + 1)
+            }),
+        }
+    }
+}
+
+impl Display for DumpValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { self.write(f, 0) }
+}
+
+#[test]
+fn test_dump() {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::SchemaMapBuilder;
+
+    #[derive(Serialize, Deserialize)]
+    enum Level {
+        Debug,
+        Warn(String),
+        Error { code: u32, fatal: bool },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Point(i16, f32);
+
+    #[derive(Serialize, Deserialize)]
+    struct Record {
+        level: Level,
+        origin: Point,
+        thread: Option<(u64, u16)>,
+        tags: Vec<String>,
+        counts: BTreeMap<String, i8>,
+        payload: serde_bytes::ByteBuf,
+    }
+
+    let record = Record {
+        level: Level::Error {
+            code: 503,
+            fatal: false,
+        },
+        origin: Point(-2, 1.5),
+        thread: Some((7, 300)),
+        tags: vec![],
+        counts: vec![("a".to_string(), -1), ("b".to_string(), 2)]
+            .into_iter()
+            .collect(),
+        payload: serde_bytes::ByteBuf::from(b"\x00ok".to_vec()),
+    };
+    // bincode's default encoding matches typetag_static::binary except for `char`.
+    let bytes = bincode::serialize(&record).unwrap();
+
+    let mut builder = SchemaMapBuilder::new();
+    let id = builder.add::<Record>().unwrap();
+    let map = builder.build();
+    let value = dump(&map, id, &bytes).unwrap();
+    assert_eq!(
+        value.to_string(),
+        r#"Record {
+    level: Level::Error {
+        code: 503u32,
+        fatal: false,
+    },
+    origin: Point(
+        -2i16,
+        1.5f32,
+    ),
+    thread: Some((
+        7u64,
+        300u16,
+    )),
+    tags: [],
+    counts: {
+        "a": -1i8,
+        "b": 2i8,
+    },
+    payload: b"\x00ok",
+}"#
+    );
+
+    let err = dump(&map, id, &bytes[..bytes.len() - 1]).err().unwrap();
+    assert_eq!(err.to_string(), "unexpected end of input at Record.payload");
+    let err = dump(&map, id, &[bytes.as_slice(), &[0]].concat())
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "1 trailing bytes at Record");
+    let mut bad = bytes.clone();
+    bad[0] = 9;
+    let err = dump(&map, id, &bad).err().unwrap();
+    assert_eq!(err.to_string(), "unknown variant index 9 at Record.level");
+
+    let mut builder = SchemaMapBuilder::new();
+    let id = builder.add::<Vec<()>>().unwrap();
+    let map = builder.build();
+    let bytes = bincode::serialize(&vec![(); 3]).unwrap();
+    assert_eq!(
+        dump(&map, id, &bytes).unwrap().to_string(),
+        "[\n    (),\n    (),\n    (),\n]"
+    );
+    let err = dump(&map, id, &u64::MAX.to_le_bytes()).err().unwrap();
+    assert!(matches!(err, Error::Malformed { .. }));
+}
@@ -1,12 +1,151 @@
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug)]
-pub struct Error;
+/// One step from a type into one of its components.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PathSegment {
+    Type(String),
+    Field(String),
+    Index(usize),
+    Variant(String),
+}
+
+/// The location within a type where schema building failed, e.g. `Outer.field[3].Variant`.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Path {
+    pub(crate) segments: Vec<PathSegment>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Error {
+    Custom {
+        path: Path,
+        message: String,
+    },
+    DeserializeAny {
+        path: Path,
+    },
+    DeserializeIdentifier {
+        path: Path,
+    },
+    DeserializeIgnoredAny {
+        path: Path,
+    },
+    EmptyEnum {
+        path: Path,
+    },
+    /// Samples passed to [`SchemaSerializer`](crate::SchemaSerializer) disagree about a type.
+    Inconsistent {
+        path: Path,
+    },
+    /// No sample passed to [`SchemaSerializer`](crate::SchemaSerializer) determined this part
+    /// of a type, e.g. an `Option` that was always `None` or an enum variant never used.
+    Incomplete {
+        path: Path,
+    },
+    /// The type cannot be described in the requested export format.
+    Unrepresentable {
+        path: Path,
+    },
+    /// Every value of the type contains another value of the same type, e.g.
+    /// `struct Loop(Box<Loop>)`.
+    Uninhabited {
+        path: Path,
+    },
+    /// A payload passed to [`dump`](crate::dump) does not match its schema.
+    Malformed {
+        path: Path,
+        message: String,
+    },
+}
+
+impl Path {
+    pub fn segments(&self) -> &[PathSegment] { &self.segments }
+}
+
+impl Error {
+    pub fn path(&self) -> &Path {
+        match self {
+            Error::Custom { path, .. } => path,
+            Error::DeserializeAny { path } => path,
+            Error::DeserializeIdentifier { path } => path,
+            Error::DeserializeIgnoredAny { path } => path,
+            Error::EmptyEnum { path } => path,
+            Error::Inconsistent { path } => path,
+            Error::Incomplete { path } => path,
+            Error::Unrepresentable { path } => path,
+            Error::Uninhabited { path } => path,
+            Error::Malformed { path, .. } => path,
+        }
+    }
+    fn path_mut(&mut self) -> &mut Path {
+        match self {
+            Error::Custom { path, .. } => path,
+            Error::DeserializeAny { path } => path,
+            Error::DeserializeIdentifier { path } => path,
+            Error::DeserializeIgnoredAny { path } => path,
+            Error::EmptyEnum { path } => path,
+            Error::Inconsistent { path } => path,
+            Error::Incomplete { path } => path,
+            Error::Unrepresentable { path } => path,
+            Error::Uninhabited { path } => path,
+            Error::Malformed { path, .. } => path,
+        }
+    }
+    /// Record that the error occurred inside `segment`. Called while unwinding, so segments
+    /// arrive innermost first.
+    pub(crate) fn within(mut self, segment: PathSegment) -> Self {
+        self.path_mut().segments.insert(0, segment);
+        self
+    }
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathSegment::Type(x) => write!(f, "{}", x),
+            PathSegment::Field(x) => write!(f, ".{}", x),
+            PathSegment::Index(x) => write!(f, "[{}]", x),
+            PathSegment::Variant(x) => write!(f, ".{}", x),
+        }
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for segment in &self.segments {
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
 
 impl std::error::Error for Error {}
 
 impl Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "Error") }
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Custom { message, .. } => write!(f, "{}", message)?,
+            Error::DeserializeAny { .. } => write!(f, "deserialize_any is not supported")?,
+            Error::DeserializeIdentifier { .. } => {
+                write!(f, "deserialize_identifier is not supported")?
+            }
+            Error::DeserializeIgnoredAny { .. } => {
+                write!(f, "deserialize_ignored_any is not supported")?
+            }
+            Error::EmptyEnum { .. } => write!(f, "enums without variants are not supported")?,
+            Error::Inconsistent { .. } => write!(f, "samples serialized inconsistently")?,
+            Error::Incomplete { .. } => write!(f, "not determined by any sample")?,
+            Error::Unrepresentable { .. } => {
+                write!(f, "cannot be represented in a FlatBuffers schema")?
+            }
+            Error::Uninhabited { .. } => write!(f, "type has no finite values")?,
+            Error::Malformed { message, .. } => write!(f, "{}", message)?,
+        }
+        if !self.path().segments.is_empty() {
+            write!(f, " at {}", self.path())?;
+        }
+        Ok(())
+    }
 }
 
 impl serde::de::Error for Error {
@@ -14,6 +153,18 @@ impl serde::de::Error for Error {
     where
         T: Display,
     {
-        todo!()
+        Error::Custom {
+            path: Path::default(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        <Self as serde::de::Error>::custom(msg)
     }
 }
@@ -0,0 +1,342 @@
+use std::fmt::Write;
+
+use crate::builder::short_name;
+use crate::error::{Error, Path, PathSegment};
+use crate::export::Names;
+use crate::{Schema, SchemaId, SchemaMap, VariantSchema};
+
+const HEADER: &str = "\
+// Layout written by flatbuffers_serde::serialize_raw_with.
+//
+// Enum fields are unions whose type field is written as a zero-based ushort variant index. Its
+// low byte matches the union type for every variant but the first, which reads as NONE and is
+// listed in a comment inside the union.
+// Fields of unit type occupy a slot but are never written.
+";
+
+struct FbsWriter<'a> {
+    map: &'a SchemaMap,
+    names: Names<'a>,
+    out: String,
+    unit: bool,
+    flat128: bool,
+}
+
+/// Describe the buffers that `flatbuffers_serde::serialize_raw_with` writes for the type `id` as
+/// a FlatBuffers schema (`.fbs`).
+///
+/// Structs and tuples become tables with one field per slot. Enums become unions with a wrapper
+/// table for each variant's data. Maps become vectors of key/value tables. Newtype structs are
+/// transparent and `None` is an absent field.
+///
+/// Some values written by `flatbuffers-serde` have no FlatBuffers equivalent, such as an
+/// `Option` of a scalar (an offset to a bare scalar) or a vector of vectors. These fail with
+/// [`Error::Unrepresentable`].
+pub fn to_fbs(map: &SchemaMap, id: SchemaId) -> Result<String, Error> {
+    let mut writer = FbsWriter {
+        map,
+        names: Names::new(map),
+        out: HEADER.to_string(),
+        unit: false,
+        flat128: false,
+    };
+    let root = writer
+        .table(id)
+        .map_err(|e| e.within(PathSegment::Type(short_name(&map.get(id).name).to_string())))?;
+    if writer.unit {
+        writer.out.push_str("\ntable Unit {}\n");
+    }
+    if writer.flat128 {
+        writer
+            .out
+            .push_str("\n// Little-endian 128-bit integer.\nstruct Flat128 {\n    low: ulong;\n    high: ulong;\n}\n");
+    }
+    writeln!(writer.out, "\nroot_type {};", root).unwrap();
+    Ok(writer.out)
+}
+
+fn unrepresentable<T>() -> Result<T, Error> {
+    Err(Error::Unrepresentable {
+        path: Path::default(),
+    })
+}
+
+impl<'a> FbsWriter<'a> {
+    /// Follow newtype structs, which `flatbuffers-serde` writes transparently.
+    fn resolve(&self, mut id: SchemaId) -> SchemaId {
+        while let Schema::NewtypeStruct { value, .. } = &self.map.get(id).schema {
+            id = *value;
+        }
+        id
+    }
+    fn scalar(&mut self, schema: &Schema) -> Option<&'static str> {
+        Some(match schema {
+            Schema::Bool => "bool",
+            Schema::Unsigned(8) => "ubyte",
+            Schema::Unsigned(16) => "ushort",
+            Schema::Unsigned(32) => "uint",
+            Schema::Unsigned(64) => "ulong",
+            Schema::Signed(8) => "byte",
+            Schema::Signed(16) => "short",
+            Schema::Signed(32) => "int",
+            Schema::Signed(64) => "long",
+            Schema::Unsigned(128) | Schema::Signed(128) => {
+                self.flat128 = true;
+                "Flat128"
+            }
+            Schema::Float(32) => "float",
+            Schema::Float(64) => "double",
+            Schema::Char => "uint",
+            _ => return None,
+        })
+    }
+    /// The type of a table slot holding `id`. Enums produce a union, which takes two slots just
+    /// like the variant and value that `flatbuffers-serde` writes.
+    fn field(&mut self, id: SchemaId) -> Result<String, Error> {
+        let map = self.map;
+        let id = self.resolve(id);
+        let schema = &map.get(id).schema;
+        if let Some(scalar) = self.scalar(schema) {
+            return Ok(scalar.to_string());
+        }
+        match schema {
+            Schema::String => Ok("string".to_string()),
+            Schema::Bytes => Ok("[ubyte]".to_string()),
+            Schema::Unit | Schema::UnitStruct { .. } => {
+                self.unit = true;
+                Ok("Unit".to_string())
+            }
+            Schema::Option(x) => self.reference(*x),
+            Schema::Enum { .. } => self.union(id),
+            Schema::Vec(x) => Ok(format!("[{}]", self.element(*x)?)),
+            Schema::Map(k, v) => Ok(format!("[{}]", self.entry(id, *k, *v)?)),
+            Schema::Tuple(_) | Schema::TupleStruct { .. } | Schema::Struct { .. } => self.table(id),
+            _ => unrepresentable(),
+        }
+    }
+    /// The type of a value that is written as an offset: `Some` of an option or the data of a
+    /// newtype variant. Enums are wrapped in a table.
+    fn reference(&mut self, id: SchemaId) -> Result<String, Error> {
+        let map = self.map;
+        let id = self.resolve(id);
+        match &map.get(id).schema {
+            Schema::Enum { .. } => self.table(id),
+            Schema::String
+            | Schema::Bytes
+            | Schema::Vec(_)
+            | Schema::Map(_, _)
+            | Schema::Tuple(_)
+            | Schema::TupleStruct { .. }
+            | Schema::Struct { .. } => self.field(id),
+            _ => unrepresentable(),
+        }
+    }
+    /// The type of a vector element. Enums are wrapped in a table.
+    fn element(&mut self, id: SchemaId) -> Result<String, Error> {
+        let map = self.map;
+        let id = self.resolve(id);
+        let schema = &map.get(id).schema;
+        if let Some(scalar) = self.scalar(schema) {
+            return Ok(scalar.to_string());
+        }
+        match schema {
+            Schema::Enum { .. } => self.table(id),
+            Schema::String
+            | Schema::Tuple(_)
+            | Schema::TupleStruct { .. }
+            | Schema::Struct { .. } => self.field(id),
+            _ => unrepresentable(),
+        }
+    }
+    fn entry(&mut self, id: SchemaId, key: SchemaId, value: SchemaId) -> Result<String, Error> {
+        let (name, new) = self.names.get(id, "_Entry");
+        if new {
+            self.write_table(
+                &name,
+                vec![("key".to_string(), key), ("value".to_string(), value)],
+            )?;
+        }
+        Ok(name)
+    }
+    fn write_table(&mut self, name: &str, fields: Vec<(String, SchemaId)>) -> Result<(), Error> {
+        let mut body = String::new();
+        for (field, id) in fields {
+            let typ = self
+                .field(id)
+                .map_err(|e| e.within(PathSegment::Field(field.clone())))?;
+            writeln!(body, "    {}: {};", field, typ).unwrap();
+        }
+        if body.is_empty() {
+            writeln!(self.out, "\ntable {} {{}}", name).unwrap();
+        } else {
+            writeln!(self.out, "\ntable {} {{\n{}}}", name, body).unwrap();
+        }
+        Ok(())
+    }
+    /// The table written for `id`. An enum outside of a table is wrapped in a table holding its
+    /// variant and value.
+    fn table(&mut self, id: SchemaId) -> Result<String, Error> {
+        let map = self.map;
+        let id = self.resolve(id);
+        let (fields, suffix) = match &map.get(id).schema {
+            Schema::Tuple(fields) | Schema::TupleStruct { fields, .. } => (numbered(fields), ""),
+            Schema::Struct { fields, .. } => (fields.clone(), ""),
+            Schema::Enum { .. } => (vec![("value".to_string(), id)], "_Value"),
+            _ => return unrepresentable(),
+        };
+        let (name, new) = self.names.get(id, suffix);
+        if new {
+            self.write_table(&name, fields)?;
+        }
+        Ok(name)
+    }
+    fn union(&mut self, id: SchemaId) -> Result<String, Error> {
+        let map = self.map;
+        let (name, new) = self.names.get(id, "");
+        if !new {
+            return Ok(name);
+        }
+        let enums = match &map.get(id).schema {
+            Schema::Enum { enums } => enums,
+            _ => unreachable!(),
+        };
+        if enums.len() > 256 {
+            return unrepresentable();
+        }
+        let mut body = String::new();
+        for (index, (variant, schema)) in enums.iter().enumerate() {
+            let member = match schema {
+                VariantSchema::Unit if index == 0 => Ok(None),
+                VariantSchema::Unit => self.variant_table(&name, variant, vec![]).map(Some),
+                VariantSchema::Newtype(x) => self.reference(*x).map(Some),
+                VariantSchema::Tuple(fields) => self
+                    .variant_table(&name, variant, numbered(fields))
+                    .map(Some),
+                VariantSchema::Struct(fields) => {
+                    self.variant_table(&name, variant, fields.clone()).map(Some)
+                }
+            }
+            .map_err(|e| e.within(PathSegment::Variant(variant.clone())))?;
+            match (index, member) {
+                (0, None) => writeln!(body, "    // {} (NONE)", variant),
+                (0, Some(member)) => writeln!(body, "    // {}: {} (NONE)", variant, member),
+                (_, member) => writeln!(body, "    {}: {},", variant, member.unwrap()),
+            }
+            .unwrap();
+        }
+        writeln!(self.out, "\nunion {} {{\n{}}}", name, body).unwrap();
+        Ok(name)
+    }
+    fn variant_table(
+        &mut self,
+        union: &str,
+        variant: &str,
+        fields: Vec<(String, SchemaId)>,
+    ) -> Result<String, Error> {
+        let name = self.names.unique(format!("{}_{}", union, variant));
+        self.write_table(&name, fields)?;
+        Ok(name)
+    }
+}
+
+fn numbered(fields: &[SchemaId]) -> Vec<(String, SchemaId)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (format!("_{}", index), *id))
+        .collect()
+}
+
+#[test]
+fn test_fbs() {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use crate::SchemaMapBuilder;
+
+    #[derive(Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(Point),
+        Rect { w: u16, h: u16 },
+    }
+
+    #[derive(Deserialize)]
+    struct Point(f32, f32);
+
+    #[derive(Deserialize)]
+    struct Anchor {
+        x: i8,
+        y: i8,
+    }
+
+    #[derive(Deserialize)]
+    struct Drawing {
+        name: String,
+        shapes: Vec<Shape>,
+        origin: Option<Anchor>,
+        layers: BTreeMap<String, Shape>,
+    }
+
+    let mut builder = SchemaMapBuilder::new();
+    let id = builder.add::<Drawing>().unwrap();
+    let map = builder.build();
+    assert_eq!(
+        to_fbs(&map, id).unwrap(),
+        HEADER.to_string()
+            + r#"
+table Point {
+    _0: float;
+    _1: float;
+}
+
+table Shape_Rect {
+    w: ushort;
+    h: ushort;
+}
+
+union Shape {
+    // Empty (NONE)
+    Circle: Point,
+    Rect: Shape_Rect,
+}
+
+table Shape_Value {
+    value: Shape;
+}
+
+table Anchor {
+    x: byte;
+    y: byte;
+}
+
+table BTreeMap_String_Shape_Entry {
+    key: string;
+    value: Shape;
+}
+
+table Drawing {
+    name: string;
+    shapes: [Shape_Value];
+    origin: Anchor;
+    layers: [BTreeMap_String_Shape_Entry];
+}
+
+root_type Drawing;
+"#
+    );
+
+    #[derive(Deserialize)]
+    struct Scalars {
+        maybe: Option<u32>,
+    }
+
+    let mut builder = SchemaMapBuilder::new();
+    let id = builder.add::<Scalars>().unwrap();
+    let err = to_fbs(&builder.build(), id).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "cannot be represented in a FlatBuffers schema at Scalars.maybe"
+    );
+}
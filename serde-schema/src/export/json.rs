@@ -0,0 +1,205 @@
+use serde_json::{json, Map, Value};
+
+use crate::export::Names;
+use crate::{Schema, SchemaId, SchemaMap, VariantSchema};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+struct JsonWriter<'a> {
+    map: &'a SchemaMap,
+    names: Names<'a>,
+    defs: Map<String, Value>,
+}
+
+/// Describe the JSON that `serde_json` produces for the type `id`, as a JSON Schema (draft
+/// 2020-12) document.
+///
+/// Named types (structs and enums) are placed in `$defs` and referenced. Enums use serde's default externally tagged representation. `Option` fields are
+/// not required, matching `#[derive(Deserialize)]`.
+pub fn to_json_schema(map: &SchemaMap, id: SchemaId) -> Value {
+    let mut writer = JsonWriter {
+        map,
+        names: Names::new(map),
+        defs: Map::new(),
+    };
+    let mut root = writer.schema(id);
+    let root_object = root.as_object_mut().unwrap();
+    root_object.insert("$schema".to_string(), json!(DRAFT));
+    if !writer.defs.is_empty() {
+        root_object.insert("$defs".to_string(), Value::Object(writer.defs));
+    }
+    root
+}
+
+fn integer(bits: usize, signed: bool) -> Value {
+    if bits > 64 {
+        json!({ "type": "integer" })
+    } else if signed {
+        let max = i64::MAX >> (64 - bits);
+        json!({ "type": "integer", "minimum": -max - 1, "maximum": max })
+    } else {
+        let max = u64::MAX >> (64 - bits);
+        json!({ "type": "integer", "minimum": 0, "maximum": max })
+    }
+}
+
+fn fixed_array(items: Vec<Value>) -> Value {
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "items": false,
+        "minItems": items.len(),
+    })
+}
+
+impl<'a> JsonWriter<'a> {
+    fn schema(&mut self, id: SchemaId) -> Value {
+        match &self.map.get(id).schema {
+            Schema::Bool => json!({ "type": "boolean" }),
+            Schema::Unsigned(bits) => integer(*bits, false),
+            Schema::Signed(bits) => integer(*bits, true),
+            Schema::Float(_) => json!({ "type": "number" }),
+            Schema::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+            Schema::String => json!({ "type": "string" }),
+            Schema::Bytes => json!({ "type": "array", "items": integer(8, false) }),
+            Schema::Option(x) => json!({ "anyOf": [{ "type": "null" }, self.schema(*x)] }),
+            Schema::Unit => json!({ "type": "null" }),
+            Schema::Vec(x) => json!({ "type": "array", "items": self.schema(*x) }),
+            Schema::Map(_, v) => {
+                json!({ "type": "object", "additionalProperties": self.schema(*v) })
+            }
+            Schema::Tuple(x) => {
+                let items = x.iter().map(|x| self.schema(*x)).collect();
+                fixed_array(items)
+            }
+            Schema::UnitStruct { .. }
+            | Schema::NewtypeStruct { .. }
+            | Schema::Enum { .. }
+            | Schema::TupleStruct { .. }
+            | Schema::Struct { .. } => self.reference(id),
+        }
+    }
+    fn reference(&mut self, id: SchemaId) -> Value {
+        let (name, new) = self.names.get(id, "");
+        if new {
+            // Reserve the name before recursing so recursive types terminate.
+            self.defs.insert(name.clone(), Value::Null);
+            let def = self.definition(id);
+            self.defs.insert(name.clone(), def);
+        }
+        json!({ "$ref": format!("#/$defs/{}", name) })
+    }
+    fn fields(&mut self, fields: &[(String, SchemaId)]) -> Value {
+        let mut properties = Map::new();
+        let mut required = vec![];
+        for (name, id) in fields {
+            properties.insert(name.clone(), self.schema(*id));
+            if !matches!(self.map.get(*id).schema, Schema::Option(_)) {
+                required.push(json!(name));
+            }
+        }
+        json!({ "type": "object", "properties": properties, "required": required })
+    }
+    fn definition(&mut self, id: SchemaId) -> Value {
+        match &self.map.get(id).schema {
+            Schema::UnitStruct { .. } => json!({ "type": "null" }),
+            Schema::NewtypeStruct { value, .. } => self.schema(*value),
+            Schema::TupleStruct { fields, .. } => {
+                let items = fields.iter().map(|x| self.schema(*x)).collect();
+                fixed_array(items)
+            }
+            Schema::Struct { fields, .. } => self.fields(fields),
+            Schema::Enum { enums } => {
+                let variants: Vec<Value> = enums
+                    .iter()
+                    .map(|(name, variant)| {
+                        let value = match variant {
+                            VariantSchema::Unit => return json!({ "const": name }),
+                            VariantSchema::Newtype(x) => self.schema(*x),
+                            VariantSchema::Tuple(x) => {
+                                let items = x.iter().map(|x| self.schema(*x)).collect();
+                                fixed_array(items)
+                            }
+                            VariantSchema::Struct(x) => self.fields(x),
+                        };
+                        json!({
+                            "type": "object",
+                            "properties": { name.clone(): value },
+                            "required": [name],
+                            "additionalProperties": false,
+                        })
+                    })
+                    .collect();
+                json!({ "oneOf": variants })
+            }
+            _ => self.schema(id),
+        }
+    }
+}
+
+#[test]
+fn test_json_schema() {
+    use serde::Deserialize;
+
+    use crate::SchemaMapBuilder;
+
+    #[derive(Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect { w: u16, h: u16 },
+    }
+
+    #[derive(Deserialize)]
+    struct Node {
+        shape: Shape,
+        label: Option<String>,
+        children: Vec<Shape>,
+    }
+
+    let mut builder = SchemaMapBuilder::new();
+    let id = builder.add::<Node>().unwrap();
+    let schema = to_json_schema(&builder.build(), id);
+    let u16 = json!({ "type": "integer", "minimum": 0, "maximum": 65535 });
+    assert_eq!(
+        schema,
+        json!({
+            "$schema": DRAFT,
+            "$ref": "#/$defs/Node",
+            "$defs": {
+                "Node": {
+                    "type": "object",
+                    "properties": {
+                        "shape": { "$ref": "#/$defs/Shape" },
+                        "label": { "anyOf": [{ "type": "null" }, { "type": "string" }] },
+                        "children": { "type": "array", "items": { "$ref": "#/$defs/Shape" } },
+                    },
+                    "required": ["shape", "children"],
+                },
+                "Shape": {
+                    "oneOf": [
+                        { "const": "Empty" },
+                        {
+                            "type": "object",
+                            "properties": { "Circle": { "type": "number" } },
+                            "required": ["Circle"],
+                            "additionalProperties": false,
+                        },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "Rect": {
+                                    "type": "object",
+                                    "properties": { "w": u16, "h": u16 },
+                                    "required": ["w", "h"],
+                                },
+                            },
+                            "required": ["Rect"],
+                            "additionalProperties": false,
+                        },
+                    ],
+                },
+            },
+        })
+    );
+}
@@ -0,0 +1,63 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::builder::short_name;
+use crate::{Schema, SchemaId, SchemaMap};
+
+pub mod fbs;
+pub mod json;
+
+/// Allocates identifiers for generated definitions, one per `(id, suffix)` pair.
+struct Names<'a> {
+    map: &'a SchemaMap,
+    used: HashSet<String>,
+    names: HashMap<(SchemaId, &'static str), String>,
+}
+
+/// Turns a Rust type name into an identifier valid in both exported formats by dropping module
+/// paths and replacing punctuation, e.g. `Wrapper<alloc::string::String>` becomes
+/// `Wrapper_String`.
+fn identifier(type_name: &str) -> String {
+    type_name
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .filter(|x| !x.is_empty())
+        .map(short_name)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+impl<'a> Names<'a> {
+    fn new(map: &'a SchemaMap) -> Self {
+        Names {
+            map,
+            used: HashSet::new(),
+            names: HashMap::new(),
+        }
+    }
+    /// Returns the name for `(id, suffix)` and whether it was newly allocated.
+    fn get(&mut self, id: SchemaId, suffix: &'static str) -> (String, bool) {
+        if let Some(name) = self.names.get(&(id, suffix)) {
+            return (name.clone(), false);
+        }
+        let typ = self.map.get(id);
+        let base = match &typ.schema {
+            Schema::UnitStruct { name }
+            | Schema::NewtypeStruct { name, .. }
+            | Schema::TupleStruct { name, .. }
+            | Schema::Struct { name, .. } => name.clone(),
+            _ => identifier(&typ.name),
+        };
+        let name = self.unique(format!("{}{}", base, suffix));
+        self.names.insert((id, suffix), name.clone());
+        (name, true)
+    }
+    /// Returns `base`, or `base` with a numeric suffix if it is already taken.
+    fn unique(&mut self, base: String) -> String {
+        let mut name = base.clone();
+        let mut index = 1;
+        while !self.used.insert(name.clone()) {
+            name = format!("{}_{}", base, index);
+            index += 1;
+        }
+        name
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+
+use sha2::{Digest, Sha256};
+
+use crate::{Schema, SchemaId, SchemaMap, SchemaType, VariantSchema};
+
+/// Computes content-addressed [`SchemaId`]s for a map keyed by provisional ids.
+///
+/// A type's id hashes its name, version and schema, with each referenced type replaced by that
+/// type's id. A reference back to a type that is still being hashed (a cycle) is replaced by its
+/// distance up the stack instead, so the id of a recursive type depends only on the types
+/// reachable from it.
+pub(crate) struct ContentHasher<'a> {
+    map: &'a HashMap<SchemaId, SchemaType>,
+    stack: Vec<SchemaId>,
+    /// Types on a cycle are hashed differently depending on which member of the cycle the hasher
+    /// started from, so only the ids of the other types are memoized.
+    cyclic: HashSet<SchemaId>,
+    memo: HashMap<SchemaId, SchemaId>,
+}
+
+/// Tarjan's strongly connected components algorithm, collecting the types that are on a cycle.
+struct CycleFinder<'a> {
+    map: &'a HashMap<SchemaId, SchemaType>,
+    index: HashMap<SchemaId, usize>,
+    low: HashMap<SchemaId, usize>,
+    stack: Vec<SchemaId>,
+    cyclic: HashSet<SchemaId>,
+}
+
+impl<'a> CycleFinder<'a> {
+    fn visit(&mut self, id: SchemaId) {
+        let index = self.index.len();
+        self.index.insert(id, index);
+        self.low.insert(id, index);
+        self.stack.push(id);
+        let children = self.map[&id].schema.children();
+        for child in children.iter() {
+            if !self.index.contains_key(child) {
+                self.visit(*child);
+                let low = self.low[&id].min(self.low[child]);
+                self.low.insert(id, low);
+            } else if self.stack.contains(child) {
+                let low = self.low[&id].min(self.index[child]);
+                self.low.insert(id, low);
+            }
+        }
+        if self.low[&id] == index {
+            let start = self.stack.iter().rposition(|x| *x == id).unwrap();
+            let component = self.stack.split_off(start);
+            if component.len() > 1 || children.contains(&id) {
+                self.cyclic.extend(component);
+            }
+        }
+    }
+}
+
+impl<'a> ContentHasher<'a> {
+    pub fn new(map: &'a HashMap<SchemaId, SchemaType>) -> Self {
+        let mut finder = CycleFinder {
+            map,
+            index: HashMap::new(),
+            low: HashMap::new(),
+            stack: vec![],
+            cyclic: HashSet::new(),
+        };
+        for id in map.keys() {
+            if !finder.index.contains_key(id) {
+                finder.visit(*id);
+            }
+        }
+        ContentHasher {
+            map,
+            stack: vec![],
+            cyclic: finder.cyclic,
+            memo: HashMap::new(),
+        }
+    }
+    pub fn id(&mut self, id: SchemaId) -> SchemaId { self.hash(id) }
+    /// Rewrite every provisional id in the map to its content id.
+    pub fn rewrite(mut self) -> (SchemaMap, HashMap<SchemaId, SchemaId>) {
+        let map = self.map;
+        let ids: HashMap<SchemaId, SchemaId> = map.keys().map(|id| (*id, self.id(*id))).collect();
+        let map = map
+            .iter()
+            .map(|(id, typ)| {
+                (
+                    ids[id],
+                    SchemaType {
+                        name: typ.name.clone(),
+                        version: typ.version,
+                        schema: typ.schema.map_ids(|x| ids[&x]),
+                    },
+                )
+            })
+            .collect();
+        (SchemaMap { map }, ids)
+    }
+    fn hash(&mut self, id: SchemaId) -> SchemaId {
+        if let Some(hash) = self.memo.get(&id) {
+            return *hash;
+        }
+        self.stack.push(id);
+        let map = self.map;
+        let typ = &map[&id];
+        let mut sha = Sha256::new();
+        write_str(&mut sha, &typ.name);
+        sha.update((typ.version as u64).to_le_bytes());
+        let mut reference = |sha: &mut Sha256, id: &SchemaId| self.reference(sha, *id);
+        match &typ.schema {
+            Schema::Bool => sha.update([0]),
+            Schema::Unsigned(x) => {
+                sha.update([1]);
+                sha.update((*x as u64).to_le_bytes());
+            }
+            Schema::Signed(x) => {
+                sha.update([2]);
+                sha.update((*x as u64).to_le_bytes());
+            }
+            Schema::Float(x) => {
+                sha.update([3]);
+                sha.update((*x as u64).to_le_bytes());
+            }
+            Schema::Char => sha.update([4]),
+            Schema::String => sha.update([5]),
+            Schema::Bytes => sha.update([6]),
+            Schema::Option(x) => {
+                sha.update([7]);
+                reference(&mut sha, x);
+            }
+            Schema::Unit => sha.update([8]),
+            Schema::UnitStruct { name } => {
+                sha.update([9]);
+                write_str(&mut sha, name);
+            }
+            Schema::NewtypeStruct { name, value } => {
+                sha.update([10]);
+                write_str(&mut sha, name);
+                reference(&mut sha, value);
+            }
+            Schema::Enum { enums } => {
+                sha.update([11]);
+                sha.update((enums.len() as u64).to_le_bytes());
+                for (name, variant) in enums {
+                    write_str(&mut sha, name);
+                    match variant {
+                        VariantSchema::Unit => sha.update([0]),
+                        VariantSchema::Newtype(x) => {
+                            sha.update([1]);
+                            reference(&mut sha, x);
+                        }
+                        VariantSchema::Tuple(fields) => {
+                            sha.update([2]);
+                            sha.update((fields.len() as u64).to_le_bytes());
+                            for x in fields {
+                                reference(&mut sha, x);
+                            }
+                        }
+                        VariantSchema::Struct(fields) => {
+                            sha.update([3]);
+                            sha.update((fields.len() as u64).to_le_bytes());
+                            for (name, x) in fields {
+                                write_str(&mut sha, name);
+                                reference(&mut sha, x);
+                            }
+                        }
+                    }
+                }
+            }
+            Schema::Vec(x) => {
+                sha.update([12]);
+                reference(&mut sha, x);
+            }
+            Schema::Map(k, v) => {
+                sha.update([13]);
+                reference(&mut sha, k);
+                reference(&mut sha, v);
+            }
+            Schema::Tuple(fields) => {
+                sha.update([14]);
+                sha.update((fields.len() as u64).to_le_bytes());
+                for x in fields {
+                    reference(&mut sha, x);
+                }
+            }
+            Schema::TupleStruct { name, fields } => {
+                sha.update([15]);
+                write_str(&mut sha, name);
+                sha.update((fields.len() as u64).to_le_bytes());
+                for x in fields {
+                    reference(&mut sha, x);
+                }
+            }
+            Schema::Struct { name, fields } => {
+                sha.update([16]);
+                write_str(&mut sha, name);
+                sha.update((fields.len() as u64).to_le_bytes());
+                for (name, x) in fields {
+                    write_str(&mut sha, name);
+                    reference(&mut sha, x);
+                }
+            }
+        }
+        self.stack.pop();
+        let hash = SchemaId(u64::from_le_bytes(
+            sha.finalize().as_slice()[0..8]
+                .try_into()
+                .expect("wrong length"),
+        ));
+        if !self.cyclic.contains(&id) {
+            self.memo.insert(id, hash);
+        }
+        hash
+    }
+    fn reference(&mut self, sha: &mut Sha256, id: SchemaId) {
+        if let Some(index) = self.stack.iter().position(|x| *x == id) {
+            sha.update([1]);
+            sha.update(((self.stack.len() - index) as u64).to_le_bytes());
+        } else {
+            let hash = self.hash(id);
+            sha.update([0]);
+            sha.update(hash.0.to_le_bytes());
+        }
+    }
+}
+
+fn write_str(sha: &mut Sha256, x: &str) {
+    sha.update((x.len() as u64).to_le_bytes());
+    sha.update(x);
+}
+
+impl Schema {
+    fn children(&self) -> Vec<SchemaId> {
+        match self {
+            Schema::Option(x) | Schema::NewtypeStruct { value: x, .. } | Schema::Vec(x) => vec![*x],
+            Schema::Map(k, v) => vec![*k, *v],
+            Schema::Tuple(fields) | Schema::TupleStruct { fields, .. } => fields.clone(),
+            Schema::Struct { fields, .. } => fields.iter().map(|(_, x)| *x).collect(),
+            Schema::Enum { enums } => enums
+                .iter()
+                .flat_map(|(_, variant)| match variant {
+                    VariantSchema::Unit => vec![],
+                    VariantSchema::Newtype(x) => vec![*x],
+                    VariantSchema::Tuple(x) => x.clone(),
+                    VariantSchema::Struct(x) => x.iter().map(|(_, x)| *x).collect(),
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+    fn map_ids(&self, mut f: impl FnMut(SchemaId) -> SchemaId) -> Schema {
+        match self {
+            Schema::Option(x) => Schema::Option(f(*x)),
+            Schema::NewtypeStruct { name, value } => Schema::NewtypeStruct {
+                name: name.clone(),
+                value: f(*value),
+            },
+            Schema::Enum { enums } => Schema::Enum {
+                enums: enums
+                    .iter()
+                    .map(|(name, variant)| {
+                        (
+                            name.clone(),
+                            match variant {
+                                VariantSchema::Unit => VariantSchema::Unit,
+                                VariantSchema::Newtype(x) => VariantSchema::Newtype(f(*x)),
+                                VariantSchema::Tuple(x) => {
+                                    VariantSchema::Tuple(x.iter().map(|x| f(*x)).collect())
+                                }
+                                VariantSchema::Struct(x) => VariantSchema::Struct(
+                                    x.iter().map(|(name, x)| (name.clone(), f(*x))).collect(),
+                                ),
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+            Schema::Vec(x) => Schema::Vec(f(*x)),
+            Schema::Map(k, v) => Schema::Map(f(*k), f(*v)),
+            Schema::Tuple(x) => Schema::Tuple(x.iter().map(|x| f(*x)).collect()),
+            Schema::TupleStruct { name, fields } => Schema::TupleStruct {
+                name: name.clone(),
+                fields: fields.iter().map(|x| f(*x)).collect(),
+            },
+            Schema::Struct { name, fields } => Schema::Struct {
+                name: name.clone(),
+                fields: fields
+                    .iter()
+                    .map(|(name, x)| (name.clone(), f(*x)))
+                    .collect(),
+            },
+            x => x.clone(),
+        }
+    }
+}
+
+#[test]
+fn test_content_ids() {
+    use serde::Deserialize;
+
+    use crate::SchemaMapBuilder;
+
+    #[derive(Deserialize)]
+    struct A {
+        x: u8,
+    }
+
+    #[derive(Deserialize)]
+    struct B {
+        a: A,
+        y: Option<String>,
+    }
+
+    let mut forward = SchemaMapBuilder::new();
+    let a1 = forward.add::<A>().unwrap();
+    let b1 = forward.add::<B>().unwrap();
+    let mut backward = SchemaMapBuilder::new();
+    let b2 = backward.add::<B>().unwrap();
+    let a2 = backward.add::<A>().unwrap();
+    assert_eq!((a1, b1), (a2, b2));
+    let forward = forward.build();
+    assert!(forward.contains(a1) && forward.contains(b1));
+    assert_eq!(forward, backward.build());
+
+    // Cycles are hashed by shape, independently of the provisional ids: `List` refers to itself
+    // and `Even` and `Odd` refer to each other.
+    let typ = |name: &str, schema: Schema| SchemaType {
+        name: name.to_string(),
+        version: 0,
+        schema,
+    };
+    let list = |this: u64, option: u64| {
+        vec![
+            (
+                SchemaId(this),
+                typ(
+                    "List",
+                    Schema::Struct {
+                        name: "List".to_string(),
+                        fields: vec![("next".to_string(), SchemaId(option))],
+                    },
+                ),
+            ),
+            (
+                SchemaId(option),
+                typ("Option<List>", Schema::Option(SchemaId(this))),
+            ),
+        ]
+    };
+    let parity = |even: u64, odd: u64| {
+        vec![
+            (SchemaId(even), typ("Even", Schema::Option(SchemaId(odd)))),
+            (SchemaId(odd), typ("Odd", Schema::Option(SchemaId(even)))),
+        ]
+    };
+    let first: HashMap<_, _> = list(0, 1).into_iter().chain(parity(2, 3)).collect();
+    let second: HashMap<_, _> = list(11, 10).into_iter().chain(parity(13, 12)).collect();
+    let (first, first_ids) = ContentHasher::new(&first).rewrite();
+    let (second, second_ids) = ContentHasher::new(&second).rewrite();
+    assert_eq!(first, second);
+    assert_eq!(first_ids[&SchemaId(0)], second_ids[&SchemaId(11)]);
+    assert_eq!(first_ids[&SchemaId(2)], second_ids[&SchemaId(13)]);
+    assert_ne!(first_ids[&SchemaId(2)], first_ids[&SchemaId(3)]);
+}
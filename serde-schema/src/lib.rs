@@ -13,13 +13,26 @@ use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
 
+pub use builder::SchemaMapBuilder;
+pub use compat::{check_compatibility, Change, ChangeKind, CompatReport, Compatibility};
+pub use de::{deserialize, SchemaDeserializer};
+pub use dump::{dump, DumpFields, DumpValue};
+pub use error::{Error, Path, PathSegment};
+pub use export::fbs::to_fbs;
+pub use export::json::to_json_schema;
+pub use ser::SchemaSerializer;
 use serde::de::{DeserializeSeed, EnumAccess, IntoDeserializer, SeqAccess, VariantAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::error::Error;
-
 mod builder;
+mod compat;
+mod de;
+mod dump;
 mod error;
+pub mod export;
+mod hash;
+mod print;
+mod ser;
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Clone)]
 pub enum VariantSchema {
@@ -70,6 +83,9 @@ pub struct SchemaType {
     schema: Schema,
 }
 
+/// Identifies a [`SchemaType`] by a hash of its name, version and schema, including the ids of
+/// the types it refers to. The same type produces the same id in every build and process, so ids
+/// can be stored alongside data and looked up later in any [`SchemaMap`] containing the type.
 #[derive(Serialize, Deserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
 pub struct SchemaId(u64);
 
@@ -80,4 +96,8 @@ pub struct SchemaMap {
 
 impl SchemaMap {
     fn get(&self, id: SchemaId) -> &SchemaType { self.map.get(&id).unwrap() }
+    pub fn contains(&self, id: SchemaId) -> bool { self.map.contains_key(&id) }
+    /// Add the types from `other`. Because ids are content-addressed, maps built separately can
+    /// be combined into a single registry.
+    pub fn extend(&mut self, other: SchemaMap) { self.map.extend(other.map) }
 }
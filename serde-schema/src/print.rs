@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Write};
+
+use crate::builder::short_name;
+use crate::{Schema, SchemaId, SchemaMap, SchemaType, VariantSchema};
+
+/// Turns a Rust type name into the name used when printing, dropping module paths from every
+/// path in it, e.g. `Tree<alloc::string::String>` becomes `Tree<String>`.
+pub(crate) fn rust_name(type_name: &str) -> String {
+    let mut result = String::new();
+    let mut path = String::new();
+    for c in type_name.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            result.push_str(short_name(&path));
+            path.clear();
+            result.push(c);
+        }
+    }
+    result.pop();
+    result
+}
+
+fn is_named(schema: &Schema) -> bool {
+    matches!(
+        schema,
+        Schema::UnitStruct { .. }
+            | Schema::NewtypeStruct { .. }
+            | Schema::TupleStruct { .. }
+            | Schema::Struct { .. }
+            | Schema::Enum { .. }
+    )
+}
+
+struct Printer<'a> {
+    map: &'a SchemaMap,
+    names: HashMap<SchemaId, String>,
+}
+
+impl<'a> Printer<'a> {
+    /// Names every struct and enum in `map`. Types sharing a name are told apart by their
+    /// version, and failing that by their id.
+    fn new(map: &'a SchemaMap) -> Self {
+        let mut by_name: HashMap<String, Vec<(&SchemaId, &SchemaType)>> = HashMap::new();
+        for (id, typ) in map.map.iter() {
+            if is_named(&typ.schema) {
+                by_name
+                    .entry(rust_name(&typ.name))
+                    .or_default()
+                    .push((id, typ));
+            }
+        }
+        let mut names = HashMap::new();
+        for (name, types) in by_name {
+            for (id, typ) in types.iter() {
+                let name = if types.len() == 1 {
+                    name.clone()
+                } else if types
+                    .iter()
+                    .filter(|(_, x)| x.version == typ.version)
+                    .count()
+                    == 1
+                {
+                    format!("{}#{}", name, typ.version)
+                } else {
+                    format!("{}#{:016x}", name, id.0)
+                };
+                names.insert(**id, name);
+            }
+        }
+        Printer { map, names }
+    }
+    /// The type of a field or element that refers to `id`.
+    fn reference(&self, id: SchemaId) -> String {
+        if let Some(name) = self.names.get(&id) {
+            return name.clone();
+        }
+        match &self.map.get(id).schema {
+            Schema::Bool => "bool".to_string(),
+            Schema::Unsigned(bits) => format!("u{}", bits),
+            Schema::Signed(bits) => format!("i{}", bits),
+            Schema::Float(bits) => format!("f{}", bits),
+            Schema::Char => "char".to_string(),
+            Schema::String => "String".to_string(),
+            Schema::Bytes => "Bytes".to_string(),
+            Schema::Option(x) => format!("Option<{}>", self.reference(*x)),
+            Schema::Unit => "()".to_string(),
+            Schema::Vec(x) => format!("Vec<{}>", self.reference(*x)),
+            Schema::Map(k, v) => format!("Map<{}, {}>", self.reference(*k), self.reference(*v)),
+            Schema::Tuple(x) if x.len() == 1 => format!("({},)", self.reference(x[0])),
+            Schema::Tuple(x) => format!("({})", self.tuple(x)),
+            _ => unreachable!(),
+        }
+    }
+    fn tuple(&self, fields: &[SchemaId]) -> String {
+        fields
+            .iter()
+            .map(|x| self.reference(*x))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    fn fields(&self, fields: &[(String, SchemaId)], indent: &str) -> String {
+        let mut result = String::new();
+        for (name, id) in fields {
+            writeln!(result, "{}    {}: {},", indent, name, self.reference(*id)).unwrap();
+        }
+        result
+    }
+    fn definition(&self, id: SchemaId) -> String {
+        let name = &self.names[&id];
+        match &self.map.get(id).schema {
+            Schema::UnitStruct { .. } => format!("struct {};\n", name),
+            Schema::NewtypeStruct { value, .. } => {
+                format!("struct {}({});\n", name, self.reference(*value))
+            }
+            Schema::TupleStruct { fields, .. } => {
+                format!("struct {}({});\n", name, self.tuple(fields))
+            }
+            Schema::Struct { fields, .. } if fields.is_empty() => format!("struct {} {{}}\n", name),
+            Schema::Struct { fields, .. } => {
+                format!("struct {} {{\n{}}}\n", name, self.fields(fields, ""))
+            }
+            Schema::Enum { enums } => {
+                let mut body = String::new();
+                for (variant, schema) in enums {
+                    match schema {
+                        VariantSchema::Unit => writeln!(body, "    {},", variant),
+                        VariantSchema::Newtype(x) => {
+                            writeln!(body, "    {}({}),", variant, self.reference(*x))
+                        }
+                        VariantSchema::Tuple(x) => {
+                            writeln!(body, "    {}({}),", variant, self.tuple(x))
+                        }
+                        VariantSchema::Struct(x) => writeln!(
+                            body,
+                            "    {} {{\n{}    }},",
+                            variant,
+                            self.fields(x, "    ")
+                        ),
+                    }
+                    .unwrap();
+                }
+                format!("enum {} {{\n{}}}\n", name, body)
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Prints every struct and enum in the map as a Rust-like definition, sorted by name. Other
+/// types are written inline where they are used, with maps as `Map<K, V>` and byte buffers as
+/// `Bytes`.
+impl Display for SchemaMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let printer = Printer::new(self);
+        let mut named: Vec<(&String, &SchemaId)> =
+            printer.names.iter().map(|(id, name)| (name, id)).collect();
+        named.sort();
+        for (index, (_, id)) in named.into_iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", printer.definition(*id))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_display() {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use crate::SchemaMapBuilder;
+
+    #[derive(Deserialize)]
+    enum Level {
+        Debug,
+        Warn(String),
+        Error { code: u32, fatal: bool },
+    }
+
+    #[derive(Deserialize)]
+    struct Tree<T> {
+        value: T,
+        children: Vec<Option<Tree<T>>>,
+    }
+
+    #[derive(Deserialize)]
+    struct Marker;
+
+    #[derive(Deserialize)]
+    struct Record {
+        level: Level,
+        thread: Option<(u64, String)>,
+        counts: BTreeMap<String, i16>,
+        tree: Tree<f32>,
+        marker: Marker,
+    }
+
+    let mut builder = SchemaMapBuilder::new();
+    builder.add::<Record>().unwrap();
+    assert_eq!(
+        builder.build().to_string(),
+        r#"enum Level {
+    Debug,
+    Warn(String),
+    Error {
+        code: u32,
+        fatal: bool,
+    },
+}
+
+struct Marker;
+
+struct Record {
+    level: Level,
+    thread: Option<(u64, String)>,
+    counts: Map<String, i16>,
+    tree: Tree<f32>,
+    marker: Marker,
+}
+
+struct Tree<f32> {
+    value: f32,
+    children: Vec<Option<Tree<f32>>>,
+}
+"#
+    );
+}
@@ -0,0 +1,768 @@
+use std::any::type_name;
+use std::collections::HashMap;
+
+use serde::ser::{
+    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Serialize, Serializer};
+
+use crate::builder::{short_name, short_type_name};
+use crate::error::{Error, Path, PathSegment};
+use crate::hash::ContentHasher;
+use crate::{Schema, SchemaId, SchemaMap, SchemaType, VariantSchema};
+
+/// Types are identified by [`type_name`], which is also the name recorded in the [`SchemaMap`].
+type Key = &'static str;
+
+/// Derives a [`SchemaMap`] from sample values by observing their [`Serialize`] impls.
+///
+/// A single sample rarely shows the whole type: a `None` says nothing about the `Option`'s
+/// contents, an empty `Vec` nothing about its elements, and an enum value only shows one variant.
+/// Observations from every sample passed to [`add`](Self::add) are merged, and
+/// [`build`](Self::build) fails with [`Error::Incomplete`] if some part of a type was never seen.
+/// Variants after the last one observed cannot be detected and are left out of the schema.
+///
+/// For a type that also implements `Deserialize`, the result is equal to what
+/// [`SchemaMapBuilder`](crate::SchemaMapBuilder) produces for the same roots.
+#[derive(Debug)]
+pub struct SchemaSerializer {
+    shapes: HashMap<Key, Shape>,
+    roots: Vec<Key>,
+}
+
+/// What has been observed about a type so far. `None` marks parts no sample has revealed yet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Shape {
+    Bool,
+    Unsigned(usize),
+    Signed(usize),
+    Float(usize),
+    Char,
+    String,
+    Bytes,
+    Option(Option<Key>),
+    Unit,
+    UnitStruct {
+        name: &'static str,
+    },
+    NewtypeStruct {
+        name: &'static str,
+        value: Key,
+    },
+    Enum {
+        variants: Vec<Option<(&'static str, VariantShape)>>,
+    },
+    Vec(Option<Key>),
+    Map(Option<(Key, Key)>),
+    Tuple(Vec<Key>),
+    TupleStruct {
+        name: &'static str,
+        fields: Vec<Key>,
+    },
+    Struct {
+        name: &'static str,
+        fields: Vec<(&'static str, Option<Key>)>,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum VariantShape {
+    Unit,
+    Newtype(Key),
+    Tuple(Vec<Key>),
+    Struct(Vec<(&'static str, Option<Key>)>),
+}
+
+struct ShapeSerializer<'a> {
+    ser: &'a mut SchemaSerializer,
+    key: Key,
+}
+
+enum CompoundKind {
+    Seq,
+    Map,
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(u32, &'static str),
+    Struct(&'static str),
+    StructVariant(u32, &'static str),
+}
+
+struct ShapeCompound<'a> {
+    ser: &'a mut SchemaSerializer,
+    key: Key,
+    kind: CompoundKind,
+    elements: Vec<Key>,
+    fields: Vec<(&'static str, Option<Key>)>,
+    entry: Option<(Option<Key>, Option<Key>)>,
+}
+
+struct Resolver<'a> {
+    shapes: &'a HashMap<Key, Shape>,
+    ids: HashMap<Key, SchemaId>,
+    map: HashMap<SchemaId, SchemaType>,
+}
+
+fn merge_option<T: PartialEq>(
+    old: &mut Option<T>,
+    new: Option<T>,
+    merge: impl FnOnce(&mut T, T) -> Result<(), Error>,
+) -> Result<(), Error> {
+    match (old.as_mut(), new) {
+        (_, None) => Ok(()),
+        (None, Some(new)) => {
+            *old = Some(new);
+            Ok(())
+        }
+        (Some(old), Some(new)) => merge(old, new),
+    }
+}
+
+fn merge_eq<T: PartialEq>(old: &mut T, new: T) -> Result<(), Error> {
+    if *old == new {
+        Ok(())
+    } else {
+        Err(Error::Inconsistent {
+            path: Path::default(),
+        })
+    }
+}
+
+fn merge_fields(
+    old: &mut Vec<(&'static str, Option<Key>)>,
+    new: Vec<(&'static str, Option<Key>)>,
+) -> Result<(), Error> {
+    if old.len() != new.len() {
+        return Err(Error::Inconsistent {
+            path: Path::default(),
+        });
+    }
+    for ((old_name, old), (new_name, new)) in old.iter_mut().zip(new.into_iter()) {
+        merge_eq(old_name, new_name)?;
+        merge_option(old, new, merge_eq)
+            .map_err(|e| e.within(PathSegment::Field(new_name.to_string())))?;
+    }
+    Ok(())
+}
+
+impl VariantShape {
+    fn merge(&mut self, new: VariantShape) -> Result<(), Error> {
+        match (self, new) {
+            (VariantShape::Struct(old), VariantShape::Struct(new)) => merge_fields(old, new),
+            (old, new) => merge_eq(old, new),
+        }
+    }
+}
+
+impl Shape {
+    fn merge(&mut self, new: Shape) -> Result<(), Error> {
+        match (self, new) {
+            (Shape::Option(old), Shape::Option(new)) => merge_option(old, new, merge_eq),
+            (Shape::Vec(old), Shape::Vec(new)) => merge_option(old, new, merge_eq),
+            (Shape::Map(old), Shape::Map(new)) => merge_option(old, new, merge_eq),
+            (
+                Shape::Struct { name, fields },
+                Shape::Struct {
+                    name: new_name,
+                    fields: new_fields,
+                },
+            ) => {
+                merge_eq(name, new_name)?;
+                merge_fields(fields, new_fields)
+            }
+            (
+                Shape::Enum { variants },
+                Shape::Enum {
+                    variants: new_variants,
+                },
+            ) => {
+                if variants.len() < new_variants.len() {
+                    variants.resize(new_variants.len(), None);
+                }
+                for (old, new) in variants.iter_mut().zip(new_variants.into_iter()) {
+                    let name = new.as_ref().map(|(name, _)| name.to_string());
+                    merge_option(old, new, |(old_name, old), (new_name, new)| {
+                        merge_eq(old_name, new_name)?;
+                        old.merge(new)
+                    })
+                    .map_err(|e| match name {
+                        Some(name) => e.within(PathSegment::Variant(name)),
+                        None => e,
+                    })?;
+                }
+                Ok(())
+            }
+            (old, new) => merge_eq(old, new),
+        }
+    }
+}
+
+/// The type a `Box`, `Rc` or `Arc` points to.
+fn pointee(key: Key) -> Option<Key> {
+    ["alloc::boxed::Box<", "alloc::rc::Rc<", "alloc::sync::Arc<"]
+        .iter()
+        .find_map(|pointer| key.strip_prefix(pointer)?.strip_suffix('>'))
+}
+
+impl SchemaSerializer {
+    pub fn new() -> Self {
+        SchemaSerializer {
+            shapes: HashMap::new(),
+            roots: vec![],
+        }
+    }
+    /// Record the shape of `value`, merging it with earlier samples of the same types.
+    pub fn add<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .child(value)
+            .map_err(|e| e.within(PathSegment::Type(short_type_name::<T>().to_string())))?;
+        if !self.roots.contains(&key) {
+            self.roots.push(key);
+        }
+        Ok(())
+    }
+    fn child<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<Key, Error> {
+        // References and smart pointers serialize as their referent; collections hand their
+        // elements over by reference.
+        let mut key = type_name::<T>();
+        while let Some(referent) = key
+            .strip_prefix("&mut ")
+            .or_else(|| key.strip_prefix('&'))
+            .or_else(|| pointee(key))
+        {
+            key = referent;
+        }
+        value.serialize(ShapeSerializer { ser: self, key })?;
+        Ok(key)
+    }
+    fn observe(&mut self, key: Key, shape: Shape) -> Result<(), Error> {
+        match self.shapes.get_mut(key) {
+            None => {
+                self.shapes.insert(key, shape);
+                Ok(())
+            }
+            Some(old) => old.merge(shape),
+        }
+    }
+    /// Convert the observations into a [`SchemaMap`]. Also returns the ids of the sampled types,
+    /// in the order they were first passed to [`add`](Self::add).
+    pub fn build(self) -> Result<(SchemaMap, Vec<SchemaId>), Error> {
+        let mut resolver = Resolver {
+            shapes: &self.shapes,
+            ids: HashMap::new(),
+            map: HashMap::new(),
+        };
+        let mut roots = Vec::with_capacity(self.roots.len());
+        for root in self.roots.iter() {
+            roots.push(
+                resolver
+                    .resolve(root)
+                    .map_err(|e| e.within(PathSegment::Type(short_name(root).to_string())))?,
+            );
+        }
+        let (map, ids) = ContentHasher::new(&resolver.map).rewrite();
+        Ok((map, roots.iter().map(|id| ids[id]).collect()))
+    }
+}
+
+impl<'a> Resolver<'a> {
+    fn known<T>(x: Option<T>) -> Result<T, Error> {
+        x.ok_or(Error::Incomplete {
+            path: Path::default(),
+        })
+    }
+    fn resolve_all(&mut self, keys: &[Key]) -> Result<Vec<SchemaId>, Error> {
+        keys.iter()
+            .enumerate()
+            .map(|(index, key)| {
+                self.resolve(*key)
+                    .map_err(|e| e.within(PathSegment::Index(index)))
+            })
+            .collect()
+    }
+    fn resolve_fields(
+        &mut self,
+        fields: &[(&'static str, Option<Key>)],
+    ) -> Result<Vec<(String, SchemaId)>, Error> {
+        fields
+            .iter()
+            .map(|(name, key)| {
+                Ok((
+                    name.to_string(),
+                    Self::known(*key)
+                        .and_then(|key| self.resolve(key))
+                        .map_err(|e| e.within(PathSegment::Field(name.to_string())))?,
+                ))
+            })
+            .collect()
+    }
+    /// Assigns provisional ids, which [`build`](SchemaSerializer::build) replaces with content
+    /// ids.
+    fn resolve(&mut self, key: Key) -> Result<SchemaId, Error> {
+        if let Some(id) = self.ids.get(key) {
+            return Ok(*id);
+        }
+        let id = SchemaId(self.ids.len() as u64);
+        self.ids.insert(key, id);
+        let shapes = self.shapes;
+        let schema = match &shapes[key] {
+            Shape::Bool => Schema::Bool,
+            Shape::Unsigned(x) => Schema::Unsigned(*x),
+            Shape::Signed(x) => Schema::Signed(*x),
+            Shape::Float(x) => Schema::Float(*x),
+            Shape::Char => Schema::Char,
+            Shape::String => Schema::String,
+            Shape::Bytes => Schema::Bytes,
+            Shape::Option(x) => Schema::Option(self.resolve(Self::known(*x)?)?),
+            Shape::Unit => Schema::Unit,
+            Shape::UnitStruct { name } => Schema::UnitStruct {
+                name: name.to_string(),
+            },
+            Shape::NewtypeStruct { name, value } => Schema::NewtypeStruct {
+                name: name.to_string(),
+                value: self.resolve(*value)?,
+            },
+            Shape::Enum { variants } => {
+                let mut enums = Vec::with_capacity(variants.len());
+                for (index, variant) in variants.iter().enumerate() {
+                    let (name, variant) = Self::known(variant.as_ref())
+                        .map_err(|e| e.within(PathSegment::Index(index)))?;
+                    let variant = match variant {
+                        VariantShape::Unit => Ok(VariantSchema::Unit),
+                        VariantShape::Newtype(x) => self.resolve(*x).map(VariantSchema::Newtype),
+                        VariantShape::Tuple(x) => self.resolve_all(x).map(VariantSchema::Tuple),
+                        VariantShape::Struct(x) => {
+                            self.resolve_fields(x).map(VariantSchema::Struct)
+                        }
+                    }
+                    .map_err(|e| e.within(PathSegment::Variant(name.to_string())))?;
+                    enums.push((name.to_string(), variant));
+                }
+                Schema::Enum { enums }
+            }
+            Shape::Vec(x) => Schema::Vec(self.resolve(Self::known(*x)?)?),
+            Shape::Map(x) => {
+                let (k, v) = Self::known(*x)?;
+                Schema::Map(self.resolve(k)?, self.resolve(v)?)
+            }
+            Shape::Tuple(x) => Schema::Tuple(self.resolve_all(x)?),
+            Shape::TupleStruct { name, fields } => Schema::TupleStruct {
+                name: name.to_string(),
+                fields: self.resolve_all(fields)?,
+            },
+            Shape::Struct { name, fields } => Schema::Struct {
+                name: name.to_string(),
+                fields: self.resolve_fields(fields)?,
+            },
+        };
+        self.map.insert(
+            id,
+            SchemaType {
+                name: key.to_string(),
+                version: 0,
+                schema,
+            },
+        );
+        Ok(id)
+    }
+}
+
+impl<'a> ShapeSerializer<'a> {
+    fn set(self, shape: Shape) -> Result<(), Error> { self.ser.observe(self.key, shape) }
+    fn variant(self, index: u32, variant: &'static str, shape: VariantShape) -> Result<(), Error> {
+        let mut variants = vec![None; index as usize + 1];
+        variants[index as usize] = Some((variant, shape));
+        self.set(Shape::Enum { variants })
+    }
+    fn compound(self, kind: CompoundKind) -> ShapeCompound<'a> {
+        ShapeCompound {
+            ser: self.ser,
+            key: self.key,
+            kind,
+            elements: vec![],
+            fields: vec![],
+            entry: None,
+        }
+    }
+}
+
+impl<'a> ShapeCompound<'a> {
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let index = self.elements.len();
+        let key = self.ser.child(value).map_err(|e| match self.kind {
+            CompoundKind::Seq => e,
+            _ => e.within(PathSegment::Index(index)),
+        })?;
+        match self.kind {
+            CompoundKind::Seq if index > 0 => {}
+            _ => self.elements.push(key),
+        }
+        Ok(())
+    }
+    fn field<T: ?Sized + Serialize>(&mut self, name: &'static str, value: &T) -> Result<(), Error> {
+        let key = self
+            .ser
+            .child(value)
+            .map_err(|e| e.within(PathSegment::Field(name.to_string())))?;
+        self.fields.push((name, Some(key)));
+        Ok(())
+    }
+    fn end(self) -> Result<(), Error> {
+        let ShapeCompound {
+            ser,
+            key,
+            kind,
+            elements,
+            fields,
+            entry,
+        } = self;
+        let serializer = ShapeSerializer { ser, key };
+        match kind {
+            CompoundKind::Seq => serializer.set(Shape::Vec(elements.first().copied())),
+            CompoundKind::Map => serializer.set(Shape::Map(match entry {
+                Some((Some(k), Some(v))) => Some((k, v)),
+                _ => None,
+            })),
+            CompoundKind::Tuple => serializer.set(Shape::Tuple(elements)),
+            CompoundKind::TupleStruct(name) => serializer.set(Shape::TupleStruct {
+                name,
+                fields: elements,
+            }),
+            CompoundKind::TupleVariant(index, variant) => {
+                serializer.variant(index, variant, VariantShape::Tuple(elements))
+            }
+            CompoundKind::Struct(name) => serializer.set(Shape::Struct { name, fields }),
+            CompoundKind::StructVariant(index, variant) => {
+                serializer.variant(index, variant, VariantShape::Struct(fields))
+            }
+        }
+    }
+}
+
+impl<'a> Serializer for ShapeSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = ShapeCompound<'a>;
+    type SerializeTuple = ShapeCompound<'a>;
+    type SerializeTupleStruct = ShapeCompound<'a>;
+    type SerializeTupleVariant = ShapeCompound<'a>;
+    type SerializeMap = ShapeCompound<'a>;
+    type SerializeStruct = ShapeCompound<'a>;
+    type SerializeStructVariant = ShapeCompound<'a>;
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> { self.set(Shape::Bool) }
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> { self.set(Shape::Signed(8)) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> { self.set(Shape::Signed(16)) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> { self.set(Shape::Signed(32)) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> { self.set(Shape::Signed(64)) }
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.set(Shape::Signed(128))
+    }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> { self.set(Shape::Unsigned(8)) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.set(Shape::Unsigned(16))
+    }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.set(Shape::Unsigned(32))
+    }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.set(Shape::Unsigned(64))
+    }
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.set(Shape::Unsigned(128))
+    }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> { self.set(Shape::Float(32)) }
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> { self.set(Shape::Float(64)) }
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> { self.set(Shape::Char) }
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> { self.set(Shape::String) }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> { self.set(Shape::Bytes) }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> { self.set(Shape::Option(None)) }
+    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        let inner = self.ser.child(value)?;
+        self.set(Shape::Option(Some(inner)))
+    }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> { self.set(Shape::Unit) }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.set(Shape::UnitStruct { name })
+    }
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.variant(variant_index, variant, VariantShape::Unit)
+    }
+    fn serialize_newtype_struct<T: ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        let value = self.ser.child(value)?;
+        self.set(Shape::NewtypeStruct { name, value })
+    }
+    fn serialize_newtype_variant<T: ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize,
+    {
+        let value = self
+            .ser
+            .child(value)
+            .map_err(|e| e.within(PathSegment::Variant(variant.to_string())))?;
+        self.variant(variant_index, variant, VariantShape::Newtype(value))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self.compound(CompoundKind::Seq))
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self.compound(CompoundKind::Tuple))
+    }
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self.compound(CompoundKind::TupleStruct(name)))
+    }
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(self.compound(CompoundKind::TupleVariant(variant_index, variant)))
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(self.compound(CompoundKind::Map))
+    }
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self.compound(CompoundKind::Struct(name)))
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(self.compound(CompoundKind::StructVariant(variant_index, variant)))
+    }
+    fn is_human_readable(&self) -> bool { false }
+}
+
+impl<'a> SerializeSeq for ShapeCompound<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.element(value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> { ShapeCompound::end(self) }
+}
+
+impl<'a> SerializeTuple for ShapeCompound<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.element(value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> { ShapeCompound::end(self) }
+}
+
+impl<'a> SerializeTupleStruct for ShapeCompound<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.element(value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> { ShapeCompound::end(self) }
+}
+
+impl<'a> SerializeTupleVariant for ShapeCompound<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.element(value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> { ShapeCompound::end(self) }
+}
+
+impl<'a> SerializeMap for ShapeCompound<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        let key = self.ser.child(key)?;
+        self.entry.get_or_insert((None, None)).0 = Some(key);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        let value = self.ser.child(value)?;
+        self.entry.get_or_insert((None, None)).1 = Some(value);
+        Ok(())
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> { ShapeCompound::end(self) }
+}
+
+impl<'a> SerializeStruct for ShapeCompound<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.field(key, value)
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.fields.push((key, None));
+        Ok(())
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> { ShapeCompound::end(self) }
+}
+
+impl<'a> SerializeStructVariant for ShapeCompound<'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.field(key, value)
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.fields.push((key, None));
+        Ok(())
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> { ShapeCompound::end(self) }
+}
+
+#[test]
+fn test_samples() {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use crate::SchemaMapBuilder;
+
+    #[derive(Serialize, Deserialize)]
+    enum Level {
+        Debug,
+        Info,
+        Warn(String),
+        Error { code: u32, fatal: bool },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Record {
+        level: Level,
+        message: String,
+        thread: Option<(u64, String)>,
+        tags: Vec<String>,
+        counts: BTreeMap<String, u64>,
+    }
+
+    let samples = vec![
+        Record {
+            level: Level::Debug,
+            message: "starting".to_string(),
+            thread: None,
+            tags: vec![],
+            counts: BTreeMap::new(),
+        },
+        Record {
+            level: Level::Error {
+                code: 503,
+                fatal: false,
+            },
+            message: "request".to_string(),
+            thread: Some((1, "main".to_string())),
+            tags: vec!["http".to_string()],
+            counts: BTreeMap::new(),
+        },
+        Record {
+            level: Level::Info,
+            message: "done".to_string(),
+            thread: None,
+            tags: vec![],
+            counts: vec![("bytes".to_string(), 10)].into_iter().collect(),
+        },
+    ];
+    let mut serializer = SchemaSerializer::new();
+    for sample in samples.iter() {
+        serializer.add(sample).unwrap();
+    }
+    let err = serializer.build().err().unwrap();
+    assert_eq!(
+        err,
+        Error::Incomplete {
+            path: Path {
+                segments: vec![
+                    PathSegment::Type("Record".to_string()),
+                    PathSegment::Field("level".to_string()),
+                    PathSegment::Index(2),
+                ]
+            }
+        }
+    );
+
+    let mut serializer = SchemaSerializer::new();
+    for sample in samples.iter() {
+        serializer.add(sample).unwrap();
+    }
+    serializer
+        .add(&Record {
+            level: Level::Warn("slow".to_string()),
+            message: "retrying".to_string(),
+            thread: None,
+            tags: vec![],
+            counts: BTreeMap::new(),
+        })
+        .unwrap();
+    let (from_samples, roots) = serializer.build().unwrap();
+
+    let mut builder = SchemaMapBuilder::new();
+    let root = builder.add::<Record>().unwrap();
+    assert_eq!(roots, vec![root]);
+    assert_eq!(from_samples, builder.build());
+}
@@ -1,8 +1,9 @@
-use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::any::{type_name, Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 //use crate::util::AnySingleton;
 use std::fmt::{Debug, Formatter};
-use std::io::Seek;
+use std::io::Read;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -14,7 +15,9 @@ use serde::{de, ser, Deserialize, Serialize, Serializer};
 use crate::binary::de::BinaryDeserializer;
 use crate::binary::ser::BinarySerializer;
 use crate::binary::{Error, UnknownBinary};
+use crate::registry::{Format, Registration, Sample};
 use crate::tag::{HasTypeTag, TypeTag, TypeTagHash};
+use crate::version::{HasUpgrades, UpgradeFrom};
 use crate::{AnyDeserializer, AnySerde, AnySerializerDefault, ArcAnySerde, BoxAnySerde};
 
 impl<'a> AnySerializerDefault for BinarySerializer<'a> {
@@ -30,7 +33,7 @@ impl<'a> AnySerializerDefault for BinarySerializer<'a> {
             IMPLS
                 .by_type_id
                 .get(&id)
-                .ok_or(Error::MissingSerialize(value.inner_type_name().to_string()))?
+                .ok_or_else(|| Error::MissingSerialize(crate::describe(value)))?
                 .serialize_binary(self, value)
         }
     }
@@ -42,18 +45,14 @@ impl Debug for dyn AnyBinary {
     }
 }
 
-impl<'a, 'de> AnyDeserializer<'de> for &'a mut BinaryDeserializer<'de> {
+impl<'a, 'de, R: Read> AnyDeserializer<'de> for &'a mut BinaryDeserializer<'de, R> {
     fn deserialize_box_impl(self) -> Result<BoxAnySerde, Self::Error> {
         let tag: TypeTagHash = TypeTagHash::deserialize(&mut *self)?;
         let length = u64::from_le_bytes(self.read_fixed()?);
-        if let Some(imp) = IMPLS.by_type_tag_hash.get(&tag) {
-            imp.deserialize_box_binary(self)
+        if let Some(imp) = IMPLS.by_hash(tag)? {
+            self.read_nested(length, |de| imp.deserialize_box_binary(de))
         } else {
-            if length > self.cursor.stream_len().unwrap() {
-                return Err(Self::Error::BadLength);
-            }
-            let mut content = vec![0; length as usize];
-            self.read_exact(&mut content)?;
+            let content = self.read_vec(length)?;
             Ok(Box::new(UnknownBinary { tag, content }))
         }
     }
@@ -61,14 +60,10 @@ impl<'a, 'de> AnyDeserializer<'de> for &'a mut BinaryDeserializer<'de> {
     fn deserialize_arc_impl(self) -> Result<ArcAnySerde, Self::Error> {
         let tag: TypeTagHash = TypeTagHash::deserialize(&mut *self)?;
         let length = u64::from_le_bytes(self.read_fixed()?);
-        if let Some(imp) = IMPLS.by_type_tag_hash.get(&tag) {
-            imp.deserialize_arc_binary(self)
+        if let Some(imp) = IMPLS.by_hash(tag)? {
+            self.read_nested(length, |de| imp.deserialize_arc_binary(de))
         } else {
-            if length > self.cursor.stream_len().unwrap() {
-                return Err(Self::Error::BadLength);
-            }
-            let mut content = vec![0; length as usize];
-            self.read_exact(&mut content)?;
+            let content = self.read_vec(length)?;
             Ok(ArcAnySerde::new(UnknownBinary { tag, content }))
         }
     }
@@ -77,6 +72,11 @@ impl<'a, 'de> AnyDeserializer<'de> for &'a mut BinaryDeserializer<'de> {
 pub trait AnyBinary: 'static + Send + Sync {
     fn inner_type_tag(&self) -> &'static TypeTag;
     fn inner_type_id(&self) -> TypeId;
+    fn inner_type_name(&self) -> &'static str;
+    /// Whether this impl only reads an older version of its type.
+    fn is_upgrade(&self) -> bool { false }
+    /// A default-constructed value, if the type implements [`Default`].
+    fn sample(&self) -> Option<BoxAnySerde> { None }
     fn serialize_binary<'a>(
         &self,
         serializer: BinarySerializer<'a>,
@@ -97,6 +97,8 @@ impl<T: Serialize + for<'de> Deserialize<'de> + 'static + HasTypeTag + AnySerde>
 {
     fn inner_type_tag(&self) -> &'static TypeTag { T::type_tag() }
     fn inner_type_id(&self) -> TypeId { TypeId::of::<T>() }
+    fn inner_type_name(&self) -> &'static str { type_name::<T>() }
+    fn sample(&self) -> Option<BoxAnySerde> { T::sample() }
     fn serialize_binary<'a>(
         &self,
         mut serializer: BinarySerializer<'a>,
@@ -120,6 +122,43 @@ impl<T: Serialize + for<'de> Deserialize<'de> + 'static + HasTypeTag + AnySerde>
     }
 }
 
+impl<T: HasUpgrades + AnySerde> AnyBinary for UpgradeFrom<T> {
+    fn inner_type_tag(&self) -> &'static TypeTag { self.tag() }
+    fn inner_type_id(&self) -> TypeId { TypeId::of::<T>() }
+    fn inner_type_name(&self) -> &'static str { type_name::<T>() }
+    fn is_upgrade(&self) -> bool { true }
+    fn serialize_binary<'a>(
+        &self,
+        serializer: BinarySerializer<'a>,
+        value: &dyn AnySerde,
+    ) -> Result<(), Error> {
+        Err(Error::BadType)
+    }
+    fn deserialize_box_binary<'a, 'de>(
+        &self,
+        deserializer: &'a mut BinaryDeserializer<'de>,
+    ) -> Result<BoxAnySerde, Error> {
+        let old = (self.step().deserialize_binary)(deserializer)?;
+        Ok(Box::new(self.upgrade(old)))
+    }
+    fn deserialize_arc_binary<'a, 'de>(
+        &self,
+        deserializer: &'a mut BinaryDeserializer<'de>,
+    ) -> Result<ArcAnySerde, Error> {
+        let old = (self.step().deserialize_binary)(deserializer)?;
+        Ok(ArcAnySerde::new(self.upgrade(old)))
+    }
+}
+
+pub(crate) type DeserializeAny =
+    for<'a, 'de> fn(&'a mut BinaryDeserializer<'de>) -> Result<Box<dyn Any>, Error>;
+
+pub(crate) fn deserialize_any<'a, 'de, T: for<'de2> Deserialize<'de2> + 'static>(
+    deserializer: &'a mut BinaryDeserializer<'de>,
+) -> Result<Box<dyn Any>, Error> {
+    Ok(Box::new(T::deserialize(deserializer)?))
+}
+
 impl AnySerde for UnknownBinary {
     fn clone_box(&self) -> BoxAnySerde { Box::new(self.clone()) }
 
@@ -127,31 +166,84 @@ impl AnySerde for UnknownBinary {
 }
 
 pub struct Impls {
+    all: Vec<&'static dyn AnyBinary>,
     by_type_id: HashMap<TypeId, &'static dyn AnyBinary>,
     by_type_tag_hash: HashMap<TypeTagHash, &'static dyn AnyBinary>,
+    /// Hashes registered for more than one type, which fail to deserialize.
+    ambiguous: HashSet<TypeTagHash>,
+}
+
+impl Impls {
+    pub(crate) fn tag_by_type_id(&self, id: TypeId) -> Option<&'static TypeTag> {
+        Some(self.by_type_id.get(&id)?.inner_type_tag())
+    }
+    pub(crate) fn tag_by_hash(&self, hash: TypeTagHash) -> Option<&'static TypeTag> {
+        Some(self.by_type_tag_hash.get(&hash)?.inner_type_tag())
+    }
+    fn by_hash(&self, hash: TypeTagHash) -> Result<Option<&'static dyn AnyBinary>, Error> {
+        if self.ambiguous.contains(&hash) {
+            return Err(Error::AmbiguousTag(hash));
+        }
+        Ok(self.by_type_tag_hash.get(&hash).copied())
+    }
+    pub(crate) fn registrations(&self) -> impl Iterator<Item = Registration> + '_ {
+        self.all.iter().map(|imp| Registration {
+            format: Format::Binary,
+            tag: imp.inner_type_tag(),
+            type_name: imp.inner_type_name(),
+            type_id: imp.inner_type_id(),
+            upgrade: imp.is_upgrade(),
+        })
+    }
+    pub(crate) fn samples(&self) -> Vec<BoxAnySerde> {
+        self.by_type_id
+            .values()
+            .filter_map(|imp| imp.sample())
+            .collect()
+    }
 }
 
 impl Builder for Impls {
     type Output = Self;
     fn new() -> Self {
         Impls {
+            all: vec![],
             by_type_id: HashMap::new(),
             by_type_tag_hash: HashMap::new(),
+            ambiguous: HashSet::new(),
         }
     }
     fn build(self) -> Self::Output { self }
 }
 
 impl BuilderFrom<&'static dyn AnyBinary> for Impls {
+    /// Conflicting elements are kept for [`validate`](crate::validate). A hash registered for
+    /// more than one type is not resolved to either of them, so reading it fails no matter which
+    /// was registered first.
     fn insert(&mut self, element: &'static dyn AnyBinary) {
-        assert!(self
-            .by_type_id
-            .insert(element.inner_type_id(), element)
-            .is_none());
-        assert!(self
-            .by_type_tag_hash
-            .insert(element.inner_type_tag().hash, element)
-            .is_none());
+        self.all.push(element);
+        if !element.is_upgrade() {
+            self.by_type_id
+                .entry(element.inner_type_id())
+                .or_insert(element);
+        }
+        let tag = element.inner_type_tag();
+        if self.ambiguous.contains(&tag.hash) {
+            return;
+        }
+        match self.by_type_tag_hash.entry(tag.hash) {
+            Entry::Occupied(first)
+                if first.get().inner_type_id() != element.inner_type_id()
+                    || first.get().inner_type_tag().name != tag.name =>
+            {
+                first.remove();
+                self.ambiguous.insert(tag.hash);
+            }
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(element);
+            }
+        }
     }
 }
 
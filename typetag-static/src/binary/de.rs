@@ -1,22 +1,83 @@
 use std::io::{Cursor, Read};
+use std::marker::PhantomData;
+use std::mem::size_of;
 
 use serde::de::{
     DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
 };
 use serde::Deserializer;
 
-use crate::binary::{Error, Result};
+use crate::binary::{Error, Limit, Options, Result};
 
-pub struct BinaryDeserializer<'de> {
-    pub cursor: Cursor<&'de [u8]>,
+/// Reads the binary format from `cursor`, which is a [`Cursor`] over a slice unless the
+/// deserializer was created with [`BinaryDeserializer::from_reader`].
+pub struct BinaryDeserializer<'de, R = Cursor<&'de [u8]>> {
+    pub cursor: R,
+    options: Options,
+    allocated: u64,
+    depth: usize,
+    phantom: PhantomData<&'de [u8]>,
+}
+
+/// The source of a [`BinaryDeserializer`], with a faster path for input that is already in
+/// memory.
+pub(crate) trait Input: Read {
+    /// Reads exactly `length` bytes, allocating as they arrive rather than trusting `length`.
+    fn read_vec(&mut self, length: u64) -> Result<Vec<u8>>;
+    /// Takes the next `length` bytes without copying them, if the input is in memory.
+    fn read_borrowed(&mut self, length: u64) -> Option<Result<&[u8]>>;
+}
+
+impl<R: Read> Input for R {
+    default fn read_vec(&mut self, length: u64) -> Result<Vec<u8>> {
+        let mut vec = vec![];
+        self.take(length).read_to_end(&mut vec)?;
+        if vec.len() as u64 != length {
+            return Err(Error::BadLength);
+        }
+        Ok(vec)
+    }
+    default fn read_borrowed(&mut self, length: u64) -> Option<Result<&[u8]>> { None }
+}
+
+impl<'s> Input for Cursor<&'s [u8]> {
+    fn read_vec(&mut self, length: u64) -> Result<Vec<u8>> {
+        Ok(self.read_borrowed(length).unwrap()?.to_vec())
+    }
+    fn read_borrowed(&mut self, length: u64) -> Option<Result<&[u8]>> {
+        let slice: &'s [u8] = self.get_ref();
+        let start = self.position();
+        let end = start
+            .checked_add(length)
+            .filter(|end| *end <= slice.len() as u64);
+        Some(match end {
+            Some(end) => {
+                self.set_position(end);
+                Ok(&slice[start as usize..end as usize])
+            }
+            None => Err(Error::BadLength),
+        })
+    }
 }
 
 impl<'de> BinaryDeserializer<'de> {
-    pub fn new(slice: &'de [u8]) -> Self {
+    pub fn new(slice: &'de [u8]) -> Self { BinaryDeserializer::from_reader(Cursor::new(slice)) }
+}
+
+impl<'de, R: Read> BinaryDeserializer<'de, R> {
+    pub fn from_reader(reader: R) -> Self {
         BinaryDeserializer {
-            cursor: Cursor::new(slice),
+            cursor: reader,
+            options: Options::default(),
+            allocated: 0,
+            depth: 0,
+            phantom: PhantomData,
         }
     }
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
     pub fn read_fixed<const C: usize>(&mut self) -> Result<[u8; C]> {
         let mut buf = [0u8; C];
         self.cursor.read_exact(&mut buf)?;
@@ -25,9 +86,73 @@ impl<'de> BinaryDeserializer<'de> {
     pub fn read_exact(&mut self, slice: &mut [u8]) -> Result<()> {
         Ok(self.cursor.read_exact(slice)?)
     }
+    /// Reads `length` bytes, failing with [`Error::BadLength`] if the input ends first. Memory is
+    /// allocated as the bytes arrive rather than trusting `length` up front, and counts towards
+    /// [`Options::max_alloc`].
+    pub fn read_vec(&mut self, length: u64) -> Result<Vec<u8>> {
+        charge(&mut self.allocated, &self.options, length)?;
+        self.cursor.read_vec(length)
+    }
+    /// Deserializes the next `length` bytes with `f`. They are borrowed when reading from a
+    /// slice, and otherwise read into a buffer that counts towards [`Options::max_alloc`].
+    pub(crate) fn read_nested<T>(
+        &mut self,
+        length: u64,
+        f: impl FnOnce(&mut BinaryDeserializer) -> Result<T>,
+    ) -> Result<T> {
+        let buffer;
+        let slice = match self.cursor.read_borrowed(length) {
+            Some(slice) => slice?,
+            None => {
+                charge(&mut self.allocated, &self.options, length)?;
+                buffer = self.cursor.read_vec(length)?;
+                &buffer
+            }
+        };
+        let mut nested = BinaryDeserializer {
+            cursor: Cursor::new(slice),
+            options: self.options,
+            allocated: self.allocated,
+            depth: self.depth,
+            phantom: PhantomData,
+        };
+        let result = f(&mut nested);
+        self.allocated = nested.allocated;
+        result
+    }
+    /// Counts `size` bytes towards [`Options::max_alloc`] for an element of a sequence or map.
+    fn charge(&mut self, size: usize) -> Result<()> {
+        charge(&mut self.allocated, &self.options, size as u64)
+    }
+    fn read_len(&mut self) -> Result<usize> {
+        let len = u64::from_le_bytes(self.read_fixed()?);
+        if len > self.options.max_len {
+            return Err(Error::LimitExceeded(Limit::Len));
+        }
+        usize::try_from(len).map_err(|_| Error::LimitExceeded(Limit::Len))
+    }
+    /// Runs `f` one level deeper, failing if that exceeds [`Options::max_depth`].
+    fn nest<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= self.options.max_depth {
+            return Err(Error::LimitExceeded(Limit::Depth));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
 }
 
-impl<'a, 'de> EnumAccess<'de> for &'a mut BinaryDeserializer<'de> {
+/// Adds `length` to the bytes `allocated` so far, failing if that exceeds [`Options::max_alloc`].
+fn charge(allocated: &mut u64, options: &Options, length: u64) -> Result<()> {
+    *allocated = allocated
+        .checked_add(length)
+        .filter(|allocated| *allocated <= options.max_alloc)
+        .ok_or(Error::LimitExceeded(Limit::Alloc))?;
+    Ok(())
+}
+
+impl<'a, 'de, R: Read> EnumAccess<'de> for &'a mut BinaryDeserializer<'de, R> {
     type Error = Error;
     type Variant = Self;
 
@@ -41,7 +166,7 @@ impl<'a, 'de> EnumAccess<'de> for &'a mut BinaryDeserializer<'de> {
     }
 }
 
-impl<'a, 'de> VariantAccess<'de> for &'a mut BinaryDeserializer<'de> {
+impl<'a, 'de, R: Read> VariantAccess<'de> for &'a mut BinaryDeserializer<'de, R> {
     type Error = Error;
     fn unit_variant(self) -> Result<()> { Ok(()) }
     fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
@@ -64,7 +189,7 @@ impl<'a, 'de> VariantAccess<'de> for &'a mut BinaryDeserializer<'de> {
     }
 }
 
-impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
+impl<'a, 'de, R: Read> Deserializer<'de> for &'a mut BinaryDeserializer<'de, R> {
     type Error = Error;
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -169,8 +294,7 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
         V: Visitor<'de>,
     {
         let length = u64::from_le_bytes(self.read_fixed()?);
-        let mut vec = vec![0; length as usize];
-        self.cursor.read_exact(&mut vec)?;
+        let vec = self.read_vec(length)?;
         visitor.visit_string(String::from_utf8(vec)?)
     }
     fn deserialize_bytes<V>(mut self, visitor: V) -> Result<V::Value>
@@ -184,8 +308,7 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
         V: Visitor<'de>,
     {
         let length = u64::from_le_bytes(self.read_fixed()?);
-        let mut vec = vec![0; length as usize];
-        self.cursor.read_exact(&mut vec)?;
+        let vec = self.read_vec(length)?;
         visitor.visit_byte_buf(vec)
     }
     fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value>
@@ -193,7 +316,7 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
         V: Visitor<'de>,
     {
         if u8::from_le_bytes(self.read_fixed()?) != 0 {
-            visitor.visit_some(self)
+            self.nest(|de| visitor.visit_some(de))
         } else {
             visitor.visit_none()
         }
@@ -214,24 +337,24 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        self.nest(|de| visitor.visit_newtype_struct(de))
     }
     fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let length = u64::from_le_bytes(self.read_fixed()?);
-        self.deserialize_tuple(length as usize, visitor)
+        let length = self.read_len()?;
+        self.deserialize_tuple(length, visitor)
     }
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        struct Access<'a, 'de> {
-            de: &'a mut BinaryDeserializer<'de>,
+        struct Access<'a, 'de, R> {
+            de: &'a mut BinaryDeserializer<'de, R>,
             len: usize,
         }
-        impl<'a, 'de> SeqAccess<'de> for Access<'a, 'de> {
+        impl<'a, 'de, R: Read> SeqAccess<'de> for Access<'a, 'de, R> {
             type Error = Error;
             fn next_element_seed<T: DeserializeSeed<'de>>(
                 &mut self,
@@ -239,6 +362,7 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
             ) -> Result<Option<T::Value>> {
                 if self.len > 0 {
                     self.len -= 1;
+                    self.de.charge(size_of::<T::Value>())?;
                     let value = seed.deserialize(&mut *self.de)?;
                     Ok(Some(value))
                 } else {
@@ -247,7 +371,7 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
             }
             fn size_hint(&self) -> Option<usize> { Some(self.len) }
         }
-        visitor.visit_seq(Access { de: self, len })
+        self.nest(|de| visitor.visit_seq(Access { de, len }))
     }
     fn deserialize_tuple_struct<V>(
         self,
@@ -264,12 +388,12 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let len = u64::from_le_bytes(self.read_fixed()?) as usize;
-        struct Access<'a, 'de> {
-            de: &'a mut BinaryDeserializer<'de>,
+        let len = self.read_len()?;
+        struct Access<'a, 'de, R> {
+            de: &'a mut BinaryDeserializer<'de, R>,
             len: usize,
         }
-        impl<'a, 'de> MapAccess<'de> for Access<'a, 'de> {
+        impl<'a, 'de, R: Read> MapAccess<'de> for Access<'a, 'de, R> {
             type Error = Error;
             fn size_hint(&self) -> Option<usize> { Some(self.len) }
             fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
@@ -278,6 +402,7 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
             {
                 if self.len > 0 {
                     self.len -= 1;
+                    self.de.charge(size_of::<K::Value>())?;
                     let key = seed.deserialize(&mut *self.de)?;
                     Ok(Some(key))
                 } else {
@@ -288,10 +413,11 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
             where
                 V: DeserializeSeed<'de>,
             {
+                self.de.charge(size_of::<V::Value>())?;
                 seed.deserialize(&mut *self.de)
             }
         }
-        visitor.visit_map(Access { de: self, len })
+        self.nest(|de| visitor.visit_map(Access { de, len }))
     }
     fn deserialize_struct<V>(
         self,
@@ -313,7 +439,7 @@ impl<'a, 'de> Deserializer<'de> for &'a mut BinaryDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(self)
+        self.nest(|de| visitor.visit_enum(de))
     }
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -2,7 +2,7 @@ use std::any::TypeId;
 use std::f32::consts::E;
 use std::fmt::{Debug, Display, Formatter};
 use std::io;
-use std::io::{Cursor, ErrorKind, Read};
+use std::io::{Cursor, ErrorKind, Read, Write};
 use std::ops::Range;
 use std::string::FromUtf8Error;
 
@@ -17,7 +17,7 @@ use serde::ser::{
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::binary::de::BinaryDeserializer;
-use crate::binary::ser::BinarySerializer;
+use crate::binary::ser::{BinarySerializer, Writer};
 use crate::tag::{TypeTag, TypeTagHash};
 
 pub mod any;
@@ -34,6 +34,31 @@ pub enum Error {
     MissingSerialize(String),
     BadType,
     BadLength,
+    LimitExceeded(Limit),
+    /// More than one type is registered with the tag, so the value cannot be read.
+    AmbiguousTag(TypeTagHash),
+}
+
+/// A limit set by [`Options`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Limit {
+    Alloc,
+    Len,
+    Depth,
+}
+
+/// Limits for deserializing untrusted input. Every limit defaults to unlimited.
+#[derive(Debug, Copy, Clone)]
+pub struct Options {
+    /// The total number of bytes allocated while deserializing: the contents of strings, byte
+    /// buffers and unknown values, plus the in-memory size of every element of a sequence or map
+    /// (its key and value for a map). Capacity that serde reserves from a length prefix is capped
+    /// by serde itself, and is not counted until the elements arrive.
+    pub max_alloc: u64,
+    /// The number of elements in any one sequence or map.
+    pub max_len: u64,
+    /// The nesting depth of sequences, maps, structs, enums and options.
+    pub max_depth: usize,
 }
 
 impl Clone for Error {
@@ -47,6 +72,8 @@ impl Clone for Error {
             Error::MissingSerialize(x) => Error::MissingSerialize(x.clone()),
             Error::BadType => Error::BadType,
             Error::BadLength => Error::BadLength,
+            Error::LimitExceeded(x) => Error::LimitExceeded(*x),
+            Error::AmbiguousTag(x) => Error::AmbiguousTag(*x),
         }
     }
 }
@@ -57,8 +84,8 @@ type Result<T> = std::result::Result<T, Error>;
 /// an unrecognized tag. Ensures that such values can safely be re-serialized without losing data.
 #[derive(Clone, Debug)]
 pub struct UnknownBinary {
-    pub(in crate::binary) tag: TypeTagHash,
-    pub(in crate::binary) content: Vec<u8>,
+    pub(crate) tag: TypeTagHash,
+    pub(crate) content: Vec<u8>,
 }
 
 impl std::error::Error for Error {}
@@ -74,10 +101,14 @@ impl Display for Error {
             Error::FromUtf8(Some(e)) => write!(f, "UTF8 error: {}", e),
             Error::Unsupported => write!(f, "Unsupported operation"),
             Error::MissingSerialize(id) => {
-                write!(f, "Missing `impl_any_binary!` for type with {:?}", id)
+                write!(f, "Missing AnyBinary impl for {}", id)
             }
             Error::BadType => write!(f, "Bad AnySerialize"),
             Error::BadLength => write!(f, "Bad length"),
+            Error::LimitExceeded(limit) => write!(f, "Exceeded {:?} limit", limit),
+            Error::AmbiguousTag(tag) => {
+                write!(f, "Tag {} is registered for more than one type", tag)
+            }
         }
     }
 }
@@ -120,7 +151,9 @@ impl From<Error> for io::Error {
             | Error::BadChar
             | Error::Unsupported
             | Error::BadType
-            | Error::BadLength => io::Error::new(ErrorKind::Other, format!("{}", e)),
+            | Error::BadLength
+            | Error::LimitExceeded(_)
+            | Error::AmbiguousTag(_) => io::Error::new(ErrorKind::Other, format!("{}", e)),
         }
     }
 }
@@ -139,3 +172,42 @@ pub fn serialize_into<T: Serialize>(output: &mut Vec<u8>, value: &T) -> Result<(
 pub fn deserialize<'de, T: Deserialize<'de>>(slice: &'de [u8]) -> Result<T> {
     T::deserialize(&mut BinaryDeserializer::new(slice))
 }
+
+/// Serializes `value` to `writer` as it goes. Values whose length prefix is not known up front,
+/// such as sequences of unknown length and [`AnySerde`](crate::AnySerde) contents, are buffered
+/// and written once complete. Writes are unbuffered, so a socket or file should be wrapped in a
+/// [`BufWriter`](std::io::BufWriter).
+pub fn serialize_to_writer<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<()> {
+    value.serialize(BinarySerializer::from_output(&mut Writer(writer)))
+}
+
+/// Deserializes a value from `reader` without reading past its end. Reads are unbuffered, so a
+/// socket or file should be wrapped in a [`BufReader`](std::io::BufReader).
+pub fn deserialize_from_reader<R: Read, T: for<'de> Deserialize<'de>>(reader: R) -> Result<T> {
+    T::deserialize(&mut BinaryDeserializer::from_reader(reader))
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_alloc: u64::MAX,
+            max_len: u64::MAX,
+            max_depth: usize::MAX,
+        }
+    }
+}
+
+impl Options {
+    /// Like [`deserialize`], but fails with [`Error::LimitExceeded`] instead of exceeding a limit.
+    pub fn deserialize<'de, T: Deserialize<'de>>(&self, slice: &'de [u8]) -> Result<T> {
+        T::deserialize(&mut BinaryDeserializer::new(slice).with_options(*self))
+    }
+    /// Like [`deserialize_from_reader`], but fails with [`Error::LimitExceeded`] instead of
+    /// exceeding a limit.
+    pub fn deserialize_from_reader<R: Read, T: for<'de> Deserialize<'de>>(
+        &self,
+        reader: R,
+    ) -> Result<T> {
+        T::deserialize(&mut BinaryDeserializer::from_reader(reader).with_options(*self))
+    }
+}
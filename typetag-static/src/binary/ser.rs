@@ -1,3 +1,5 @@
+use std::io;
+
 use serde::ser::{
     SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
     SerializeTupleStruct, SerializeTupleVariant,
@@ -6,50 +8,109 @@ use serde::{Serialize, Serializer};
 
 use crate::binary::{Error, Result};
 
+/// The destination of a [`BinarySerializer`].
+pub(crate) trait Output {
+    fn write(&mut self, bytes: &[u8]) -> Result<()>;
+    /// The output as a vector, if length prefixes can be filled in after their contents.
+    fn as_vec(&mut self) -> Option<&mut Vec<u8>> { None }
+}
+
+impl Output for Vec<u8> {
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+    fn as_vec(&mut self) -> Option<&mut Vec<u8>> { Some(self) }
+}
+
+pub(crate) struct Writer<W>(pub(crate) W);
+
+impl<W: io::Write> Output for Writer<W> {
+    fn write(&mut self, bytes: &[u8]) -> Result<()> { Ok(self.0.write_all(bytes)?) }
+}
+
 pub struct BinarySerializer<'a> {
-    vec: &'a mut Vec<u8>,
+    output: &'a mut dyn Output,
 }
 
+/// Writes the elements of a sequence or map after their count. The count is filled in
+/// afterwards when writing to a vector, written up front when the length is known, and
+/// otherwise written with the elements once they have all been buffered.
 pub struct BinaryCountSerializer<'a> {
     serializer: BinarySerializer<'a>,
-    count_index: usize,
+    count_index: Option<usize>,
+    buffer: Option<Vec<u8>>,
+    expected: Option<usize>,
     count: usize,
 }
 
 impl<'a> BinarySerializer<'a> {
-    pub fn new(vec: &'a mut Vec<u8>) -> Self { BinarySerializer { vec } }
+    pub fn new(vec: &'a mut Vec<u8>) -> Self { BinarySerializer { output: vec } }
+    pub(crate) fn from_output(output: &'a mut dyn Output) -> Self { BinarySerializer { output } }
     pub fn reborrow<'b>(&'b mut self) -> BinarySerializer<'b> {
         BinarySerializer {
-            vec: &mut *self.vec,
+            output: &mut *self.output,
         }
     }
-    pub fn serialize_raw(&mut self, bytes: &[u8]) -> Result<()> {
-        self.vec.extend_from_slice(bytes);
-        Ok(())
-    }
-    fn serialize_counted(mut self) -> Result<BinaryCountSerializer<'a>> {
-        let count_index = self.vec.len();
-        self.vec.resize(self.vec.len() + 8, 0);
-        Ok(BinaryCountSerializer {
+    pub fn serialize_raw(&mut self, bytes: &[u8]) -> Result<()> { self.output.write(bytes) }
+    fn serialize_counted(mut self, len: Option<usize>) -> Result<BinaryCountSerializer<'a>> {
+        let mut counter = BinaryCountSerializer {
             serializer: self,
-            count_index,
+            count_index: None,
+            buffer: None,
+            expected: None,
             count: 0,
-        })
+        };
+        if let Some(vec) = counter.serializer.output.as_vec() {
+            counter.count_index = Some(vec.len());
+            vec.resize(vec.len() + 8, 0);
+        } else if let Some(len) = len {
+            counter.serializer.reborrow().serialize_u64(len as u64)?;
+            counter.expected = Some(len);
+        } else {
+            counter.buffer = Some(vec![]);
+        }
+        Ok(counter)
     }
     pub fn serialize_with_length<T: Serialize>(mut self, element: &T) -> Result<()> {
-        let mut counter = self.serialize_counted()?;
-        let start = counter.serializer.vec.len();
-        element.serialize(counter.serializer.reborrow())?;
-        counter.count = counter.serializer.vec.len() - start;
-        counter.end_count()?;
+        if let Some(vec) = self.output.as_vec() {
+            let start = vec.len() + 8;
+            vec.resize(start, 0);
+            element.serialize(self.reborrow())?;
+            let vec = self.output.as_vec().unwrap();
+            let length = (vec.len() - start) as u64;
+            vec[start - 8..start].copy_from_slice(&length.to_le_bytes());
+        } else {
+            let mut buffer = vec![];
+            element.serialize(BinarySerializer::new(&mut buffer))?;
+            self.reborrow().serialize_u64(buffer.len() as u64)?;
+            self.serialize_raw(&buffer)?;
+        }
         Ok(())
     }
 }
 
 impl<'a> BinaryCountSerializer<'a> {
-    fn end_count(self) -> Result<()> {
-        self.serializer.vec[self.count_index..self.count_index + 8]
-            .copy_from_slice(&(self.count as u64).to_le_bytes());
+    fn target<'b>(&'b mut self) -> BinarySerializer<'b> {
+        match &mut self.buffer {
+            Some(buffer) => BinarySerializer::new(buffer),
+            None => self.serializer.reborrow(),
+        }
+    }
+    fn end_count(mut self) -> Result<()> {
+        if let Some(count_index) = self.count_index {
+            let vec = self.serializer.output.as_vec().unwrap();
+            vec[count_index..count_index + 8].copy_from_slice(&(self.count as u64).to_le_bytes());
+        } else if let Some(buffer) = self.buffer.take() {
+            self.serializer
+                .reborrow()
+                .serialize_u64(self.count as u64)?;
+            self.serializer.serialize_raw(&buffer)?;
+        } else if let Some(expected) = self.expected {
+            if expected != self.count {
+                return Err(Error::BadLength);
+            }
+        }
         Ok(())
     }
 }
@@ -61,9 +122,8 @@ impl<'a> SerializeSeq for BinaryCountSerializer<'a> {
     where
         T: Serialize,
     {
-        value.serialize(self.serializer.reborrow())?;
         self.count += 1;
-        Ok(())
+        value.serialize(self.target())
     }
     fn end(self) -> Result<()> { self.end_count() }
 }
@@ -111,15 +171,14 @@ impl<'a> SerializeMap for BinaryCountSerializer<'a> {
     where
         T: Serialize,
     {
-        key.serialize(self.serializer.reborrow())?;
         self.count += 1;
-        Ok(())
+        key.serialize(self.target())
     }
     fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<()>
     where
         T: Serialize,
     {
-        value.serialize(self.serializer.reborrow())
+        value.serialize(self.target())
     }
     fn end(self) -> Result<()> { self.end_count() }
 }
@@ -220,7 +279,7 @@ impl<'a> Serializer for BinarySerializer<'a> {
         Ok(())
     }
     fn serialize_seq(mut self, len: Option<usize>) -> Result<BinaryCountSerializer<'a>> {
-        self.serialize_counted()
+        self.serialize_counted(len)
     }
     fn serialize_tuple(mut self, len: usize) -> Result<Self> { Ok(self) }
     fn serialize_tuple_struct(mut self, name: &'static str, len: usize) -> Result<Self> { Ok(self) }
@@ -235,7 +294,7 @@ impl<'a> Serializer for BinarySerializer<'a> {
         Ok(self)
     }
     fn serialize_map(mut self, len: Option<usize>) -> Result<BinaryCountSerializer<'a>> {
-        self.serialize_counted()
+        self.serialize_counted(len)
     }
     fn serialize_struct(mut self, name: &'static str, len: usize) -> Result<Self> { Ok(self) }
     fn serialize_struct_variant(
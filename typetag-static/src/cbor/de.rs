@@ -0,0 +1,317 @@
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    DeserializeSeed, EnumAccess, Error as _, IntoDeserializer, Unexpected, VariantAccess, Visitor,
+};
+use serde::Deserializer;
+
+use crate::cbor::{Error, Result, Value, BIGNEG, BIGPOS, TAGGED_VARIANT};
+
+/// A [`Deserializer`] that consumes a [`Value`].
+#[derive(Debug)]
+pub struct CborDeserializer(Value);
+
+/// The [`EnumAccess`] for a variant with content, written as a map from the variant name.
+struct EnumDeserializer {
+    variant: Value,
+    value: Option<Value>,
+}
+
+impl CborDeserializer {
+    pub fn new(value: Value) -> Self { CborDeserializer(value) }
+    /// The value without any tags around it.
+    fn untagged(self) -> Value {
+        let mut value = self.0;
+        while let Value::Tag(_, inner) = value {
+            value = *inner;
+        }
+        value
+    }
+    fn integer<N: TryFrom<i128> + TryFrom<u128>>(self, expected: &'static str) -> Result<N> {
+        let bad = |value: &Value| Error::invalid_type(Unexpected::from(value), &expected);
+        let raw = |value: &Value| match value {
+            Value::Bytes(bytes) if bytes.len() <= 16 => {
+                let mut buffer = [0u8; 16];
+                buffer[16 - bytes.len()..].copy_from_slice(bytes);
+                Ok(u128::from_be_bytes(buffer))
+            }
+            _ => Err(bad(value)),
+        };
+        match &self.0 {
+            Value::Integer(x) => N::try_from(i128::from(*x)).map_err(|_| bad(&self.0)),
+            Value::Tag(BIGPOS, inner) => N::try_from(raw(inner)?).map_err(|_| bad(&self.0)),
+            Value::Tag(BIGNEG, inner) => i128::try_from(raw(inner)?)
+                .ok()
+                .and_then(|x| N::try_from(x ^ !0).ok())
+                .ok_or_else(|| bad(&self.0)),
+            value => Err(bad(value)),
+        }
+    }
+}
+
+fn visit_array<'de, V: Visitor<'de>>(items: Vec<Value>, visitor: V) -> Result<V::Value> {
+    let mut seq = SeqDeserializer::new(items.into_iter().map(CborDeserializer));
+    let result = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(result)
+}
+
+fn visit_map<'de, V: Visitor<'de>>(entries: Vec<(Value, Value)>, visitor: V) -> Result<V::Value> {
+    let mut map = MapDeserializer::new(
+        entries
+            .into_iter()
+            .map(|(k, v)| (CborDeserializer(k), CborDeserializer(v))),
+    );
+    let result = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(result)
+}
+
+macro_rules! deserialize_integer {
+    ($($method:ident => $visit:ident($ty:ty),)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                visitor.$visit(self.integer::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for CborDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Integer(x) => {
+                if let Ok(x) = u64::try_from(x) {
+                    visitor.visit_u64(x)
+                } else if let Ok(x) = i64::try_from(x) {
+                    visitor.visit_i64(x)
+                } else {
+                    visitor.visit_i128(i128::from(x))
+                }
+            }
+            Value::Bytes(x) => visitor.visit_byte_buf(x),
+            Value::Float(x) => visitor.visit_f64(x),
+            Value::Text(x) => visitor.visit_string(x),
+            Value::Bool(x) => visitor.visit_bool(x),
+            Value::Null => visitor.visit_none(),
+            // Passed as ciborium does, so that a tagged value deserializes as a `Value::Tag`.
+            Value::Tag(tag, inner) => visitor.visit_enum(EnumDeserializer {
+                variant: Value::Text(TAGGED_VARIANT.to_string()),
+                value: Some(Value::Array(vec![Value::Integer(tag.into()), *inner])),
+            }),
+            Value::Array(x) => visit_array(x, visitor),
+            Value::Map(x) => visit_map(x, visitor),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Bool(x) => visitor.visit_bool(x),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    deserialize_integer! {
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_i128 => visit_i128(i128),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_u128 => visit_u128(u128),
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Float(x) => visitor.visit_f64(x),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Text(x) if x.chars().count() == 1 => {
+                visitor.visit_char(x.chars().next().unwrap())
+            }
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Text(x) => visitor.visit_string(x),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Bytes(x) => visitor.visit_byte_buf(x),
+            Value::Array(x) => visit_array(x, visitor),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(CborDeserializer(value)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Null => visitor.visit_unit(),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Array(x) => visit_array(x, visitor),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Map(x) => visit_map(x, visitor),
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.untagged() {
+            Value::Text(variant) => visitor.visit_enum(EnumDeserializer {
+                variant: Value::Text(variant),
+                value: None,
+            }),
+            Value::Map(entries) if entries.len() == 1 => {
+                let (variant, value) = entries.into_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            value => Err(Error::invalid_type(Unexpected::from(&value), &visitor)),
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool { false }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for CborDeserializer {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self { self }
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = Self;
+    fn variant_seed<V: DeserializeSeed<'de>>(mut self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = std::mem::replace(&mut self.variant, Value::Null);
+        Ok((seed.deserialize(CborDeserializer(variant))?, self))
+    }
+}
+
+impl EnumDeserializer {
+    fn content(self) -> Result<CborDeserializer> {
+        self.value
+            .map(CborDeserializer)
+            .ok_or_else(|| Error::invalid_type(Unexpected::UnitVariant, &"variant with content"))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    fn unit_variant(self) -> Result<()> {
+        match self.value {
+            None => Ok(()),
+            Some(value) => Err(Error::invalid_type(
+                Unexpected::from(&value),
+                &"unit variant",
+            )),
+        }
+    }
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.content()?)
+    }
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        self.content()?.deserialize_seq(visitor)
+    }
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.content()?.deserialize_map(visitor)
+    }
+}
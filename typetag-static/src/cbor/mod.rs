@@ -0,0 +1,175 @@
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use catalog::Registry;
+pub use ciborium::value::Value;
+pub use de::CborDeserializer;
+pub use ser::CborSerializer;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::registry::Format;
+use crate::tagged::{self, AnyTagged, Impls, TaggedDeserializer, TaggedFormat, Unknown};
+use crate::version::UpgradeStep;
+use crate::{AnyDeserializer, AnySerde, AnySerializer, ArcAnySerde, BoxAnySerde};
+
+mod de;
+mod ser;
+
+/// The CBOR [`TaggedFormat`]. Values are converted to and from a [`Value`], which is encoded by
+/// [`ciborium`].
+pub struct Cbor;
+
+/// A struct created by [`AnySerde`](crate::AnySerde) when deserializing a CBOR value with
+/// an unrecognized tag. Ensures that such values can safely be re-serialized without losing data.
+pub type UnknownCbor = Unknown<Cbor>;
+
+/// A type registered with [`IMPLS`].
+pub type AnyCbor = dyn AnyTagged<Cbor>;
+
+#[derive(Debug)]
+pub enum Error {
+    Custom(String),
+    Io(io::Error),
+    /// The input is not well-formed CBOR at this offset.
+    Syntax(usize),
+    RecursionLimitExceeded,
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// The tags for integers that do not fit in 64 bits.
+const BIGPOS: u64 = 2;
+const BIGNEG: u64 = 3;
+
+/// The names with which [`ciborium`] passes a [`Value::Tag`] through serde.
+const TAG_NAME: &str = "@@TAG@@";
+const TAGGED_VARIANT: &str = "@@TAGGED@@";
+const UNTAGGED_VARIANT: &str = "@@UNTAGGED@@";
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Custom(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Syntax(offset) => write!(f, "Syntax error at offset {}", offset),
+            Error::RecursionLimitExceeded => write!(f, "Recursion limit exceeded"),
+        }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Error::Custom(format!("{}", msg))
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Error::Custom(format!("{}", msg))
+    }
+}
+
+impl From<ciborium::ser::Error<io::Error>> for Error {
+    fn from(e: ciborium::ser::Error<io::Error>) -> Self {
+        match e {
+            ciborium::ser::Error::Io(e) => Error::Io(e),
+            ciborium::ser::Error::Value(e) => Error::Custom(e),
+        }
+    }
+}
+
+impl From<ciborium::de::Error<io::Error>> for Error {
+    fn from(e: ciborium::de::Error<io::Error>) -> Self {
+        match e {
+            ciborium::de::Error::Io(e) => Error::Io(e),
+            ciborium::de::Error::Syntax(offset) => Error::Syntax(offset),
+            ciborium::de::Error::Semantic(_, e) => Error::Custom(e),
+            ciborium::de::Error::RecursionLimitExceeded => Error::RecursionLimitExceeded,
+        }
+    }
+}
+
+impl TaggedFormat for Cbor {
+    const FORMAT: Format = Format::Cbor;
+    type Value = Value;
+    type Error = Error;
+    type Ok = Value;
+    type Serializer<'a, 'b>
+        = CborSerializer
+    where
+        'b: 'a;
+    type Deserializer<'a, 'de>
+        = CborDeserializer
+    where
+        'de: 'a;
+    fn impls() -> &'static Impls<Self> { &IMPLS }
+    fn deserialize_old<'a, 'de>(
+        step: &UpgradeStep,
+        deserializer: CborDeserializer,
+    ) -> Result<Box<dyn Any>> {
+        (step.deserialize_cbor)(deserializer)
+    }
+}
+
+impl AnySerializer for CborSerializer {
+    fn serialize_dyn_impl(self, value: &dyn AnySerde) -> Result<Value> {
+        tagged::serialize_dyn::<Cbor>(self, value)
+    }
+}
+
+impl<'de> TaggedDeserializer<'de, Cbor> for CborDeserializer {
+    fn deserialize_box_tagged(self, imp: &'static AnyCbor) -> Result<BoxAnySerde> {
+        imp.deserialize_box_tagged(self)
+    }
+    fn deserialize_arc_tagged(self, imp: &'static AnyCbor) -> Result<ArcAnySerde> {
+        imp.deserialize_arc_tagged(self)
+    }
+}
+
+impl<'de> AnyDeserializer<'de> for CborDeserializer {
+    fn deserialize_box_impl(self) -> Result<BoxAnySerde> {
+        tagged::deserialize_dyn::<Cbor, _, _>(self)
+    }
+    fn deserialize_arc_impl(self) -> Result<ArcAnySerde> {
+        tagged::deserialize_dyn::<Cbor, _, _>(self)
+    }
+}
+
+pub(crate) type DeserializeAny = fn(CborDeserializer) -> Result<Box<dyn Any>>;
+
+pub(crate) fn deserialize_any<T: DeserializeOwned + 'static>(
+    deserializer: CborDeserializer,
+) -> Result<Box<dyn Any>> {
+    Ok(Box::new(T::deserialize(deserializer)?))
+}
+
+pub fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut output = vec![];
+    serialize_into(&mut output, value)?;
+    Ok(output)
+}
+
+pub fn serialize_into<T: Serialize>(output: &mut Vec<u8>, value: &T) -> Result<()> {
+    let value = value.serialize(CborSerializer)?;
+    ciborium::into_writer(&value, output)?;
+    Ok(())
+}
+
+/// Deserializes a `T`. The input is decoded into a [`Value`] first, so `T` cannot borrow from
+/// `slice`.
+pub fn deserialize<T: DeserializeOwned>(slice: &[u8]) -> Result<T> {
+    let value: Value = ciborium::from_reader(slice)?;
+    T::deserialize(CborDeserializer::new(value))
+}
+
+pub static IMPLS: Registry<Impls<Cbor>> = Registry::new();
@@ -0,0 +1,279 @@
+use ciborium::value::Integer;
+use serde::ser::{
+    Error as _, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Serialize, Serializer};
+
+use crate::cbor::{
+    Error, Result, Value, BIGNEG, BIGPOS, TAGGED_VARIANT, TAG_NAME, UNTAGGED_VARIANT,
+};
+
+/// A [`Serializer`] that produces a [`Value`].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CborSerializer;
+
+/// Collects the elements of a sequence, tuple, or [`Value::Tag`].
+#[derive(Debug)]
+pub struct SeqSerializer {
+    variant: Option<&'static str>,
+    tagged: bool,
+    items: Vec<Value>,
+}
+
+/// Collects the entries of a map or struct.
+#[derive(Debug)]
+pub struct MapSerializer {
+    variant: Option<&'static str>,
+    entries: Vec<(Value, Value)>,
+    key: Option<Value>,
+}
+
+/// Wraps the content of an enum variant in a map from the variant name, as [`ciborium`] does.
+fn variant(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        None => value,
+        Some(variant) => Value::Map(vec![(Value::Text(variant.to_string()), value)]),
+    }
+}
+
+/// A big integer tagged `tag`, stored as big-endian bytes without leading zeros.
+fn big(tag: u64, raw: u128) -> Value {
+    let bytes = raw.to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    Value::Tag(tag, Box::new(Value::Bytes(bytes[start..].to_vec())))
+}
+
+impl Serializer for CborSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> { Ok(Value::Bool(v)) }
+    fn serialize_i8(self, v: i8) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_i16(self, v: i16) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_i32(self, v: i32) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_i64(self, v: i64) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        Ok(match Integer::try_from(v) {
+            Ok(v) => Value::Integer(v),
+            Err(_) if v.is_negative() => big(BIGNEG, v as u128 ^ !0),
+            Err(_) => big(BIGPOS, v as u128),
+        })
+    }
+    fn serialize_u8(self, v: u8) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_u16(self, v: u16) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_u32(self, v: u32) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_u64(self, v: u64) -> Result<Value> { Ok(Value::Integer(v.into())) }
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        Ok(match Integer::try_from(v) {
+            Ok(v) => Value::Integer(v),
+            Err(_) => big(BIGPOS, v),
+        })
+    }
+    fn serialize_f32(self, v: f32) -> Result<Value> { Ok(Value::Float(v.into())) }
+    fn serialize_f64(self, v: f64) -> Result<Value> { Ok(Value::Float(v)) }
+    fn serialize_char(self, v: char) -> Result<Value> { Ok(Value::Text(v.to_string())) }
+    fn serialize_str(self, v: &str) -> Result<Value> { Ok(Value::Text(v.to_string())) }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> { Ok(Value::Bytes(v.to_vec())) }
+    fn serialize_none(self) -> Result<Value> { Ok(Value::Null) }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Value> { Ok(Value::Null) }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value> { Ok(Value::Null) }
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        Ok(Value::Text(variant.to_string()))
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        let value = value.serialize(self)?;
+        if name == TAG_NAME && variant == UNTAGGED_VARIANT {
+            Ok(value)
+        } else {
+            Ok(self::variant(Some(variant), value))
+        }
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer> {
+        Ok(SeqSerializer {
+            variant: None,
+            tagged: false,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer> { self.serialize_seq(Some(len)) }
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer> {
+        let tagged = name == TAG_NAME && variant == TAGGED_VARIANT;
+        Ok(SeqSerializer {
+            variant: (!tagged).then_some(variant),
+            tagged,
+            items: Vec::with_capacity(len),
+        })
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer> {
+        Ok(MapSerializer {
+            variant: None,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<MapSerializer> {
+        self.serialize_map(Some(len))
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer> {
+        Ok(MapSerializer {
+            variant: Some(variant),
+            entries: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+    fn is_human_readable(&self) -> bool { false }
+}
+
+impl SeqSerializer {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.items.push(value.serialize(CborSerializer)?);
+        Ok(())
+    }
+    fn finish(self) -> Result<Value> {
+        if !self.tagged {
+            return Ok(variant(self.variant, Value::Array(self.items)));
+        }
+        match <[Value; 2]>::try_from(self.items) {
+            Ok([Value::Integer(tag), value]) => Ok(Value::Tag(
+                u64::try_from(tag).map_err(|_| Error::custom("CBOR tag out of range"))?,
+                Box::new(value),
+            )),
+            _ => Err(Error::custom("expected a CBOR tag and a value")),
+        }
+    }
+}
+
+impl SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> { self.finish() }
+}
+
+impl SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> { self.finish() }
+}
+
+impl SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> { self.finish() }
+}
+
+impl SerializeTupleVariant for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> { self.finish() }
+}
+
+impl MapSerializer {
+    fn field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.entries.push((
+            Value::Text(key.to_string()),
+            value.serialize(CborSerializer)?,
+        ));
+        Ok(())
+    }
+    fn finish(self) -> Result<Value> { Ok(variant(self.variant, Value::Map(self.entries))) }
+}
+
+impl SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(CborSerializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::custom("serialize_value called before serialize_key"))?;
+        self.entries.push((key, value.serialize(CborSerializer)?));
+        Ok(())
+    }
+    fn end(self) -> Result<Value> { self.finish() }
+}
+
+impl SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+    fn end(self) -> Result<Value> { self.finish() }
+}
+
+impl SerializeStructVariant for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+    fn end(self) -> Result<Value> { self.finish() }
+}
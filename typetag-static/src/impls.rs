@@ -5,7 +5,13 @@ use catalog::register;
 macro_rules! impls {
     ($($id:ty;)*) => {
         $(
-            impl_any_serde!($id, {stringify!($id)}, crate::json::IMPLS, crate::binary::IMPLS);
+            impl_any_serde!(
+                $id,
+                {stringify!($id)},
+                crate::json::IMPLS,
+                crate::binary::IMPLS,
+                crate::cbor::IMPLS
+            );
         )*
     }
 }
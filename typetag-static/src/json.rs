@@ -1,208 +1,80 @@
-use std::any::{type_name, Any, TypeId};
-use std::collections::HashMap;
-use std::fmt;
-use std::fmt::Formatter;
-use std::marker::PhantomData;
-use std::sync::Arc;
+use std::any::Any;
 
-use catalog::{Builder, BuilderFrom, Registry};
-use lazy_static::lazy_static;
-use serde::de::{DeserializeSeed, Error, MapAccess, Visitor};
-use serde::ser::SerializeMap;
-use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use catalog::Registry;
+use serde::{Deserialize, Serialize};
 use serde_json::de::SliceRead;
 
-use crate::tag::{HasTypeTag, TypeTag};
+use crate::registry::Format;
+use crate::tagged::{self, AnyTagged, Impls, TaggedDeserializer, TaggedFormat, Unknown};
+use crate::version::UpgradeStep;
 use crate::{AnyDeserializer, AnySerde, AnySerializerDefault, ArcAnySerde, BoxAnySerde};
 
-//use crate::util::AnySingleton;
+/// The JSON [`TaggedFormat`].
+pub struct Json;
 
 /// A struct created by [`AnySerde`](crate::AnySerde) when deserializing a JSON value with
 /// an unrecognized tag. Ensures that such values can safely be re-serialized without losing data.
-#[derive(Clone, Debug)]
-pub struct UnknownJson {
-    tag: String,
-    value: serde_json::Value,
-}
+pub type UnknownJson = Unknown<Json>;
+
+/// A type registered with [`IMPLS`].
+pub type AnyJson = dyn AnyTagged<Json>;
 
 type JsonSerializer<'b> = serde_json::Serializer<&'b mut Vec<u8>>;
 type JsonDeserializer<'de> = serde_json::Deserializer<SliceRead<'de>>;
 
-impl<'a, 'b> AnySerializerDefault for &'a mut JsonSerializer<'b> {
-    fn serialize_dyn(self, value: &dyn AnySerde) -> Result<Self::Ok, Self::Error> {
-        if let Some(unknown) = value.downcast_ref::<UnknownJson>() {
-            let mut map = self.serialize_map(Some(1))?;
-            map.serialize_entry(&unknown.tag, &unknown.value)?;
-            map.end()?;
-            Ok(())
-        } else {
-            IMPLS
-                .by_type_id
-                .get(&value.type_id())
-                .ok_or(<Self::Error as ser::Error>::custom("Missing AnyJson impl"))?
-                .serialize_json(self, value)
-        }
-    }
-}
-
-trait AnyJsonDeserializer<'de>: Deserializer<'de> {
-    fn deserialize_box_json(self, imp: &'static dyn AnyJson) -> Result<BoxAnySerde, Self::Error>;
-    fn deserialize_arc_json(self, imp: &'static dyn AnyJson) -> Result<ArcAnySerde, Self::Error>;
-}
-
-impl<'de, T: Deserializer<'de>> AnyJsonDeserializer<'de> for T {
-    default fn deserialize_box_json(
-        self,
-        imp: &'static dyn AnyJson,
-    ) -> Result<BoxAnySerde, Self::Error> {
-        panic!(
-            "Missing impl of AnyJsonDeserializer for {}",
-            type_name::<T>()
-        );
-    }
-    default fn deserialize_arc_json(
-        self,
-        imp: &'static dyn AnyJson,
-    ) -> Result<ArcAnySerde, Self::Error> {
-        panic!(
-            "Missing impl of AnyJsonDeserializer for {}",
-            type_name::<T>()
-        );
+impl TaggedFormat for Json {
+    const FORMAT: Format = Format::Json;
+    type Value = serde_json::Value;
+    type Error = serde_json::Error;
+    type Ok = ();
+    type Serializer<'a, 'b>
+        = &'a mut JsonSerializer<'b>
+    where
+        'b: 'a;
+    type Deserializer<'a, 'de>
+        = &'a mut JsonDeserializer<'de>
+    where
+        'de: 'a;
+    fn impls() -> &'static Impls<Self> { &IMPLS }
+    fn deserialize_old<'a, 'de>(
+        step: &UpgradeStep,
+        deserializer: &'a mut JsonDeserializer<'de>,
+    ) -> Result<Box<dyn Any>, serde_json::Error> {
+        (step.deserialize_json)(deserializer)
     }
 }
 
-impl<'a, 'de> AnyJsonDeserializer<'de> for &'a mut JsonDeserializer<'de> {
-    fn deserialize_box_json(self, imp: &'static dyn AnyJson) -> Result<BoxAnySerde, Self::Error> {
-        imp.deserialize_box_json(self)
-    }
-    fn deserialize_arc_json(self, imp: &'static dyn AnyJson) -> Result<ArcAnySerde, Self::Error> {
-        imp.deserialize_arc_json(self)
+impl<'a, 'b> AnySerializerDefault for &'a mut JsonSerializer<'b> {
+    fn serialize_dyn(self, value: &dyn AnySerde) -> Result<Self::Ok, Self::Error> {
+        tagged::serialize_dyn::<Json>(self, value)
     }
 }
 
-struct SeedBox(&'static dyn AnyJson);
-
-struct SeedArc(&'static dyn AnyJson);
-
-impl<'de> DeserializeSeed<'de> for SeedBox {
-    type Value = BoxAnySerde;
-    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        deserializer.deserialize_box_json(self.0)
+impl<'a, 'de> TaggedDeserializer<'de, Json> for &'a mut JsonDeserializer<'de> {
+    fn deserialize_box_tagged(self, imp: &'static AnyJson) -> Result<BoxAnySerde, Self::Error> {
+        imp.deserialize_box_tagged(self)
     }
-}
-
-impl<'de> DeserializeSeed<'de> for SeedArc {
-    type Value = ArcAnySerde;
-    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        deserializer.deserialize_arc_json(self.0)
+    fn deserialize_arc_tagged(self, imp: &'static AnyJson) -> Result<ArcAnySerde, Self::Error> {
+        imp.deserialize_arc_tagged(self)
     }
 }
 
 impl<'a, 'de> AnyDeserializer<'de> for &'a mut JsonDeserializer<'de> {
     fn deserialize_box_impl(self) -> Result<BoxAnySerde, Self::Error> {
-        struct Vis;
-        impl<'de> Visitor<'de> for Vis {
-            type Value = BoxAnySerde;
-            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
-                write!(formatter, "a map with a typetag name key and dynamic value")
-            }
-            fn visit_map<A: MapAccess<'de>>(self, mut seq: A) -> Result<BoxAnySerde, A::Error> {
-                let typ = seq
-                    .next_key::<&'de str>()?
-                    .ok_or(<A::Error as de::Error>::custom("missing key"))?;
-                if let Some(imp) = IMPLS.by_type_tag_name.get(typ) {
-                    seq.next_value_seed(SeedBox(&**imp))
-                } else {
-                    Ok(Box::new(UnknownJson {
-                        tag: typ.to_string(),
-                        value: seq.next_value::<serde_json::Value>()?,
-                    }))
-                }
-            }
-        }
-        self.deserialize_map(Vis)
+        tagged::deserialize_dyn::<Json, _, _>(self)
     }
     fn deserialize_arc_impl(self) -> Result<ArcAnySerde, Self::Error> {
-        struct Vis;
-        impl<'de> Visitor<'de> for Vis {
-            type Value = ArcAnySerde;
-            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
-                write!(formatter, "a map with a typetag name key and dynamic value")
-            }
-            fn visit_map<A: MapAccess<'de>>(self, mut seq: A) -> Result<ArcAnySerde, A::Error> {
-                let typ = seq
-                    .next_key::<&'de str>()?
-                    .ok_or(<A::Error as de::Error>::custom("missing key"))?;
-                if let Some(imp) = IMPLS.by_type_tag_name.get(typ) {
-                    seq.next_value_seed(SeedArc(&**imp))
-                } else {
-                    Ok(ArcAnySerde::new(UnknownJson {
-                        tag: typ.to_string(),
-                        value: seq.next_value::<serde_json::Value>()?,
-                    }))
-                }
-            }
-        }
-        self.deserialize_map(Vis)
+        tagged::deserialize_dyn::<Json, _, _>(self)
     }
 }
 
-pub trait AnyJson: 'static + Send + Sync {
-    fn inner_type_tag(&self) -> &'static TypeTag;
-    fn inner_type_id(&self) -> TypeId;
-    fn serialize_json<'a, 'b>(
-        &self,
-        serializer: &'a mut JsonSerializer<'b>,
-        value: &dyn AnySerde,
-    ) -> Result<(), serde_json::Error>;
-    fn deserialize_box_json<'a, 'de>(
-        &self,
-        deserializer: &'a mut JsonDeserializer<'de>,
-    ) -> Result<BoxAnySerde, serde_json::Error>;
-    fn deserialize_arc_json<'a, 'de>(
-        &self,
-        deserializer: &'a mut JsonDeserializer<'de>,
-    ) -> Result<ArcAnySerde, serde_json::Error>;
-}
+pub(crate) type DeserializeAny =
+    for<'a, 'de> fn(&'a mut JsonDeserializer<'de>) -> Result<Box<dyn Any>, serde_json::Error>;
 
-impl<T: Serialize + for<'de> Deserialize<'de> + 'static + HasTypeTag + AnySerde> AnyJson
-    for PhantomData<T>
-{
-    fn inner_type_tag(&self) -> &'static TypeTag { T::type_tag() }
-    fn inner_type_id(&self) -> TypeId { TypeId::of::<T>() }
-    fn serialize_json<'a, 'b>(
-        &self,
-        serializer: &'a mut JsonSerializer<'b>,
-        value: &dyn AnySerde,
-    ) -> Result<(), serde_json::Error> {
-        let mut struc = serializer.serialize_map(Some(1))?;
-        let value = value
-            .downcast_ref::<T>()
-            .ok_or(<serde_json::Error as ser::Error>::custom(
-                "Bad type passed to AnyJson",
-            ))?;
-        struc.serialize_entry(self.inner_type_tag().name, value)?;
-        struc.end()?;
-        Ok(())
-    }
-    fn deserialize_box_json<'a, 'de>(
-        &self,
-        deserializer: &'a mut JsonDeserializer<'de>,
-    ) -> Result<BoxAnySerde, serde_json::Error> {
-        Ok(Box::new(T::deserialize(deserializer)?))
-    }
-    fn deserialize_arc_json<'a, 'de>(
-        &self,
-        deserializer: &'a mut JsonDeserializer<'de>,
-    ) -> Result<ArcAnySerde, serde_json::Error> {
-        Ok(ArcAnySerde::new(T::deserialize(deserializer)?))
-    }
+pub(crate) fn deserialize_any<'a, 'de, T: for<'de2> Deserialize<'de2> + 'static>(
+    deserializer: &'a mut JsonDeserializer<'de>,
+) -> Result<Box<dyn Any>, serde_json::Error> {
+    Ok(Box::new(T::deserialize(deserializer)?))
 }
 
 pub fn serialize<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
@@ -221,41 +93,4 @@ pub fn deserialize<'de, T: Deserialize<'de>>(slice: &'de [u8]) -> Result<T, serd
     serde_json::from_slice(slice)
 }
 
-impl AnySerde for UnknownJson {
-    fn clone_box(&self) -> BoxAnySerde { Box::new(self.clone()) }
-
-    fn inner_type_name(&self) -> &'static str { "typetag_static::json::UnknownJson" }
-}
-
-pub struct Impls {
-    by_type_id: HashMap<TypeId, &'static dyn AnyJson>,
-    by_type_tag_name: HashMap<&'static str, &'static dyn AnyJson>,
-}
-
-impl Builder for Impls {
-    type Output = Self;
-
-    fn new() -> Self {
-        Impls {
-            by_type_id: HashMap::new(),
-            by_type_tag_name: HashMap::new(),
-        }
-    }
-
-    fn build(self) -> Self::Output { self }
-}
-
-impl BuilderFrom<&'static dyn AnyJson> for Impls {
-    fn insert(&mut self, element: &'static dyn AnyJson) {
-        assert!(self
-            .by_type_id
-            .insert(element.inner_type_id(), element)
-            .is_none());
-        assert!(self
-            .by_type_tag_name
-            .insert(element.inner_type_tag().name, element)
-            .is_none());
-    }
-}
-
-pub static IMPLS: Registry<Impls> = Registry::new();
+pub static IMPLS: Registry<Impls<Json>> = Registry::new();
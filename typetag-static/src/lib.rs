@@ -1,6 +1,6 @@
 #![feature(specialization, never_type)]
 #![feature(coerce_unsized)]
-#![feature(seek_stream_len)]
+#![feature(generic_associated_types)]
 #![allow(
     incomplete_features,
     unused_variables,
@@ -45,29 +45,58 @@ use std::sync::Arc;
 use catalog::register;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::binary::UnknownBinary;
+use crate::cbor::UnknownCbor;
+use crate::json::UnknownJson;
+use crate::tag::TypeTagHash;
+
 #[macro_use]
 mod macros;
 /// A serialization format similar to [`bincode`](https://crates.io/crates/bincode) that supports [`AnySerde`](crate::AnySerde).
 pub mod binary;
+/// Support for CBOR encoding.
+pub mod cbor;
 /// Support for JSON encoding.
 pub mod json;
+/// Introspection and validation of the types registered with each format.
+pub mod registry;
 /// Support for assigning stable identifiers to types.
 pub mod tag;
+/// Support shared by formats that write a value as a map from its tag name to the value.
+pub mod tagged;
+/// Support for serializing trait objects of other traits that extend [`AnySerde`].
+pub mod traits;
+/// Conversion of [`BoxAnySerde`] values between formats.
+pub mod transcode;
+/// Support for reading values written by older versions of a type.
+pub mod version;
 
 // #[doc(hidden)]
 // pub mod util;
 #[doc(hidden)]
 pub mod reexport {
     pub use catalog;
+    pub use serde;
 }
 
 mod impls;
 
-pub trait AnySerde: Any + Send + Sync + Debug + 'static {
+pub use registry::validate;
+
+pub trait AnySerde: Any + Send + Sync + Debug + AsAnySerde + 'static {
     fn clone_box(&self) -> BoxAnySerde;
     fn inner_type_name(&self) -> &'static str;
 }
 
+/// Converts trait objects of traits that extend [`AnySerde`] to `&dyn AnySerde`.
+pub trait AsAnySerde {
+    fn as_any_serde(&self) -> &dyn AnySerde;
+}
+
+impl<T: AnySerde> AsAnySerde for T {
+    fn as_any_serde(&self) -> &dyn AnySerde { self }
+}
+
 pub fn downcast_box<T: AnySerde>(b: Box<dyn AnySerde>) -> Result<Box<T>, Box<dyn AnySerde>> {
     if b.deref().is::<T>() {
         unsafe {
@@ -227,6 +256,40 @@ impl<T: AnySerializer> AnySerializerDefault for T {
     }
 }
 
+/// Describes the tag of `value` for errors about a format that cannot serialize it.
+pub(crate) fn describe(value: &dyn AnySerde) -> String {
+    if let Some(unknown) = value.downcast_ref::<UnknownBinary>() {
+        match json::IMPLS
+            .tag_by_hash(unknown.tag)
+            .or_else(|| cbor::IMPLS.tag_by_hash(unknown.tag))
+        {
+            Some(tag) => format!("unknown binary value tagged `{}` ({})", tag.name, tag.hash),
+            None => format!("unknown binary value tagged ({})", unknown.tag),
+        }
+    } else if let Some(unknown) = value.downcast_ref::<UnknownJson>() {
+        let hash = TypeTagHash::new(&unknown.tag);
+        format!("unknown JSON value tagged `{}` ({})", unknown.tag, hash)
+    } else if let Some(unknown) = value.downcast_ref::<UnknownCbor>() {
+        let hash = TypeTagHash::new(&unknown.tag);
+        format!("unknown CBOR value tagged `{}` ({})", unknown.tag, hash)
+    } else {
+        let id = value.type_id();
+        match json::IMPLS
+            .tag_by_type_id(id)
+            .or_else(|| binary::IMPLS.tag_by_type_id(id))
+            .or_else(|| cbor::IMPLS.tag_by_type_id(id))
+        {
+            Some(tag) => format!(
+                "{} tagged `{}` ({})",
+                value.inner_type_name(),
+                tag.name,
+                tag.hash
+            ),
+            None => value.inner_type_name().to_string(),
+        }
+    }
+}
+
 // Traits for scoping macro contents.
 #[doc(hidden)]
 pub trait JsonNopTrait {
@@ -4,15 +4,37 @@
 /// unique, it should be a fully qualified path to the type (e.g. `"std::string::String"`). To
 /// handle version skew between producers and consumers of serialized data, the name should not
 /// change, even if the original type moves or changes names.
+///
+/// To change the shape of a type, give it a new version and a function that upgrades each older
+/// version to the next one:
+/// ```ignore
+/// impl_any_serde!(Type, "name", version = 3, upgrade_from = [2 => v2_to_v3, 1 => v1_to_v2], ...);
+/// ```
+/// Data tagged with an older version is deserialized as the argument type of its upgrade
+/// function and then upgraded step by step. Serialization always writes the current version.
 #[macro_export]
 macro_rules! impl_any_serde {
+    ($ty:ty, $name:tt, version = $version:literal, upgrade_from = $upgrades:tt $(, $registry:path)*) => {
+        $crate::impl_any_serde!(
+            @impl $ty,
+            $crate::tag::TypeTag::new($crate::versioned_name!($name, $version)),
+            $($registry),*
+        );
+        $crate::impl_any_serde!(@upgrades $ty, $name, $version, $upgrades);
+        $(
+            $crate::impl_any_serde!(@register_upgrades $ty, $registry, $upgrades);
+        )*
+    };
     ($ty:ty, $name:tt, $($registry:path),*) => {
+        $crate::impl_any_serde!(@impl $ty, $crate::tag::TypeTag::new($name), $($registry),*);
+    };
+    (@impl $ty:ty, $tag:expr, $($registry:path),*) => {
         impl $crate::tag::HasTypeTag for $ty {
             fn type_tag() -> &'static $crate::tag::TypeTag {
                 #[allow(non_upper_case_globals, non_snake_case)]
                 mod  internal  {
                     pub static TYPE_TAG: ::std::lazy::SyncLazy<$crate::tag::TypeTag>
-                        = ::std::lazy::SyncLazy::new(|| $crate::tag::TypeTag::new($name));
+                        = ::std::lazy::SyncLazy::new(|| $tag);
                 }
                 &*internal::TYPE_TAG
             }
@@ -31,5 +53,96 @@ macro_rules! impl_any_serde {
                 fn a() -> &'static ::std::marker::PhantomData<$ty> { &::std::marker::PhantomData }
             };
         )*
-    }
+    };
+    (@upgrades $ty:ty, $name:tt, $version:expr, [$($from:literal => $upgrade:expr),* $(,)?]) => {
+        impl $crate::version::HasUpgrades for $ty {
+            fn upgrades() -> &'static $crate::version::Upgrades {
+                static UPGRADES: ::std::lazy::SyncLazy<$crate::version::Upgrades> =
+                    ::std::lazy::SyncLazy::new(|| {
+                        $crate::version::Upgrades::new::<$ty>(
+                            $name,
+                            $version,
+                            vec![$($crate::version::UpgradeStep::new(
+                                $crate::tag::TypeTag::new($crate::versioned_name!($name, $from)),
+                                $upgrade,
+                            )),*],
+                        )
+                    });
+                &*UPGRADES
+            }
+        }
+    };
+    (@register_upgrades $ty:ty, $registry:path, [$($from:literal => $upgrade:expr),* $(,)?]) => {
+        $(
+            const _ : () = {
+                #[$crate::reexport::catalog::register($registry,crate=$crate::reexport::catalog)]
+                fn a() -> &'static $crate::version::UpgradeFrom<$ty> {
+                    static UPGRADE: $crate::version::UpgradeFrom<$ty> =
+                        $crate::version::UpgradeFrom::new($from);
+                    &UPGRADE
+                }
+            };
+        )*
+    };
+}
+
+/// The tag name of version `version` of the type named `name`: `name` for version 1, and
+/// `"name@version"` for later versions.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! versioned_name {
+    ($name:tt, $version:literal) => {
+        if $version <= 1 {
+            $name
+        } else {
+            concat!($name, "@", $version)
+        }
+    };
+}
+
+/// `any_serde_trait!(Trait, pub REGISTRY)` lets `Box<dyn Trait>` be serialized and deserialized
+/// like [`BoxAnySerde`](crate::BoxAnySerde), where `Trait` extends [`AnySerde`](crate::AnySerde).
+///
+/// This declares `REGISTRY` to collect the implementers of `Trait`. A type is added to it by
+/// listing `REGISTRY` in its [`impl_any_serde!`](crate::impl_any_serde). Deserializing a value
+/// whose type is not in `REGISTRY` fails, even if its tag is registered with the format.
+#[macro_export]
+macro_rules! any_serde_trait {
+    ($trait:path, $vis:vis $registry:ident) => {
+        $vis static $registry: $crate::reexport::catalog::Registry<
+            $crate::traits::TraitImpls<dyn $trait>,
+        > = $crate::reexport::catalog::Registry::new();
+        impl<T: $trait> $crate::traits::Cast<dyn $trait> for ::std::marker::PhantomData<T> {
+            fn inner_type_id(&self) -> ::std::any::TypeId { ::std::any::TypeId::of::<T>() }
+            fn cast_box(&self, value: $crate::BoxAnySerde) -> ::std::boxed::Box<dyn $trait> {
+                match $crate::downcast_box::<T>(value) {
+                    Ok(value) => value,
+                    Err(_) => panic!("registered with the wrong type"),
+                }
+            }
+        }
+        impl $crate::reexport::serde::Serialize for ::std::boxed::Box<dyn $trait> {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::reexport::serde::Serializer,
+            {
+                $crate::reexport::serde::Serialize::serialize(
+                    &$crate::AsAnySerde::as_any_serde(&**self),
+                    serializer,
+                )
+            }
+        }
+        impl<'de> $crate::reexport::serde::Deserialize<'de> for ::std::boxed::Box<dyn $trait> {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: $crate::reexport::serde::Deserializer<'de>,
+            {
+                let value: $crate::BoxAnySerde =
+                    $crate::reexport::serde::Deserialize::deserialize(deserializer)?;
+                $registry
+                    .cast_box(value)
+                    .map_err(<D::Error as $crate::reexport::serde::de::Error>::custom)
+            }
+        }
+    };
 }
@@ -0,0 +1,241 @@
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::tag::{TypeTag, TypeTagHash};
+use crate::{binary, cbor, json, AnySerde, BoxAnySerde};
+
+/// A format with its own registry of types.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Format {
+    Json,
+    Binary,
+    Cbor,
+}
+
+/// One registration of a type with a format.
+#[derive(Debug, Clone)]
+pub struct Registration {
+    pub format: Format,
+    pub tag: &'static TypeTag,
+    pub type_name: &'static str,
+    pub type_id: TypeId,
+    /// Whether this registration only reads an older version of the type.
+    pub upgrade: bool,
+}
+
+/// A tag and every format it is registered with.
+#[derive(Debug, Clone)]
+pub struct RegisteredTag {
+    pub name: &'static str,
+    pub hash: TypeTagHash,
+    pub type_name: &'static str,
+    pub formats: Vec<Format>,
+}
+
+/// Two registrations that cannot both be used. Values tagged with a name or hash that is
+/// registered for more than one type fail to deserialize.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Conflict {
+    /// The type is registered more than once with a format.
+    DuplicateType {
+        format: Format,
+        type_name: &'static str,
+    },
+    /// Two types are registered with the same tag name.
+    DuplicateName {
+        name: &'static str,
+        first: &'static str,
+        second: &'static str,
+    },
+    /// Two tag names have the same hash.
+    HashCollision {
+        hash: TypeTagHash,
+        first: &'static str,
+        second: &'static str,
+    },
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Json => write!(f, "json"),
+            Format::Binary => write!(f, "binary"),
+            Format::Cbor => write!(f, "cbor"),
+        }
+    }
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Conflict::DuplicateType { format, type_name } => {
+                write!(
+                    f,
+                    "{} is registered with {} more than once",
+                    type_name, format
+                )
+            }
+            Conflict::DuplicateName {
+                name,
+                first,
+                second,
+            } => write!(f, "{} and {} are both tagged `{}`", first, second, name),
+            Conflict::HashCollision {
+                hash,
+                first,
+                second,
+            } => write!(f, "`{}` and `{}` both hash to {}", first, second, hash),
+        }
+    }
+}
+
+/// Every registration with every format.
+pub fn registrations() -> Vec<Registration> {
+    json::IMPLS
+        .registrations()
+        .chain(binary::IMPLS.registrations())
+        .chain(cbor::IMPLS.registrations())
+        .collect()
+}
+
+/// Every registered tag, ordered by name.
+pub fn tags() -> Vec<RegisteredTag> {
+    let mut tags = BTreeMap::<&'static str, RegisteredTag>::new();
+    for registration in registrations() {
+        let tag = tags
+            .entry(registration.tag.name)
+            .or_insert_with(|| RegisteredTag {
+                name: registration.tag.name,
+                hash: registration.tag.hash,
+                type_name: registration.type_name,
+                formats: vec![],
+            });
+        if !tag.formats.contains(&registration.format) {
+            tag.formats.push(registration.format);
+        }
+    }
+    tags.into_values().collect()
+}
+
+/// Finds every conflict between `registrations`.
+pub fn conflicts(registrations: &[Registration]) -> Vec<Conflict> {
+    let mut conflicts = vec![];
+    let mut by_type = HashSet::new();
+    let mut by_name = BTreeMap::<&'static str, &Registration>::new();
+    let mut by_hash = BTreeMap::<TypeTagHash, &Registration>::new();
+    for registration in registrations {
+        if !registration.upgrade && !by_type.insert((registration.format, registration.type_id)) {
+            conflicts.push(Conflict::DuplicateType {
+                format: registration.format,
+                type_name: registration.type_name,
+            });
+        }
+        match by_name.get(registration.tag.name) {
+            Some(first) if first.type_id != registration.type_id => {
+                conflicts.push(Conflict::DuplicateName {
+                    name: registration.tag.name,
+                    first: first.type_name,
+                    second: registration.type_name,
+                })
+            }
+            Some(_) => {}
+            None => {
+                by_name.insert(registration.tag.name, registration);
+            }
+        }
+        match by_hash.get(&registration.tag.hash) {
+            Some(first) if first.tag.name != registration.tag.name => {
+                conflicts.push(Conflict::HashCollision {
+                    hash: registration.tag.hash,
+                    first: first.tag.name,
+                    second: registration.tag.name,
+                })
+            }
+            Some(_) => {}
+            None => {
+                by_hash.insert(registration.tag.hash, registration);
+            }
+        }
+    }
+    conflicts
+}
+
+/// Checks the registries of every format for conflicts, reporting all of them at once.
+pub fn validate() -> Result<(), Vec<Conflict>> {
+    let conflicts = conflicts(&registrations());
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        Err(conflicts)
+    }
+}
+
+/// Serializes a default-constructed sample of every registered type that implements
+/// [`Default`] in each of its formats, and checks that it deserializes to the same type and
+/// serializes to the same output again. Panics with every failure and conflict found.
+pub fn assert_round_trips() {
+    let mut failures: Vec<String> = match validate() {
+        Ok(()) => vec![],
+        Err(conflicts) => conflicts.iter().map(|c| c.to_string()).collect(),
+    };
+    let mut check = |format: Format, sample: BoxAnySerde, result: Result<(), String>| {
+        if let Err(e) = result {
+            failures.push(format!(
+                "{} via {}: {}",
+                sample.inner_type_name(),
+                format,
+                e
+            ));
+        }
+    };
+    for sample in json::IMPLS.samples() {
+        let result = round_trip(&sample, json::serialize, |bytes| {
+            json::deserialize(bytes.as_bytes())
+        });
+        check(Format::Json, sample, result);
+    }
+    for sample in binary::IMPLS.samples() {
+        let result = round_trip(&sample, binary::serialize, |bytes| {
+            binary::deserialize(bytes)
+        });
+        check(Format::Binary, sample, result);
+    }
+    for sample in cbor::IMPLS.samples() {
+        let result = round_trip(&sample, cbor::serialize, |bytes| cbor::deserialize(bytes));
+        check(Format::Cbor, sample, result);
+    }
+    if !failures.is_empty() {
+        panic!("{}", failures.join("\n"));
+    }
+}
+
+fn round_trip<O: AsRef<[u8]> + Eq, E: Display>(
+    sample: &BoxAnySerde,
+    serialize: impl Fn(&BoxAnySerde) -> Result<O, E>,
+    deserialize: impl Fn(&O) -> Result<BoxAnySerde, E>,
+) -> Result<(), String> {
+    let encoded = serialize(sample).map_err(|e| e.to_string())?;
+    let decoded = deserialize(&encoded).map_err(|e| e.to_string())?;
+    if (*decoded).type_id() != (**sample).type_id() {
+        return Err(format!("deserialized as {}", decoded.inner_type_name()));
+    }
+    if serialize(&decoded).map_err(|e| e.to_string())? != encoded {
+        return Err("serialized differently after deserializing".to_string());
+    }
+    Ok(())
+}
+
+/// A default-constructed `T`, if `T` implements [`Default`].
+pub(crate) trait Sample {
+    fn sample() -> Option<BoxAnySerde>;
+}
+
+impl<T: AnySerde> Sample for T {
+    default fn sample() -> Option<BoxAnySerde> { None }
+}
+
+impl<T: AnySerde + Default> Sample for T {
+    fn sample() -> Option<BoxAnySerde> { Some(Box::new(T::default())) }
+}
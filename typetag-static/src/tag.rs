@@ -1,4 +1,6 @@
 use std::convert::TryInto;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -8,6 +10,27 @@ use sha2::{Digest, Sha256};
 #[derive(Serialize, Deserialize, Eq, Ord, PartialEq, PartialOrd, Hash, Debug, Copy, Clone)]
 pub struct TypeTagHash([u8; 16]);
 
+impl TypeTagHash {
+    pub fn new(name: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(name);
+        TypeTagHash(
+            hasher.finalize().as_slice()[0..16]
+                .try_into()
+                .expect("wrong length"),
+        )
+    }
+}
+
+impl Display for TypeTagHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
 /// A unique stable identifier for a type.
 #[derive(Debug, Clone)]
 pub struct TypeTag {
@@ -17,17 +40,20 @@ pub struct TypeTag {
 
 impl TypeTag {
     pub fn new(name: &'static str) -> Self {
-        let mut hasher = Sha256::new();
-        hasher.update(name);
         TypeTag {
             name,
-            hash: TypeTagHash(
-                hasher.finalize().as_slice()[0..16]
-                    .try_into()
-                    .expect("wrong length"),
-            ),
+            hash: TypeTagHash::new(name),
         }
     }
+    /// The version of the type. Version 1 is tagged with the plain name, so data written before a
+    /// type was versioned is read as version 1. Later versions are tagged `"name@version"` by
+    /// [`versioned_name!`](crate::versioned_name).
+    pub fn version(&self) -> u32 {
+        self.name
+            .rsplit_once('@')
+            .and_then(|(_, version)| version.parse().ok())
+            .unwrap_or(1)
+    }
 }
 
 /// A type that has an associated [`TypeTag`].
@@ -0,0 +1,371 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+use catalog::{Builder, BuilderFrom};
+use serde::de::{DeserializeOwned, DeserializeSeed, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::registry::{Format, Registration, Sample};
+use crate::tag::{HasTypeTag, TypeTag, TypeTagHash};
+use crate::version::{HasUpgrades, UpgradeFrom, UpgradeStep};
+use crate::{AnySerde, ArcAnySerde, BoxAnySerde};
+
+/// A self-describing format that writes an [`AnySerde`] as a map with a single entry from the
+/// tag name to the value, such as [`json`](crate::json) and [`cbor`](crate::cbor).
+pub trait TaggedFormat: 'static + Sized + Send + Sync {
+    const FORMAT: Format;
+    /// A value of any shape, used to keep values with an unrecognized tag.
+    type Value: Serialize + DeserializeOwned + Clone + Debug + Send + Sync;
+    type Error: ser::Error + de::Error;
+    type Ok;
+    type Serializer<'a, 'b>: Serializer<Ok = Self::Ok, Error = Self::Error>
+    where
+        'b: 'a;
+    type Deserializer<'a, 'de>: Deserializer<'de, Error = Self::Error>
+    where
+        'de: 'a;
+    fn impls() -> &'static Impls<Self>;
+    /// Deserializes the version of a type read by `step`.
+    fn deserialize_old<'a, 'de>(
+        step: &UpgradeStep,
+        deserializer: Self::Deserializer<'a, 'de>,
+    ) -> Result<Box<dyn Any>, Self::Error>;
+}
+
+/// A struct created by [`AnySerde`](crate::AnySerde) when deserializing a value with an
+/// unrecognized tag. Ensures that such values can safely be re-serialized without losing data.
+pub struct Unknown<F: TaggedFormat> {
+    pub(crate) tag: String,
+    pub(crate) value: F::Value,
+}
+
+impl<F: TaggedFormat> Clone for Unknown<F> {
+    fn clone(&self) -> Self {
+        Unknown {
+            tag: self.tag.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<F: TaggedFormat> Debug for Unknown<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("tag", &self.tag)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<F: TaggedFormat> AnySerde for Unknown<F> {
+    fn clone_box(&self) -> BoxAnySerde { Box::new(self.clone()) }
+
+    fn inner_type_name(&self) -> &'static str { type_name::<Self>() }
+}
+
+/// Serializes `value` with the impl registered for its type.
+pub(crate) fn serialize_dyn<'a, 'b, F: TaggedFormat>(
+    serializer: F::Serializer<'a, 'b>,
+    value: &dyn AnySerde,
+) -> Result<F::Ok, F::Error> {
+    if let Some(unknown) = value.downcast_ref::<Unknown<F>>() {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&unknown.tag, &unknown.value)?;
+        map.end()
+    } else {
+        F::impls()
+            .by_type_id
+            .get(&value.type_id())
+            .ok_or_else(|| {
+                <F::Error as ser::Error>::custom(format!(
+                    "Missing {} impl for {}",
+                    F::FORMAT,
+                    crate::describe(value)
+                ))
+            })?
+            .serialize_tagged(serializer, value)
+    }
+}
+
+/// Calls the [`AnyTagged`] impl for the deserializer of `F`, which is the only deserializer
+/// that a value of `F` is ever read with.
+pub(crate) trait TaggedDeserializer<'de, F: TaggedFormat>: Deserializer<'de> {
+    fn deserialize_box_tagged(
+        self,
+        imp: &'static dyn AnyTagged<F>,
+    ) -> Result<BoxAnySerde, Self::Error>;
+    fn deserialize_arc_tagged(
+        self,
+        imp: &'static dyn AnyTagged<F>,
+    ) -> Result<ArcAnySerde, Self::Error>;
+}
+
+impl<'de, F: TaggedFormat, D: Deserializer<'de>> TaggedDeserializer<'de, F> for D {
+    default fn deserialize_box_tagged(
+        self,
+        imp: &'static dyn AnyTagged<F>,
+    ) -> Result<BoxAnySerde, Self::Error> {
+        panic!(
+            "Missing {} deserializer impl for {}",
+            F::FORMAT,
+            type_name::<D>()
+        );
+    }
+    default fn deserialize_arc_tagged(
+        self,
+        imp: &'static dyn AnyTagged<F>,
+    ) -> Result<ArcAnySerde, Self::Error> {
+        panic!(
+            "Missing {} deserializer impl for {}",
+            F::FORMAT,
+            type_name::<D>()
+        );
+    }
+}
+
+/// [`BoxAnySerde`] or [`ArcAnySerde`].
+pub(crate) trait Dynamic: Sized {
+    fn from_unknown<F: TaggedFormat>(unknown: Unknown<F>) -> Self;
+    fn deserialize_with<'de, F: TaggedFormat, D: Deserializer<'de>>(
+        deserializer: D,
+        imp: &'static dyn AnyTagged<F>,
+    ) -> Result<Self, D::Error>;
+}
+
+impl Dynamic for BoxAnySerde {
+    fn from_unknown<F: TaggedFormat>(unknown: Unknown<F>) -> Self { Box::new(unknown) }
+    fn deserialize_with<'de, F: TaggedFormat, D: Deserializer<'de>>(
+        deserializer: D,
+        imp: &'static dyn AnyTagged<F>,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_box_tagged(imp)
+    }
+}
+
+impl Dynamic for ArcAnySerde {
+    fn from_unknown<F: TaggedFormat>(unknown: Unknown<F>) -> Self { ArcAnySerde::new(unknown) }
+    fn deserialize_with<'de, F: TaggedFormat, D: Deserializer<'de>>(
+        deserializer: D,
+        imp: &'static dyn AnyTagged<F>,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_arc_tagged(imp)
+    }
+}
+
+struct Seed<F: TaggedFormat, O>(&'static dyn AnyTagged<F>, PhantomData<O>);
+
+impl<'de, F: TaggedFormat, O: Dynamic> DeserializeSeed<'de> for Seed<F, O> {
+    type Value = O;
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        O::deserialize_with(deserializer, self.0)
+    }
+}
+
+struct Vis<F, O>(PhantomData<(F, O)>);
+
+impl<'de, F: TaggedFormat, O: Dynamic> Visitor<'de> for Vis<F, O> {
+    type Value = O;
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "a map with a typetag name key and dynamic value")
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut seq: A) -> Result<O, A::Error> {
+        let typ = seq
+            .next_key::<String>()?
+            .ok_or_else(|| <A::Error as de::Error>::custom("missing key"))?;
+        if let Some(imp) = F::impls().by_name(&typ).map_err(de::Error::custom)? {
+            seq.next_value_seed(Seed::<F, O>(imp, PhantomData))
+        } else {
+            Ok(O::from_unknown(Unknown::<F> {
+                tag: typ,
+                value: seq.next_value()?,
+            }))
+        }
+    }
+}
+
+/// Deserializes a [`BoxAnySerde`] or [`ArcAnySerde`] written by [`serialize_dyn`].
+pub(crate) fn deserialize_dyn<'de, F: TaggedFormat, O: Dynamic, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<O, D::Error> {
+    deserializer.deserialize_map(Vis::<F, O>(PhantomData))
+}
+
+/// A type registered with the [`Impls`] of a [`TaggedFormat`].
+pub trait AnyTagged<F: TaggedFormat>: 'static + Send + Sync {
+    fn inner_type_tag(&self) -> &'static TypeTag;
+    fn inner_type_id(&self) -> TypeId;
+    fn inner_type_name(&self) -> &'static str;
+    /// Whether this impl only reads an older version of its type.
+    fn is_upgrade(&self) -> bool { false }
+    /// A default-constructed value, if the type implements [`Default`].
+    fn sample(&self) -> Option<BoxAnySerde> { None }
+    fn serialize_tagged<'a, 'b>(
+        &self,
+        serializer: F::Serializer<'a, 'b>,
+        value: &dyn AnySerde,
+    ) -> Result<F::Ok, F::Error>;
+    fn deserialize_box_tagged<'a, 'de>(
+        &self,
+        deserializer: F::Deserializer<'a, 'de>,
+    ) -> Result<BoxAnySerde, F::Error>;
+    fn deserialize_arc_tagged<'a, 'de>(
+        &self,
+        deserializer: F::Deserializer<'a, 'de>,
+    ) -> Result<ArcAnySerde, F::Error>;
+}
+
+impl<F: TaggedFormat, T: Serialize + DeserializeOwned + HasTypeTag + AnySerde> AnyTagged<F>
+    for PhantomData<T>
+{
+    fn inner_type_tag(&self) -> &'static TypeTag { T::type_tag() }
+    fn inner_type_id(&self) -> TypeId { TypeId::of::<T>() }
+    fn inner_type_name(&self) -> &'static str { type_name::<T>() }
+    fn sample(&self) -> Option<BoxAnySerde> { T::sample() }
+    fn serialize_tagged<'a, 'b>(
+        &self,
+        serializer: F::Serializer<'a, 'b>,
+        value: &dyn AnySerde,
+    ) -> Result<F::Ok, F::Error> {
+        let value = value.downcast_ref::<T>().ok_or_else(|| {
+            <F::Error as ser::Error>::custom(format!("Bad type passed to {} impl", F::FORMAT))
+        })?;
+        let mut struc = serializer.serialize_map(Some(1))?;
+        struc.serialize_entry(T::type_tag().name, value)?;
+        struc.end()
+    }
+    fn deserialize_box_tagged<'a, 'de>(
+        &self,
+        deserializer: F::Deserializer<'a, 'de>,
+    ) -> Result<BoxAnySerde, F::Error> {
+        Ok(Box::new(T::deserialize(deserializer)?))
+    }
+    fn deserialize_arc_tagged<'a, 'de>(
+        &self,
+        deserializer: F::Deserializer<'a, 'de>,
+    ) -> Result<ArcAnySerde, F::Error> {
+        Ok(ArcAnySerde::new(T::deserialize(deserializer)?))
+    }
+}
+
+impl<F: TaggedFormat, T: HasUpgrades + AnySerde> AnyTagged<F> for UpgradeFrom<T> {
+    fn inner_type_tag(&self) -> &'static TypeTag { self.tag() }
+    fn inner_type_id(&self) -> TypeId { TypeId::of::<T>() }
+    fn inner_type_name(&self) -> &'static str { type_name::<T>() }
+    fn is_upgrade(&self) -> bool { true }
+    fn serialize_tagged<'a, 'b>(
+        &self,
+        serializer: F::Serializer<'a, 'b>,
+        value: &dyn AnySerde,
+    ) -> Result<F::Ok, F::Error> {
+        Err(<F::Error as ser::Error>::custom(
+            "Cannot serialize an older version of a type",
+        ))
+    }
+    fn deserialize_box_tagged<'a, 'de>(
+        &self,
+        deserializer: F::Deserializer<'a, 'de>,
+    ) -> Result<BoxAnySerde, F::Error> {
+        let old = F::deserialize_old(self.step(), deserializer)?;
+        Ok(Box::new(self.upgrade(old)))
+    }
+    fn deserialize_arc_tagged<'a, 'de>(
+        &self,
+        deserializer: F::Deserializer<'a, 'de>,
+    ) -> Result<ArcAnySerde, F::Error> {
+        let old = F::deserialize_old(self.step(), deserializer)?;
+        Ok(ArcAnySerde::new(self.upgrade(old)))
+    }
+}
+
+/// The types registered with a [`TaggedFormat`].
+pub struct Impls<F: TaggedFormat> {
+    all: Vec<&'static dyn AnyTagged<F>>,
+    by_type_id: HashMap<TypeId, &'static dyn AnyTagged<F>>,
+    by_type_tag_name: HashMap<&'static str, &'static dyn AnyTagged<F>>,
+    /// Names registered for more than one type, which fail to deserialize.
+    ambiguous: HashSet<&'static str>,
+}
+
+impl<F: TaggedFormat> Impls<F> {
+    pub(crate) fn tag_by_type_id(&self, id: TypeId) -> Option<&'static TypeTag> {
+        Some(self.by_type_id.get(&id)?.inner_type_tag())
+    }
+    pub(crate) fn tag_by_hash(&self, hash: TypeTagHash) -> Option<&'static TypeTag> {
+        self.by_type_tag_name
+            .values()
+            .map(|imp| imp.inner_type_tag())
+            .find(|tag| tag.hash == hash)
+    }
+    fn by_name(&self, name: &str) -> Result<Option<&'static dyn AnyTagged<F>>, String> {
+        if self.ambiguous.contains(name) {
+            return Err(format!("`{}` is registered for more than one type", name));
+        }
+        Ok(self.by_type_tag_name.get(name).copied())
+    }
+    pub(crate) fn registrations(&self) -> impl Iterator<Item = Registration> + '_ {
+        self.all.iter().map(|imp| Registration {
+            format: F::FORMAT,
+            tag: imp.inner_type_tag(),
+            type_name: imp.inner_type_name(),
+            type_id: imp.inner_type_id(),
+            upgrade: imp.is_upgrade(),
+        })
+    }
+    pub(crate) fn samples(&self) -> Vec<BoxAnySerde> {
+        self.by_type_id
+            .values()
+            .filter_map(|imp| imp.sample())
+            .collect()
+    }
+}
+
+impl<F: TaggedFormat> Builder for Impls<F> {
+    type Output = Self;
+
+    fn new() -> Self {
+        Impls {
+            all: vec![],
+            by_type_id: HashMap::new(),
+            by_type_tag_name: HashMap::new(),
+            ambiguous: HashSet::new(),
+        }
+    }
+
+    fn build(self) -> Self::Output { self }
+}
+
+impl<F: TaggedFormat> BuilderFrom<&'static dyn AnyTagged<F>> for Impls<F> {
+    /// Conflicting elements are kept for [`validate`](crate::validate). A name registered for
+    /// more than one type is not resolved to either of them, so reading it fails no matter which
+    /// was registered first.
+    fn insert(&mut self, element: &'static dyn AnyTagged<F>) {
+        self.all.push(element);
+        if !element.is_upgrade() {
+            self.by_type_id
+                .entry(element.inner_type_id())
+                .or_insert(element);
+        }
+        let name = element.inner_type_tag().name;
+        if self.ambiguous.contains(name) {
+            return;
+        }
+        match self.by_type_tag_name.entry(name) {
+            Entry::Occupied(first) if first.get().inner_type_id() != element.inner_type_id() => {
+                first.remove();
+                self.ambiguous.insert(name);
+            }
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(element);
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use catalog::{Builder, BuilderFrom};
+
+use crate::{AnySerde, BoxAnySerde};
+
+/// Converts a [`BoxAnySerde`] holding a `T` into a `Box<D>`, where `D` is a trait object type
+/// declared with [`any_serde_trait!`](crate::any_serde_trait) and `T` implements its trait.
+pub trait Cast<D: ?Sized>: 'static + Send + Sync {
+    fn inner_type_id(&self) -> TypeId;
+    fn cast_box(&self, value: BoxAnySerde) -> Box<D>;
+}
+
+/// The error returned by [`TraitImpls::cast_box`] for a value whose type does not implement the
+/// trait. The value is returned rather than dropped.
+#[derive(Debug)]
+pub struct NotImplemented {
+    pub value: BoxAnySerde,
+    pub trait_name: &'static str,
+}
+
+/// The types registered as implementers of the trait of `D`.
+pub struct TraitImpls<D: ?Sized + 'static> {
+    by_type_id: HashMap<TypeId, &'static dyn Cast<D>>,
+}
+
+impl<D: ?Sized + 'static> TraitImpls<D> {
+    /// Converts `value` to `Box<D>`, or returns it if its type does not implement the trait.
+    pub fn cast_box(&self, value: BoxAnySerde) -> Result<Box<D>, NotImplemented> {
+        match self.by_type_id.get(&(*value).type_id()) {
+            Some(imp) => Ok(imp.cast_box(value)),
+            None => Err(NotImplemented {
+                value,
+                trait_name: type_name::<D>(),
+            }),
+        }
+    }
+}
+
+impl Display for NotImplemented {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} does not implement {}",
+            crate::describe(&*self.value),
+            self.trait_name
+        )
+    }
+}
+
+impl std::error::Error for NotImplemented {}
+
+impl<D: ?Sized + 'static> Builder for TraitImpls<D> {
+    type Output = Self;
+    fn new() -> Self {
+        TraitImpls {
+            by_type_id: HashMap::new(),
+        }
+    }
+    fn build(self) -> Self::Output { self }
+}
+
+impl<D: ?Sized + 'static> BuilderFrom<&'static dyn Cast<D>> for TraitImpls<D> {
+    fn insert(&mut self, element: &'static dyn Cast<D>) {
+        assert!(self
+            .by_type_id
+            .insert(element.inner_type_id(), element)
+            .is_none());
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::{binary, json, BoxAnySerde};
+
+/// An error from one side of a conversion. Values whose tag is not registered with both formats
+/// fail with an error that names the tag and its hash.
+#[derive(Debug)]
+pub enum Error {
+    Binary(binary::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Binary(e) => write!(f, "binary: {}", e),
+            Error::Json(e) => write!(f, "json: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<binary::Error> for Error {
+    fn from(e: binary::Error) -> Self { Error::Binary(e) }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self { Error::Json(e) }
+}
+
+/// Converts a [`BoxAnySerde`] in the [`binary`] format to JSON.
+pub fn binary_to_json(input: &[u8]) -> Result<String, Error> {
+    let value: BoxAnySerde = binary::deserialize(input)?;
+    Ok(json::serialize(&value)?)
+}
+
+/// Converts a [`BoxAnySerde`] in JSON to the [`binary`] format.
+pub fn json_to_binary(input: &str) -> Result<Vec<u8>, Error> {
+    let value: BoxAnySerde = json::deserialize(input.as_bytes())?;
+    Ok(binary::serialize(&value)?)
+}
@@ -0,0 +1,126 @@
+use std::any::{type_name, Any, TypeId};
+use std::marker::PhantomData;
+
+use serde::Deserialize;
+
+use crate::tag::{HasTypeTag, TypeTag};
+use crate::{binary, cbor, json};
+
+/// A type whose older versions are upgraded to it when they are deserialized. Implemented by
+/// [`impl_any_serde!`](crate::impl_any_serde) when given `version` and `upgrade_from`.
+pub trait HasUpgrades: HasTypeTag {
+    fn upgrades() -> &'static Upgrades;
+}
+
+/// Converts a value of one older version of a type into the next version.
+pub struct UpgradeStep {
+    from: u32,
+    tag: TypeTag,
+    input: (TypeId, &'static str),
+    output: (TypeId, &'static str),
+    pub(crate) deserialize_json: json::DeserializeAny,
+    pub(crate) deserialize_binary: binary::any::DeserializeAny,
+    pub(crate) deserialize_cbor: cbor::DeserializeAny,
+    apply: Box<dyn Fn(Box<dyn Any>) -> Box<dyn Any> + Send + Sync>,
+}
+
+/// The upgrade steps from every older version of a type, ordered by version.
+pub struct Upgrades {
+    steps: Vec<UpgradeStep>,
+}
+
+/// A registry entry that reads version `from` of `T` and upgrades it to the current version.
+pub struct UpgradeFrom<T> {
+    from: u32,
+    phantom: PhantomData<T>,
+}
+
+impl UpgradeStep {
+    /// The step that reads the version tagged `tag` as an `A` and upgrades it with `upgrade`.
+    pub fn new<A, B>(tag: TypeTag, upgrade: fn(A) -> B) -> Self
+    where
+        A: for<'de> Deserialize<'de> + 'static,
+        B: 'static,
+    {
+        UpgradeStep {
+            from: tag.version(),
+            tag,
+            input: (TypeId::of::<A>(), type_name::<A>()),
+            output: (TypeId::of::<B>(), type_name::<B>()),
+            deserialize_json: json::deserialize_any::<A>,
+            deserialize_binary: binary::any::deserialize_any::<A>,
+            deserialize_cbor: cbor::deserialize_any::<A>,
+            apply: Box::new(move |value| {
+                Box::new(upgrade(
+                    *value.downcast::<A>().expect("checked by Upgrades::new"),
+                ))
+            }),
+        }
+    }
+}
+
+impl Upgrades {
+    /// Orders the steps for `T`, which is version `version` of the type named `name`. Panics
+    /// unless each step upgrades to the input of the step for the next version and the last
+    /// step upgrades to `T`.
+    pub fn new<T: 'static>(name: &'static str, version: u32, mut steps: Vec<UpgradeStep>) -> Self {
+        steps.sort_by_key(|step| step.from);
+        let mut next = version;
+        let mut expected = (TypeId::of::<T>(), type_name::<T>());
+        for step in steps.iter().rev() {
+            if step.from + 1 != next {
+                panic!(
+                    "{} version {} has no upgrade to version {}",
+                    name,
+                    step.from + 1,
+                    next
+                );
+            }
+            if step.output.0 != expected.0 {
+                panic!(
+                    "{} upgrade from version {} produces {}, expected {}",
+                    name, step.from, step.output.1, expected.1
+                );
+            }
+            next = step.from;
+            expected = step.input;
+        }
+        Upgrades { steps }
+    }
+    fn index(&self, from: u32) -> usize {
+        self.steps
+            .iter()
+            .position(|step| step.from == from)
+            .expect("missing upgrade step")
+    }
+    pub(crate) fn tag(&self, from: u32) -> &TypeTag { &self.steps[self.index(from)].tag }
+    pub(crate) fn step(&self, from: u32) -> &UpgradeStep { &self.steps[self.index(from)] }
+    /// Applies the steps from version `from` to the current version.
+    fn upgrade(&self, from: u32, value: Box<dyn Any>) -> Box<dyn Any> {
+        self.steps[self.index(from)..]
+            .iter()
+            .fold(value, |value, step| (step.apply)(value))
+    }
+}
+
+impl<T> UpgradeFrom<T> {
+    pub const fn new(from: u32) -> Self {
+        UpgradeFrom {
+            from,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: HasUpgrades + 'static> UpgradeFrom<T> {
+    pub(crate) fn tag(&self) -> &'static TypeTag { T::upgrades().tag(self.from) }
+    pub(crate) fn step(&self) -> &'static UpgradeStep { T::upgrades().step(self.from) }
+    /// Upgrades a value of version `from`, as returned by one of the `deserialize` functions of
+    /// [`step`](Self::step).
+    pub(crate) fn upgrade(&self, value: Box<dyn Any>) -> T {
+        *T::upgrades()
+            .upgrade(self.from, value)
+            .downcast::<T>()
+            .expect("checked by Upgrades::new")
+    }
+}
@@ -4,14 +4,15 @@ use catalog::register;
 use serde::{Deserialize, Serialize};
 use typetag_static::impl_any_serde;
 
-#[derive(Serialize, Deserialize, Eq, Ord, PartialEq, PartialOrd, Clone, Hash, Debug)]
+#[derive(Serialize, Deserialize, Eq, Ord, PartialEq, PartialOrd, Clone, Hash, Debug, Default)]
 pub struct AnyString(pub String);
 
 impl_any_serde!(
     AnyString,
     "serde_any::tests::common::AnyString",
     typetag_static::json::IMPLS,
-    typetag_static::binary::IMPLS
+    typetag_static::binary::IMPLS,
+    typetag_static::cbor::IMPLS
 );
 
 // registry! {
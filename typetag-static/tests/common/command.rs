@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use typetag_static::{any_serde_trait, impl_any_serde, AnySerde};
+
+pub trait Command: AnySerde {
+    fn run(&self) -> String;
+}
+
+any_serde_trait!(Command, pub COMMANDS);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Echo(pub String);
+
+impl Command for Echo {
+    fn run(&self) -> String { self.0.clone() }
+}
+
+impl_any_serde!(
+    Echo,
+    "serde_any::tests::common::Echo",
+    typetag_static::json::IMPLS,
+    typetag_static::binary::IMPLS,
+    COMMANDS
+);
@@ -6,7 +6,7 @@ use std::ops::Deref;
 use serde::de::Visitor;
 use serde::ser::Impossible;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use typetag_static::{AnyDeserializer, AnySerde, AnySerializer, BoxAnySerde};
+use typetag_static::{AnyDeserializer, AnySerde, AnySerializer, ArcAnySerde, BoxAnySerde};
 
 use crate::common::any_string::AnyString;
 
@@ -53,9 +53,12 @@ impl AnySerializer for Custom {
 }
 
 impl<'de> AnyDeserializer<'de> for Custom {
-    fn deserialize_dyn_impl(self) -> Result<BoxAnySerde, Self::Error> {
+    fn deserialize_box_impl(self) -> Result<BoxAnySerde, Self::Error> {
         Ok(Box::new(AnyString("abcd".to_string())))
     }
+    fn deserialize_arc_impl(self) -> Result<ArcAnySerde, Self::Error> {
+        Ok(ArcAnySerde::new(AnyString("abcd".to_string())))
+    }
 }
 
 impl Serializer for Custom {
@@ -1,2 +1,4 @@
 pub mod any_string;
+pub mod command;
 pub mod custom;
+pub mod versioned;
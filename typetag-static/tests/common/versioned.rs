@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use typetag_static::impl_any_serde;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PointV1 {
+    pub x: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PointV2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+fn v1_to_v2(old: PointV1) -> PointV2 { PointV2 { x: old.x, y: 0 } }
+
+fn v2_to_v3(old: PointV2) -> Point {
+    Point {
+        x: old.x as i64,
+        y: old.y as i64,
+        z: 0,
+    }
+}
+
+impl_any_serde!(
+    Point,
+    "serde_any::tests::common::Point",
+    version = 3,
+    upgrade_from = [2 => v2_to_v3, 1 => v1_to_v2],
+    typetag_static::json::IMPLS,
+    typetag_static::binary::IMPLS,
+    typetag_static::cbor::IMPLS
+);
@@ -0,0 +1,70 @@
+#![feature(specialization, never_type)]
+#![allow(incomplete_features, unused_imports)]
+#![feature(once_cell)]
+
+//! Registers two types with the same tag, which would fail `validate` in the other tests.
+
+use catalog::register;
+use serde::{Deserialize, Serialize};
+use typetag_static::registry::Conflict;
+use typetag_static::tag::HasTypeTag;
+use typetag_static::{binary, cbor, impl_any_serde, json, BoxAnySerde};
+
+const NAME: &str = "typetag_static::tests::conflict::Shared";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct First(u32);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Second(u32);
+
+impl_any_serde!(
+    First,
+    "typetag_static::tests::conflict::Shared",
+    typetag_static::json::IMPLS,
+    typetag_static::binary::IMPLS,
+    typetag_static::cbor::IMPLS
+);
+
+impl_any_serde!(
+    Second,
+    "typetag_static::tests::conflict::Shared",
+    typetag_static::json::IMPLS,
+    typetag_static::binary::IMPLS,
+    typetag_static::cbor::IMPLS
+);
+
+#[test]
+fn test_conflicting_tag() {
+    let conflicts = typetag_static::validate().unwrap_err();
+    assert!(conflicts
+        .iter()
+        .any(|c| matches!(c, Conflict::DuplicateName { name, .. } if *name == NAME)));
+
+    let input: BoxAnySerde = Box::new(First(1));
+    let encoded = json::serialize(&input).unwrap();
+    let message = json::deserialize::<BoxAnySerde>(encoded.as_bytes())
+        .unwrap_err()
+        .to_string();
+    assert!(
+        message.contains("registered for more than one type"),
+        "{}",
+        message
+    );
+
+    let encoded = cbor::serialize(&input).unwrap();
+    let message = cbor::deserialize::<BoxAnySerde>(&encoded)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        message.contains("registered for more than one type"),
+        "{}",
+        message
+    );
+
+    let encoded = binary::serialize(&input).unwrap();
+    match binary::deserialize::<BoxAnySerde>(&encoded) {
+        Err(binary::Error::AmbiguousTag(hash)) => assert_eq!(First::type_tag().hash, hash),
+        result => panic!("{:?}", result),
+    }
+}
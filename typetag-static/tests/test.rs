@@ -10,12 +10,18 @@
 #![deny(unused_must_use)]
 #![feature(once_cell)]
 
+use std::collections::BTreeMap;
 use std::ops::Deref;
 
 use common::any_string::AnyString;
+use common::command::{Command, Echo, COMMANDS};
 use common::custom;
-use serde::{Deserialize, Serialize};
-use typetag_static::{binary, json, BoxAnySerde};
+use common::versioned::{Point, PointV1, PointV2};
+use serde::{Deserialize, Serialize, Serializer};
+use typetag_static::registry::{Conflict, Format, Registration};
+use typetag_static::tag::{HasTypeTag, TypeTag};
+use typetag_static::traits::NotImplemented;
+use typetag_static::{binary, cbor, json, transcode, ArcAnySerde, BoxAnySerde};
 
 use crate::common::custom::{Custom, Expected};
 
@@ -141,3 +147,341 @@ fn test_custom_deserializer() {
             .unwrap()
     );
 }
+
+#[test]
+fn test_json_upgrade() {
+    let v1 = r#"{"serde_any::tests::common::Point":{"x":1}}"#;
+    assert_eq!(
+        &Point { x: 1, y: 0, z: 0 },
+        json::deserialize::<BoxAnySerde>(v1.as_bytes())
+            .unwrap()
+            .downcast_ref::<Point>()
+            .unwrap()
+    );
+    let v2 = r#"{"serde_any::tests::common::Point@2":{"x":1,"y":2}}"#;
+    assert_eq!(
+        &Point { x: 1, y: 2, z: 0 },
+        json::deserialize::<ArcAnySerde>(v2.as_bytes())
+            .unwrap()
+            .downcast_ref::<Point>()
+            .unwrap()
+    );
+    let v3: BoxAnySerde = Box::new(Point { x: 1, y: 2, z: 3 });
+    assert_eq!(
+        r#"{"serde_any::tests::common::Point@3":{"x":1,"y":2,"z":3}}"#,
+        json::serialize(&v3).unwrap()
+    );
+    assert_eq!(3, Point::type_tag().version());
+    assert_eq!(1, TypeTag::new("serde_any::tests::common::Point").version());
+}
+
+fn tagged_binary<T: Serialize>(name: &'static str, value: &T) -> Vec<u8> {
+    let content = binary::serialize(value).unwrap();
+    let mut encoded = binary::serialize(&TypeTag::new(name).hash).unwrap();
+    encoded.extend_from_slice(&(content.len() as u64).to_le_bytes());
+    encoded.extend_from_slice(&content);
+    encoded
+}
+
+#[test]
+fn test_binary_upgrade() {
+    let v1 = tagged_binary("serde_any::tests::common::Point", &PointV1 { x: 1 });
+    assert_eq!(
+        &Point { x: 1, y: 0, z: 0 },
+        binary::deserialize::<BoxAnySerde>(&v1)
+            .unwrap()
+            .downcast_ref::<Point>()
+            .unwrap()
+    );
+    let v2 = tagged_binary("serde_any::tests::common::Point@2", &PointV2 { x: 1, y: 2 });
+    assert_eq!(
+        &Point { x: 1, y: 2, z: 0 },
+        binary::deserialize::<ArcAnySerde>(&v2)
+            .unwrap()
+            .downcast_ref::<Point>()
+            .unwrap()
+    );
+    let v3 = Point { x: 1, y: 2, z: 3 };
+    assert_eq!(
+        tagged_binary("serde_any::tests::common::Point@3", &v3),
+        binary::serialize(&(Box::new(v3) as BoxAnySerde)).unwrap()
+    );
+}
+
+fn tagged_cbor<T: Serialize>(name: &str, value: T) -> Vec<u8> {
+    let mut output = vec![];
+    ciborium::into_writer(&BTreeMap::from([(name, value)]), &mut output).unwrap();
+    output
+}
+
+#[test]
+fn test_cbor_any() {
+    let input = AnyString("abcd".to_string());
+    let encoded = cbor::serialize(&(Box::new(input.clone()) as BoxAnySerde)).unwrap();
+    assert_eq!(
+        tagged_cbor("serde_any::tests::common::AnyString", "abcd"),
+        encoded
+    );
+    assert_eq!(
+        &input,
+        cbor::deserialize::<BoxAnySerde>(&encoded)
+            .unwrap()
+            .deref()
+            .downcast_ref::<AnyString>()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_cbor_unknown() {
+    let input = tagged_cbor("????", vec![1, 2, 3]);
+    let decoded = cbor::deserialize::<ArcAnySerde>(&input).unwrap();
+    let encoded = cbor::serialize(&decoded).unwrap();
+    assert_eq!(input, encoded);
+
+    let input = tagged_cbor(
+        "????",
+        cbor::Value::Tag(32, Box::new(cbor::Value::Text("https://".to_string()))),
+    );
+    let decoded = cbor::deserialize::<BoxAnySerde>(&input).unwrap();
+    assert_eq!(input, cbor::serialize(&decoded).unwrap());
+}
+
+#[test]
+fn test_cbor_big_integers() {
+    for value in [u128::MAX, u64::MAX as u128 + 1, 7] {
+        let encoded = cbor::serialize(&(Box::new(value) as BoxAnySerde)).unwrap();
+        assert_eq!(tagged_cbor("u128", value), encoded);
+        let decoded = cbor::deserialize::<BoxAnySerde>(&encoded).unwrap();
+        assert_eq!(Some(&value), decoded.downcast_ref::<u128>());
+    }
+    let value = i128::MIN;
+    let encoded = cbor::serialize(&(Box::new(value) as BoxAnySerde)).unwrap();
+    assert_eq!(tagged_cbor("i128", value), encoded);
+    let decoded = cbor::deserialize::<BoxAnySerde>(&encoded).unwrap();
+    assert_eq!(Some(&value), decoded.downcast_ref::<i128>());
+}
+
+#[test]
+fn test_cbor_upgrade() {
+    let v1 = tagged_cbor("serde_any::tests::common::Point", PointV1 { x: 1 });
+    assert_eq!(
+        &Point { x: 1, y: 0, z: 0 },
+        cbor::deserialize::<BoxAnySerde>(&v1)
+            .unwrap()
+            .downcast_ref::<Point>()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_transcode() {
+    let input: BoxAnySerde = Box::new(AnyString("abcd".to_string()));
+    let encoded = binary::serialize(&input).unwrap();
+    let transcoded = transcode::binary_to_json(&encoded).unwrap();
+    assert_eq!(json::serialize(&input).unwrap(), transcoded);
+    assert_eq!(encoded, transcode::json_to_binary(&transcoded).unwrap());
+}
+
+#[test]
+fn test_transcode_unregistered() {
+    let hash = TypeTag::new("????").hash;
+    let message = transcode::json_to_binary(r#"{"????":"abcd"}"#)
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("`????`"), "{}", message);
+    assert!(message.contains(&hash.to_string()), "{}", message);
+    let input = tagged_binary("????", &"abcd");
+    let message = transcode::binary_to_json(&input).unwrap_err().to_string();
+    assert!(message.contains(&hash.to_string()), "{}", message);
+}
+
+#[test]
+fn test_trait_object() {
+    let input: Box<dyn Command> = Box::new(Echo("abcd".to_string()));
+    let encoded = json::serialize(&input).unwrap();
+    assert_eq!(r#"{"serde_any::tests::common::Echo":"abcd"}"#, encoded);
+    let output = json::deserialize::<Box<dyn Command>>(encoded.as_bytes()).unwrap();
+    assert_eq!("abcd", output.run());
+    let encoded = binary::serialize(&input).unwrap();
+    let output = binary::deserialize::<Box<dyn Command>>(&encoded).unwrap();
+    assert_eq!("abcd", output.run());
+}
+
+#[test]
+fn test_trait_object_not_implemented() {
+    let message = json::deserialize::<Box<dyn Command>>(br#"{"std::string::String":"abcd"}"#)
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("`std::string::String`"), "{}", message);
+    match COMMANDS.cast_box(Box::new("abcd".to_string())) {
+        Err(NotImplemented { value, .. }) => {
+            assert_eq!(Some(&"abcd".to_string()), value.downcast_ref::<String>())
+        }
+        Ok(_) => panic!("String does not implement Command"),
+    }
+}
+
+/// A sequence whose length is not known until it has been written.
+#[derive(Debug, Eq, PartialEq)]
+struct Evens(Vec<u32>);
+
+impl Serialize for Evens {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().filter(|x| *x % 2 == 0))
+    }
+}
+
+#[test]
+fn test_binary_stream() {
+    let input = (
+        Box::new(AnyString("abcd".to_string())) as BoxAnySerde,
+        Evens(vec![1, 2, 3, 4]),
+        vec![Box::new(Point { x: 1, y: 2, z: 3 }) as BoxAnySerde],
+    );
+    let mut streamed = vec![];
+    binary::serialize_to_writer(&mut streamed, &input).unwrap();
+    assert_eq!(binary::serialize(&input).unwrap(), streamed);
+    let (any, evens, points) = binary::deserialize_from_reader::<
+        _,
+        (BoxAnySerde, Vec<u32>, Vec<BoxAnySerde>),
+    >(&streamed[..])
+    .unwrap();
+    assert_eq!(
+        &AnyString("abcd".to_string()),
+        any.downcast_ref::<AnyString>().unwrap()
+    );
+    assert_eq!(vec![2, 4], evens);
+    assert_eq!(
+        &Point { x: 1, y: 2, z: 3 },
+        points[0].downcast_ref::<Point>().unwrap()
+    );
+}
+
+#[test]
+fn test_binary_stream_unknown() {
+    let input = tagged_binary("????", &"abcd");
+    let decoded = binary::deserialize_from_reader::<_, BoxAnySerde>(&input[..]).unwrap();
+    let mut encoded = vec![];
+    binary::serialize_to_writer(&mut encoded, &decoded).unwrap();
+    assert_eq!(input, encoded);
+    assert!(matches!(
+        binary::deserialize_from_reader::<_, BoxAnySerde>(&input[..input.len() - 1]),
+        Err(binary::Error::BadLength)
+    ));
+}
+
+#[test]
+fn test_registry_tags() {
+    let tags = typetag_static::registry::tags();
+    let any_string = tags
+        .iter()
+        .find(|tag| tag.name == "serde_any::tests::common::AnyString")
+        .unwrap();
+    assert_eq!(TypeTag::new(any_string.name).hash, any_string.hash);
+    assert_eq!(std::any::type_name::<AnyString>(), any_string.type_name);
+    assert_eq!(
+        vec![Format::Json, Format::Binary, Format::Cbor],
+        any_string.formats
+    );
+    let upgrade = tags
+        .iter()
+        .find(|tag| tag.name == "serde_any::tests::common::Point@2")
+        .unwrap();
+    assert_eq!(std::any::type_name::<Point>(), upgrade.type_name);
+}
+
+#[test]
+fn test_validate() {
+    typetag_static::validate().unwrap();
+    let registration = |format, name: &'static str, type_id, type_name| Registration {
+        format,
+        tag: Box::leak(Box::new(TypeTag::new(name))),
+        type_name,
+        type_id,
+        upgrade: false,
+    };
+    let string = (std::any::TypeId::of::<String>(), "String");
+    let u32 = (std::any::TypeId::of::<u32>(), "u32");
+    let conflicts = typetag_static::registry::conflicts(&[
+        registration(Format::Json, "a", string.0, string.1),
+        registration(Format::Json, "b", string.0, string.1),
+        registration(Format::Binary, "a", u32.0, u32.1),
+    ]);
+    assert_eq!(
+        vec![
+            Conflict::DuplicateType {
+                format: Format::Json,
+                type_name: "String"
+            },
+            Conflict::DuplicateName {
+                name: "a",
+                first: "String",
+                second: "u32"
+            },
+        ],
+        conflicts
+    );
+}
+
+#[test]
+fn test_round_trips() { typetag_static::registry::assert_round_trips(); }
+
+#[test]
+fn test_binary_limits() {
+    let options = binary::Options {
+        max_alloc: 16,
+        max_len: 4,
+        max_depth: 2,
+    };
+    let huge = u64::MAX.to_le_bytes();
+    assert!(matches!(
+        options.deserialize::<String>(&huge),
+        Err(binary::Error::LimitExceeded(binary::Limit::Alloc))
+    ));
+    assert!(matches!(
+        binary::deserialize::<String>(&huge),
+        Err(binary::Error::BadLength)
+    ));
+    let strings = binary::serialize(&vec!["0123456789".to_string(); 2]).unwrap();
+    assert!(matches!(
+        options.deserialize::<Vec<String>>(&strings),
+        Err(binary::Error::LimitExceeded(binary::Limit::Alloc))
+    ));
+    let long = binary::serialize(&vec![0u32; 5]).unwrap();
+    assert!(matches!(
+        options.deserialize::<Vec<u32>>(&long),
+        Err(binary::Error::LimitExceeded(binary::Limit::Len))
+    ));
+    let short = binary::serialize(&vec![0u32; 4]).unwrap();
+    assert_eq!(
+        vec![0u32; 4],
+        options.deserialize::<Vec<u32>>(&short).unwrap()
+    );
+    let wide = binary::serialize(&vec![0u64; 3]).unwrap();
+    assert!(matches!(
+        options.deserialize::<Vec<u64>>(&wide),
+        Err(binary::Error::LimitExceeded(binary::Limit::Alloc))
+    ));
+    let map = binary::serialize(&BTreeMap::from([(0u64, 0u64), (1, 1)])).unwrap();
+    assert!(matches!(
+        options.deserialize::<BTreeMap<u64, u64>>(&map),
+        Err(binary::Error::LimitExceeded(binary::Limit::Alloc))
+    ));
+    let deep = binary::serialize(&Some(Some(Some(1u8)))).unwrap();
+    assert!(matches!(
+        options.deserialize::<Option<Option<Option<u8>>>>(&deep),
+        Err(binary::Error::LimitExceeded(binary::Limit::Depth))
+    ));
+    assert_eq!(
+        Some(Some(1u8)),
+        options
+            .deserialize::<Option<Option<u8>>>(&binary::serialize(&Some(Some(1u8))).unwrap())
+            .unwrap()
+    );
+    let unknown = tagged_binary("????", &"0123456789abcdef0123");
+    assert!(matches!(
+        options.deserialize_from_reader::<_, BoxAnySerde>(&unknown[..]),
+        Err(binary::Error::LimitExceeded(binary::Limit::Alloc))
+    ));
+}